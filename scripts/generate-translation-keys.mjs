@@ -0,0 +1,44 @@
+// Writes src/types/translationKeys.ts from public/languages/en_US.json, the
+// single source of translation keys. Runs before `npm run dev` and `npm run build`;
+// pass --check to fail instead of writing when the file is out of date
+import { readFileSync, writeFileSync } from 'node:fs';
+import { dirname, join } from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+const root = join(dirname(fileURLToPath(import.meta.url)), '..');
+const sourcePath = join(root, 'public/languages/en_US.json');
+const outputPath = join(root, 'src/types/translationKeys.ts');
+
+const language = JSON.parse(readFileSync(sourcePath, 'utf8'));
+const keys = Object.keys(language).filter((key) => key !== 'metadata').sort();
+
+const output = [
+  '// Generated by scripts/generate-translation-keys.mjs from public/languages/en_US.json, do not edit',
+  '',
+  'export const TRANSLATION_KEYS = [',
+  ...keys.map((key) => `  '${key}',`),
+  '] as const;',
+  '',
+  'export type TranslationKey = typeof TRANSLATION_KEYS[number];',
+  '',
+  'export const isTranslationKey = (key: string): key is TranslationKey =>',
+  '  (TRANSLATION_KEYS as readonly string[]).includes(key);',
+  '',
+].join('\n');
+
+let current = null;
+try {
+  current = readFileSync(outputPath, 'utf8');
+} catch {
+  // Not generated yet
+}
+
+if (current === output) {
+  process.exit(0);
+}
+if (process.argv.includes('--check')) {
+  console.error('src/types/translationKeys.ts is out of date, run `npm run generate:keys`');
+  process.exit(1);
+}
+// Only touch the file when it changes so the dev server doesn't reload needlessly
+writeFileSync(outputPath, output);
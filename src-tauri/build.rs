@@ -1,3 +1,69 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Bundled English pack, the single source of translation keys and built-in defaults
+const EN_US_PATH: &str = "../public/languages/en_US.json";
+
+// Turn a key like "settings.tab.appearance" into a constant name SETTINGS_TAB_APPEARANCE
+fn const_name(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+// Generate Rust defaults and key constants from en_US.json into OUT_DIR. The
+// frontend's key list comes from scripts/generate-translation-keys.mjs
+fn generate_translations() {
+    println!("cargo:rerun-if-changed={}", EN_US_PATH);
+
+    let source = fs::read_to_string(EN_US_PATH)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", EN_US_PATH, e));
+    let json: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&source)
+        .unwrap_or_else(|e| panic!("Failed to parse {}: {}", EN_US_PATH, e));
+
+    let metadata = json.get("metadata").filter(|metadata| metadata.is_object())
+        .unwrap_or_else(|| panic!("{} has no metadata block", EN_US_PATH));
+    let id = metadata.get("id").and_then(|value| value.as_str())
+        .unwrap_or_else(|| panic!("{} is missing metadata.id", EN_US_PATH));
+
+    let mut translations = Vec::new();
+    let mut const_names = BTreeSet::new();
+    for (key, value) in &json {
+        if key == "metadata" {
+            continue;
+        }
+        let value = value.as_str()
+            .unwrap_or_else(|| panic!("Translation {} in {} is not a string", key, EN_US_PATH));
+        if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+            panic!("Translation key {:?} may only contain letters, digits, '.' and '_'", key);
+        }
+        if !const_names.insert(const_name(key)) {
+            panic!("Translation key {:?} collides with another key as {}", key, const_name(key));
+        }
+        translations.push((key.as_str(), value));
+    }
+
+    // Rust: built-in defaults and key constants
+    let mut rust = String::from("// Generated by build.rs from public/languages/en_US.json, do not edit\n\n");
+    rust.push_str(&format!("pub const DEFAULT_LANGUAGE_ID: &str = {:?};\n", id));
+    rust.push_str(&format!("pub const DEFAULT_LANGUAGE_METADATA: &str = {:?};\n\n", metadata.to_string()));
+    rust.push_str("pub const DEFAULT_TRANSLATIONS: &[(&str, &str)] = &[\n");
+    for (key, value) in &translations {
+        rust.push_str(&format!("    ({:?}, {:?}),\n", key, value));
+    }
+    rust.push_str("];\n\n#[allow(dead_code)]\npub mod keys {\n");
+    for (key, _) in &translations {
+        rust.push_str(&format!("    pub const {}: &str = {:?};\n", const_name(key), key));
+    }
+    rust.push_str("}\n");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set");
+    fs::write(Path::new(&out_dir).join("translations.rs"), rust).expect("Failed to write translations.rs");
+}
+
 fn main() {
+    generate_translations();
     tauri_build::build()
 }
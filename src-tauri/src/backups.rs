@@ -0,0 +1,721 @@
+// World backups. Files are stored once by content hash, gzipped, in
+// <data>/backups/objects, and each backup is a manifest listing the files of
+// a world, <data>/backups/<instance>/<world>/<id>.json. Unchanged files cost
+// nothing in later backups, and objects no manifest refers to are removed
+// after retention is applied
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::instances::Instance;
+use crate::nbt::{self, Tag};
+use crate::saves::{self, SESSION_LOCK};
+use crate::versions::{is_sha1_hex, sha1_hex};
+
+type BackupResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+const DAY: u64 = 24 * 60 * 60;
+const WEEK: u64 = 7 * DAY;
+
+// Reads of a file the game keeps writing to before it is skipped
+const READ_ATTEMPTS: usize = 5;
+const READ_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+// One backup or restore at a time, they share the object store
+static BACKUP_LOCK: Mutex<()> = Mutex::new(());
+
+// Backup settings, part of the launcher settings
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct BackupSettings {
+    // Minutes between backups of worlds in running instances, None or 0 turns them off
+    pub interval_minutes: Option<u32>,
+    // Back up changed worlds when a game exits
+    pub on_exit: bool,
+    // Retention: the newest backups, plus the newest backup of recent days and weeks
+    pub keep_last: usize,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self { interval_minutes: None, on_exit: false, keep_last: 5, keep_daily: 7, keep_weekly: 4 }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    Manual,
+    Scheduled,
+    OnExit,
+    // Taken automatically before a restore overwrites the world
+    PreRestore,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupInfo {
+    pub id: String,
+    pub instance_id: String,
+    pub world: String,
+    pub world_name: String,
+    // Unix timestamp in seconds
+    pub created_at: u64,
+    pub reason: BackupReason,
+    pub file_count: usize,
+    // Size of the world when it was backed up, and of the objects this backup added
+    pub size: u64,
+    pub added_size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct BackupFile {
+    // Relative to the world folder, `/` separated
+    path: String,
+    hash: String,
+    size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(flatten)]
+    info: BackupInfo,
+    files: Vec<BackupFile>,
+}
+
+// Progress of a backup or restore, sent as `backup-progress` events
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupProgress {
+    pub instance_id: String,
+    pub world: String,
+    pub stage: &'static str,
+    pub done: usize,
+    pub total: usize,
+}
+
+pub type Progress<'a> = &'a (dyn Fn(BackupProgress) + Send + Sync);
+
+fn backups_root(data_path: &Path) -> PathBuf {
+    data_path.join("backups")
+}
+
+fn objects_dir(data_path: &Path) -> PathBuf {
+    backups_root(data_path).join("objects")
+}
+
+fn object_path(data_path: &Path, hash: &str) -> PathBuf {
+    objects_dir(data_path).join(&hash[..2]).join(format!("{}.gz", hash))
+}
+
+fn world_backups_dir(data_path: &Path, instance_id: &str, world: &str) -> PathBuf {
+    backups_root(data_path).join(instance_id).join(world)
+}
+
+fn now() -> (u64, u128) {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    (elapsed.as_secs(), elapsed.as_millis())
+}
+
+// Names coming from the frontend must stay a single path component
+fn check_component(value: &str) -> BackupResult<()> {
+    let mut components = Path::new(value).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(()),
+        _ => Err(format!("Invalid name: {}", value).into()),
+    }
+}
+
+// Files of a world, relative paths, without the session lock
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> BackupResult<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if entry.file_name() != SESSION_LOCK {
+            let relative = path.strip_prefix(root)?.components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((relative, path));
+        }
+    }
+    Ok(())
+}
+
+// Read a file the game may be writing to. Size and modification time are
+// compared before and after reading, and the read is retried when they changed
+fn read_stable(path: &Path) -> io::Result<Vec<u8>> {
+    let stamp = |path: &Path| fs::metadata(path).map(|metadata| (metadata.len(), metadata.modified().ok()));
+    for _ in 0..READ_ATTEMPTS {
+        let before = stamp(path)?;
+        let contents = fs::read(path)?;
+        if contents.len() as u64 == before.0 && stamp(path)? == before {
+            return Ok(contents);
+        }
+        thread::sleep(READ_RETRY_DELAY);
+    }
+    Err(io::Error::other("the file kept changing while it was read"))
+}
+
+// Store file contents unless an object with the same hash exists, returns the bytes added
+fn store_object(data_path: &Path, hash: &str, contents: &[u8]) -> BackupResult<u64> {
+    let path = object_path(data_path, hash);
+    if path.exists() {
+        return Ok(0);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("gz.part");
+    let mut encoder = GzEncoder::new(File::create(&tmp_path)?, Compression::default());
+    io::copy(&mut &contents[..], &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(fs::metadata(&path)?.len())
+}
+
+// Read a manifest, rejecting hashes that can't name an object
+fn read_manifest(path: &Path) -> BackupResult<Manifest> {
+    let manifest: Manifest = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if let Some(file) = manifest.files.iter().find(|file| !is_sha1_hex(&file.hash)) {
+        return Err(format!("Invalid hash for {}: {:?}", file.path, file.hash).into());
+    }
+    Ok(manifest)
+}
+
+fn manifests(dir: &Path) -> Vec<(PathBuf, Manifest)> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut manifests: Vec<(PathBuf, Manifest)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| match read_manifest(&path) {
+            Ok(manifest) => Some((path, manifest)),
+            Err(e) => {
+                eprintln!("Ignoring unreadable backup {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect();
+    manifests.sort_by(|(_, a), (_, b)| b.info.created_at.cmp(&a.info.created_at).then_with(|| b.info.id.cmp(&a.info.id)));
+    manifests
+}
+
+fn back_up_locked(
+    data_path: &Path,
+    instance: &Instance,
+    world: &str,
+    reason: BackupReason,
+    progress: Progress,
+) -> BackupResult<BackupInfo> {
+    let world_path = saves::saves_dir(data_path, instance).join(world);
+    if !world_path.join("level.dat").is_file() {
+        return Err(format!("World {} not found in {}", world, instance.name).into());
+    }
+    let (_, level) = nbt::read_gzip(&world_path.join("level.dat"))?;
+    let world_name = level
+        .path(&["Data", "LevelName"])
+        .and_then(Tag::as_str)
+        .unwrap_or(world)
+        .to_string();
+
+    let mut sources = Vec::new();
+    collect_files(&world_path, &world_path, &mut sources)?;
+
+    let report = |done| {
+        progress(BackupProgress {
+            instance_id: instance.id.clone(),
+            world: world.to_string(),
+            stage: "backup",
+            done,
+            total: sources.len(),
+        })
+    };
+    report(0);
+
+    let mut files = Vec::with_capacity(sources.len());
+    let mut added_size = 0;
+    for (index, (relative, path)) in sources.iter().enumerate() {
+        // The game may be saving the world, a file that can't be read is skipped
+        let contents = match read_stable(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("Skipping {:?} in backup: {}", path, e);
+                report(index + 1);
+                continue;
+            }
+        };
+        let hash = sha1_hex(&contents);
+        added_size += store_object(data_path, &hash, &contents)?;
+        files.push(BackupFile { path: relative.clone(), hash, size: contents.len() as u64 });
+        report(index + 1);
+    }
+
+    let (created_at, millis) = now();
+    let info = BackupInfo {
+        id: millis.to_string(),
+        instance_id: instance.id.clone(),
+        world: world.to_string(),
+        world_name,
+        created_at,
+        reason,
+        file_count: files.len(),
+        size: files.iter().map(|file| file.size).sum(),
+        added_size,
+    };
+
+    let dir = world_backups_dir(data_path, &instance.id, world);
+    fs::create_dir_all(&dir)?;
+    let manifest = Manifest { info: info.clone(), files };
+    fs::write(dir.join(format!("{}.json", info.id)), serde_json::to_string_pretty(&manifest)?)?;
+    Ok(info)
+}
+
+// Back up a world, then apply retention to its backups
+pub fn back_up_world(
+    data_path: &Path,
+    instance: &Instance,
+    world: &str,
+    reason: BackupReason,
+    settings: &BackupSettings,
+    progress: Progress,
+) -> BackupResult<BackupInfo> {
+    check_component(world)?;
+    let _guard = BACKUP_LOCK.lock().map_err(|_| "Backup lock is poisoned")?;
+
+    let info = back_up_locked(data_path, instance, world, reason, progress)?;
+    apply_retention(&world_backups_dir(data_path, &instance.id, world), settings)?;
+    collect_garbage(data_path)?;
+    Ok(info)
+}
+
+// Backups to keep: the newest `keep_last`, and the newest one of each of the
+// latest `keep_daily` days and `keep_weekly` weeks that have backups
+fn retained(backups: &[BackupInfo], settings: &BackupSettings) -> HashSet<String> {
+    let mut keep: HashSet<String> = backups.iter().take(settings.keep_last).map(|backup| backup.id.clone()).collect();
+
+    for (period, count) in [(DAY, settings.keep_daily), (WEEK, settings.keep_weekly)] {
+        let mut periods = HashSet::new();
+        for backup in backups {
+            if periods.len() >= count {
+                break;
+            }
+            // Backups are newest first, the first one seen in a period is its newest
+            if periods.insert(backup.created_at / period) {
+                keep.insert(backup.id.clone());
+            }
+        }
+    }
+    keep
+}
+
+fn apply_retention(dir: &Path, settings: &BackupSettings) -> BackupResult<()> {
+    let manifests = manifests(dir);
+    let backups: Vec<BackupInfo> = manifests.iter().map(|(_, manifest)| manifest.info.clone()).collect();
+    let keep = retained(&backups, settings);
+
+    for (path, manifest) in &manifests {
+        if !keep.contains(&manifest.info.id) {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+// Every manifest under the backups root
+fn all_manifests(data_path: &Path) -> Vec<Manifest> {
+    let root = backups_root(data_path);
+    let Ok(instances) = fs::read_dir(&root) else { return Vec::new() };
+
+    instances
+        .flatten()
+        .filter(|entry| entry.path() != objects_dir(data_path))
+        .filter_map(|entry| fs::read_dir(entry.path()).ok())
+        .flat_map(|worlds| worlds.flatten())
+        .flat_map(|world| manifests(&world.path()))
+        .map(|(_, manifest)| manifest)
+        .collect()
+}
+
+// Remove objects no backup refers to
+fn collect_garbage(data_path: &Path) -> BackupResult<()> {
+    let referenced: HashSet<String> = all_manifests(data_path)
+        .into_iter()
+        .flat_map(|manifest| manifest.files.into_iter().map(|file| file.hash))
+        .collect();
+
+    let Ok(buckets) = fs::read_dir(objects_dir(data_path)) else { return Ok(()) };
+    for bucket in buckets.flatten() {
+        for object in fs::read_dir(bucket.path())?.flatten() {
+            let name = object.file_name().to_string_lossy().to_string();
+            let hash = name.trim_end_matches(".gz").trim_end_matches(".gz.part");
+            if !referenced.contains(hash) {
+                fs::remove_file(object.path())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Backups of an instance, or one of its worlds, newest first
+pub fn list_backups(data_path: &Path, instance_id: &str, world: Option<&str>) -> BackupResult<Vec<BackupInfo>> {
+    check_component(instance_id)?;
+    let instance_dir = backups_root(data_path).join(instance_id);
+    let worlds: Vec<PathBuf> = match world {
+        Some(world) => {
+            check_component(world)?;
+            vec![instance_dir.join(world)]
+        },
+        None => fs::read_dir(&instance_dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default(),
+    };
+
+    let mut backups: Vec<BackupInfo> = worlds
+        .iter()
+        .flat_map(|dir| manifests(dir))
+        .map(|(_, manifest)| manifest.info)
+        .collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+    Ok(backups)
+}
+
+fn manifest_path(data_path: &Path, instance_id: &str, world: &str, id: &str) -> BackupResult<PathBuf> {
+    for value in [instance_id, world, id] {
+        check_component(value)?;
+    }
+    Ok(world_backups_dir(data_path, instance_id, world).join(format!("{}.json", id)))
+}
+
+pub fn delete_backup(data_path: &Path, instance_id: &str, world: &str, id: &str) -> BackupResult<()> {
+    let path = manifest_path(data_path, instance_id, world, id)?;
+    let _guard = BACKUP_LOCK.lock().map_err(|_| "Backup lock is poisoned")?;
+    fs::remove_file(path)?;
+    collect_garbage(data_path)
+}
+
+// Replace a world with a backup. The current world is backed up first and kept
+// until the restored copy is complete
+pub fn restore_backup(
+    data_path: &Path,
+    instance: &Instance,
+    world: &str,
+    id: &str,
+    settings: &BackupSettings,
+    progress: Progress,
+) -> BackupResult<()> {
+    let manifest = read_manifest(&manifest_path(data_path, &instance.id, world, id)?)?;
+    let _guard = BACKUP_LOCK.lock().map_err(|_| "Backup lock is poisoned")?;
+
+    let saves_dir = saves::saves_dir(data_path, instance);
+    let world_path = saves_dir.join(world);
+    if world_path.join("level.dat").exists() {
+        back_up_locked(data_path, instance, world, BackupReason::PreRestore, progress)?;
+    }
+
+    let staging = saves_dir.join(format!(".{}.restore", world));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let total = manifest.files.len();
+    let restore = || -> BackupResult<()> {
+        for (index, file) in manifest.files.iter().enumerate() {
+            let relative = Path::new(&file.path);
+            if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+                return Err(format!("Invalid path in backup: {}", file.path).into());
+            }
+            let dest = staging.join(relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let mut contents = Vec::with_capacity(file.size as usize);
+            GzDecoder::new(File::open(object_path(data_path, &file.hash))?).read_to_end(&mut contents)?;
+            if sha1_hex(&contents) != file.hash {
+                return Err(format!("Backup object for {} is corrupt", file.path).into());
+            }
+            fs::write(dest, contents)?;
+            progress(BackupProgress {
+                instance_id: instance.id.clone(),
+                world: world.to_string(),
+                stage: "restore",
+                done: index + 1,
+                total,
+            });
+        }
+        Ok(())
+    };
+    if let Err(e) = restore() {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    // Swap the restored copy in, keeping the old world until it's in place
+    let previous = saves_dir.join(format!(".{}.previous", world));
+    if previous.exists() {
+        fs::remove_dir_all(&previous)?;
+    }
+    let moved_aside = world_path.exists();
+    if moved_aside {
+        fs::rename(&world_path, &previous)?;
+    }
+    if let Err(e) = fs::rename(&staging, &world_path) {
+        if moved_aside {
+            if let Err(undo) = fs::rename(&previous, &world_path) {
+                return Err(format!("Failed to restore {} ({}), the current world was left in {:?} ({})", world, e, previous, undo).into());
+            }
+        }
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e.into());
+    }
+    if moved_aside {
+        fs::remove_dir_all(&previous)?;
+    }
+
+    apply_retention(&world_backups_dir(data_path, &instance.id, world), settings)?;
+    collect_garbage(data_path)
+}
+
+// Back up the worlds of an instance whose level.dat changed since `since` (unix
+// seconds). Scheduled backups run while the game plays, files it writes during
+// the backup are read again, see read_stable
+pub fn back_up_changed_worlds(
+    data_path: &Path,
+    instance: &Instance,
+    since: u64,
+    reason: BackupReason,
+    settings: &BackupSettings,
+    progress: Progress,
+) -> Vec<BackupInfo> {
+    let worlds = saves::list_worlds(data_path, instance).unwrap_or_default();
+    let mut last_backups: BTreeMap<String, u64> = BTreeMap::new();
+    for backup in list_backups(data_path, &instance.id, None).unwrap_or_default() {
+        last_backups.entry(backup.world).or_insert(backup.created_at);
+    }
+
+    worlds
+        .iter()
+        .filter(|world| {
+            let modified = fs::metadata(saves::saves_dir(data_path, instance).join(&world.folder).join("level.dat"))
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|modified| modified.as_secs())
+                .unwrap_or_default();
+            let last_backup = last_backups.get(&world.folder).copied().unwrap_or_default();
+            modified >= since && modified >= last_backup
+        })
+        .filter_map(|world| {
+            back_up_world(data_path, instance, &world.folder, reason, settings, progress)
+                .map_err(|e| eprintln!("Failed to back up {} of {}: {}", world.folder, instance.id, e))
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_data(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("intelligence-backups-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn instance() -> Instance {
+        serde_json::from_value(serde_json::json!({ "id": "a", "name": "A", "game_version": "1.20.1" })).unwrap()
+    }
+
+    // A world with a level.dat and one region file
+    fn create_world(data_path: &Path, region: &str) -> PathBuf {
+        let world = saves::saves_dir(data_path, &instance()).join("World");
+        fs::create_dir_all(world.join("region")).unwrap();
+        let data = Tag::Compound(vec![("LevelName".to_string(), Tag::String("My World".to_string()))].into_iter().collect());
+        let root = Tag::Compound(vec![("Data".to_string(), data)].into_iter().collect());
+        nbt::write_gzip(&world.join("level.dat"), "", &root).unwrap();
+        fs::write(world.join("region/r.0.0.mca"), region).unwrap();
+        world
+    }
+
+    fn backup(id: &str, created_at: u64) -> BackupInfo {
+        BackupInfo {
+            id: id.to_string(),
+            instance_id: "a".to_string(),
+            world: "World".to_string(),
+            world_name: "My World".to_string(),
+            created_at,
+            reason: BackupReason::Scheduled,
+            file_count: 0,
+            size: 0,
+            added_size: 0,
+        }
+    }
+
+    fn object_count(data_path: &Path) -> usize {
+        fs::read_dir(objects_dir(data_path))
+            .map(|buckets| buckets.flatten().flat_map(|bucket| fs::read_dir(bucket.path()).unwrap().flatten()).count())
+            .unwrap_or_default()
+    }
+
+    fn no_progress(_: BackupProgress) {}
+
+    #[test]
+    fn retention_keeps_last_daily_and_weekly() {
+        // Newest first: three today, one yesterday, one two days ago, one last
+        // week. Today is midday of the fourth day of a week
+        let now = 100 * WEEK + 3 * DAY + DAY / 2;
+        let backups = vec![
+            backup("today-3", now - 60),
+            backup("today-2", now - 120),
+            backup("today-1", now - 180),
+            backup("yesterday", now - DAY),
+            backup("two-days", now - 2 * DAY),
+            backup("last-week", now - WEEK - DAY),
+        ];
+
+        let keep = |keep_last, keep_daily, keep_weekly| {
+            let settings = BackupSettings { keep_last, keep_daily, keep_weekly, ..Default::default() };
+            let mut kept: Vec<String> = retained(&backups, &settings).into_iter().collect();
+            kept.sort();
+            kept
+        };
+
+        assert_eq!(keep(2, 0, 0), vec!["today-2", "today-3"]);
+        assert_eq!(keep(1, 2, 0), vec!["today-3", "yesterday"]);
+        assert_eq!(keep(0, 0, 2), vec!["last-week", "today-3"]);
+        assert_eq!(keep(1, 3, 2), vec!["last-week", "today-3", "two-days", "yesterday"]);
+        assert_eq!(keep(10, 0, 0).len(), backups.len());
+    }
+
+    #[test]
+    fn objects_are_stored_once() {
+        let data_path = temp_data("dedup");
+        let hash = sha1_hex(b"region");
+
+        assert!(store_object(&data_path, &hash, b"region").unwrap() > 0);
+        assert_eq!(store_object(&data_path, &hash, b"region").unwrap(), 0);
+        assert_eq!(object_count(&data_path), 1);
+
+        let mut contents = Vec::new();
+        GzDecoder::new(File::open(object_path(&data_path, &hash)).unwrap()).read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, b"region");
+        fs::remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn backups_share_objects_and_garbage_is_collected() {
+        let data_path = temp_data("gc");
+        let world = create_world(&data_path, "v1");
+        let settings = BackupSettings::default();
+
+        let first = back_up_world(&data_path, &instance(), "World", BackupReason::Manual, &settings, &no_progress).unwrap();
+        assert_eq!(first.file_count, 2);
+        assert_eq!(first.world_name, "My World");
+        assert_eq!(object_count(&data_path), 2);
+
+        // Only the changed region file adds an object. Backup ids are millisecond timestamps
+        fs::write(world.join("region/r.0.0.mca"), "v2").unwrap();
+        thread::sleep(Duration::from_millis(5));
+        let second = back_up_world(&data_path, &instance(), "World", BackupReason::Manual, &settings, &no_progress).unwrap();
+        assert_eq!(object_count(&data_path), 3);
+        assert!(second.added_size > 0);
+
+        // An object no manifest refers to is removed
+        store_object(&data_path, &sha1_hex(b"orphan"), b"orphan").unwrap();
+        collect_garbage(&data_path).unwrap();
+        assert_eq!(object_count(&data_path), 3);
+
+        // The level.dat object stays while the second backup uses it
+        delete_backup(&data_path, "a", "World", &first.id).unwrap();
+        assert_eq!(object_count(&data_path), 2);
+        assert!(!object_path(&data_path, &sha1_hex(b"v1")).exists());
+        assert!(object_path(&data_path, &sha1_hex(b"v2")).exists());
+        fs::remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn restore_replaces_world_and_reports_progress() {
+        let data_path = temp_data("restore");
+        let world = create_world(&data_path, "v1");
+        let settings = BackupSettings::default();
+
+        let backup = back_up_world(&data_path, &instance(), "World", BackupReason::Manual, &settings, &no_progress).unwrap();
+        fs::write(world.join("region/r.0.0.mca"), "v2").unwrap();
+        fs::write(world.join("added.dat"), "new").unwrap();
+        // Backup ids are millisecond timestamps
+        thread::sleep(Duration::from_millis(5));
+
+        let reports = Mutex::new(Vec::new());
+        let progress = |progress: BackupProgress| reports.lock().unwrap().push((progress.stage, progress.done, progress.total));
+        restore_backup(&data_path, &instance(), "World", &backup.id, &settings, &progress).unwrap();
+
+        assert_eq!(fs::read_to_string(world.join("region/r.0.0.mca")).unwrap(), "v1");
+        assert!(!world.join("added.dat").exists());
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.iter().rfind(|(stage, _, _)| *stage == "backup"), Some(&("backup", 3, 3)));
+        assert_eq!(reports.last(), Some(&("restore", 2, 2)));
+
+        // The world as it was before restoring was backed up first
+        let backups = list_backups(&data_path, "a", Some("World")).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].reason, BackupReason::PreRestore);
+        fs::remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn restore_rejects_paths_outside_the_world() {
+        let data_path = temp_data("traversal");
+        let hash = sha1_hex(b"evil");
+        store_object(&data_path, &hash, b"evil").unwrap();
+
+        let dir = world_backups_dir(&data_path, "a", "World");
+        fs::create_dir_all(&dir).unwrap();
+        for (id, path) in [("1", "../../escaped.txt"), ("2", "/tmp/escaped.txt")] {
+            let manifest = Manifest {
+                info: backup(id, 1),
+                files: vec![BackupFile { path: path.to_string(), hash: hash.clone(), size: 4 }],
+            };
+            fs::write(dir.join(format!("{}.json", id)), serde_json::to_string(&manifest).unwrap()).unwrap();
+
+            let error = restore_backup(&data_path, &instance(), "World", id, &BackupSettings::default(), &no_progress).unwrap_err();
+            assert!(error.to_string().starts_with("Invalid path in backup"), "{}", error);
+        }
+        assert!(!saves::saves_dir(&data_path, &instance()).join("World").exists());
+
+        assert!(list_backups(&data_path, "../a", None).is_err());
+        assert!(restore_backup(&data_path, &instance(), "../World", "1", &BackupSettings::default(), &no_progress).is_err());
+        fs::remove_dir_all(data_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_files_still_count_towards_progress() {
+        let data_path = temp_data("progress");
+        let world = create_world(&data_path, "v1");
+        // A dangling symlink is listed but can't be read
+        std::os::unix::fs::symlink(world.join("missing"), world.join("region/r.1.0.mca")).unwrap();
+
+        let reports = Mutex::new(Vec::new());
+        let progress = |progress: BackupProgress| reports.lock().unwrap().push((progress.done, progress.total));
+        let info = back_up_world(&data_path, &instance(), "World", BackupReason::Manual, &BackupSettings::default(), &progress).unwrap();
+
+        let (done, total) = *reports.lock().unwrap().last().unwrap();
+        assert_eq!(done, total);
+        assert_eq!(info.file_count, total - 1);
+        fs::remove_dir_all(data_path).unwrap();
+    }
+}
@@ -0,0 +1,175 @@
+// Headless command line interface. When the binary is started with a known
+// subcommand it runs it without opening a window:
+//
+//   intelligence list
+//   intelligence install <version> [--instance <name>]
+//   intelligence launch <instance> [--username <name>]
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use crate::{instances, jvm, launcher, paths, read_settings_file, single_instance, validate_username, versions, Settings};
+
+const USAGE: &str = "Usage:
+  intelligence list                                   List instances
+  intelligence install <version> [--instance <name>]  Install a Minecraft version (or latest, snapshot)
+                                                      and optionally create an instance for it
+  intelligence launch <instance> [--username <name>]  Launch an instance by id or name and wait for it to exit
+  intelligence help                                   Show this message";
+
+type CliResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// Settings as stored on disk, the CLI never writes them
+fn load_settings() -> Settings {
+    let path = match paths::resolve() {
+        Ok(dirs) => dirs.config.join("settings.json"),
+        Err(_) => return Settings::default(),
+    };
+    if !path.exists() {
+        return Settings::default();
+    }
+    match read_settings_file(&path) {
+        Ok((settings, reset_fields)) => {
+            if !reset_fields.is_empty() {
+                eprintln!("Ignoring invalid settings in {:?}: {}", path, reset_fields.join(", "));
+            }
+            settings
+        },
+        Err(e) => {
+            eprintln!("Ignoring unreadable settings file {:?}: {}", path, e);
+            Settings::default()
+        }
+    }
+}
+
+// Game data directory, honoring a migrated location from settings
+fn data_path(settings: &Settings) -> CliResult<PathBuf> {
+    let path = match &settings.data_directory {
+        Some(path) => path.clone(),
+        None => paths::resolve().map_err(|e| e.to_string())?.data,
+    };
+    std::fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+// Value of a `--name value` option
+pub(crate) fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+fn list() -> CliResult<i32> {
+    let settings = load_settings();
+    let instances = instances::list_instances(&data_path(&settings)?)?;
+
+    if instances.is_empty() {
+        println!("No instances. Create one with: intelligence install <version> --instance <name>");
+        return Ok(0);
+    }
+
+    for instance in instances {
+        let loader = instance.loader.map(|loader| format!(" ({})", loader)).unwrap_or_default();
+        println!("{}\t{}\t{}{}", instance.id, instance.name, instance.game_version, loader);
+    }
+    Ok(0)
+}
+
+fn install(version: &str, instance_name: Option<&str>) -> CliResult<i32> {
+    let settings = load_settings();
+    let data_path = data_path(&settings)?;
+
+    let progress = |progress: versions::InstallProgress| {
+        eprint!("\r{} {}: {}/{}", progress.version, progress.stage, progress.done, progress.total);
+        if progress.done == progress.total {
+            eprintln!();
+        }
+        let _ = std::io::stderr().flush();
+    };
+    let installed = tauri::async_runtime::block_on(versions::install_version(&data_path, version, &progress))?;
+    println!("Installed {}", installed);
+
+    if let Some(name) = instance_name {
+        let instance = instances::create_instance(&data_path, name, &installed)?;
+        println!("Created instance {} ({})", instance.name, instance.id);
+    }
+    Ok(0)
+}
+
+fn launch(instance: &str, username: Option<&str>) -> CliResult<i32> {
+    // No launcher was running to forward to. Waiting for the game here must not
+    // keep a launcher window opened meanwhile from taking over requests
+    single_instance::release();
+
+    let settings = load_settings();
+    let data_path = data_path(&settings)?;
+    let username = username.unwrap_or(&settings.username);
+    validate_username(username)?;
+
+    let mut instance = instances::find_instance(&data_path, instance)?;
+    println!("Launching {} ({}) as {}", instance.name, instance.game_version, username);
+
+    let options = launcher::LaunchOptions { username: username.to_string(), server: None };
+    let mut child = launcher::launch(&data_path, &mut instance, &options, |command| {
+        command.stdin(Stdio::null());
+    })?;
+    let status = child.wait()?;
+    jvm::run_post_exit_hook(&data_path, &instance);
+    Ok(status.code().unwrap_or(1))
+}
+
+// Run a CLI command. Returns the process exit code, or None when the arguments
+// are not a CLI command and the app should start normally
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.first()?.as_str();
+    let rest = &args[1..];
+
+    let result = match (command, rest.first()) {
+        ("list", _) => list(),
+        ("install", Some(version)) => install(version, option_value(rest, "--instance")),
+        ("launch", Some(instance)) => launch(instance, option_value(rest, "--username")),
+        ("help" | "--help" | "-h", _) => {
+            println!("{}", USAGE);
+            Ok(0)
+        },
+        ("install" | "launch", None) => {
+            eprintln!("{}", USAGE);
+            Ok(2)
+        },
+        _ => return None,
+    };
+
+    Some(result.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        1
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn option_values() {
+        let rest = args(&["survival", "--username", "Notch", "--instance"]);
+        assert_eq!(option_value(&rest, "--username"), Some("Notch"));
+        assert_eq!(option_value(&rest, "--instance"), None);
+        assert_eq!(option_value(&rest, "--server"), None);
+    }
+
+    #[test]
+    fn only_known_commands_are_run() {
+        assert_eq!(run(&[]), None);
+        assert_eq!(run(&args(&["intelligence://launch/survival"])), None);
+        assert_eq!(run(&args(&["--minimized"])), None);
+        assert_eq!(run(&args(&["help"])), Some(0));
+        assert_eq!(run(&args(&["launch"])), Some(2));
+        assert_eq!(run(&args(&["install"])), Some(2));
+    }
+}
@@ -0,0 +1,446 @@
+// Crash analysis for games that exited abnormally. Finds the report written
+// during the session (crash-reports/crash-*.txt from Minecraft, hs_err_pid*.log
+// from a JVM fatal error), pulls out the interesting parts and matches them
+// against known causes so the UI can say what to do about it
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use serde::Serialize;
+
+// Stack frames kept in the summary, the rest is in the file
+const MAX_STACK_LINES: usize = 40;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    CrashReport,
+    JvmFatalError,
+}
+
+// Known crash causes, the UI has a translated hint for each (crash.cause.<name>)
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashCause {
+    WrongJava,
+    OutOfMemory,
+    MissingDependency,
+    MixinFailure,
+    DuplicateMods,
+}
+
+// A cause and the report line that gave it away
+#[derive(Debug, Serialize, Clone)]
+pub struct MatchedCause {
+    pub cause: CrashCause,
+    pub line: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct JvmDetails {
+    pub java_version: Option<String>,
+    pub vm: Option<String>,
+    pub flags: Option<String>,
+    pub memory: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CrashSummary {
+    pub kind: CrashKind,
+    pub path: PathBuf,
+    pub time: Option<String>,
+    pub description: Option<String>,
+    // First line of the stack trace, or the problematic frame of a JVM crash
+    pub exception: Option<String>,
+    pub stack_trace: Vec<String>,
+    pub suspected_mods: Vec<String>,
+    pub jvm: JvmDetails,
+    pub causes: Vec<MatchedCause>,
+}
+
+// Lowercase fragments identifying a cause. A line that also contains one of
+// the `unless` fragments doesn't count
+struct Rule {
+    cause: CrashCause,
+    patterns: &'static [&'static str],
+    unless: &'static [&'static str],
+}
+
+// Checked in order, patterns are the wording of the JVM, the loaders and
+// Mixin rather than plain words that also show up in ordinary log lines
+const RULES: &[Rule] = &[
+    Rule {
+        cause: CrashCause::WrongJava,
+        patterns: &[
+            "unsupportedclassversionerror",
+            "compiled by a more recent version of the java runtime",
+            "class file version",
+            "incompatible java version",
+        ],
+        unless: &[],
+    },
+    Rule {
+        cause: CrashCause::OutOfMemory,
+        patterns: &[
+            "java.lang.outofmemoryerror",
+            "insufficient memory for the java runtime environment",
+            "native memory allocation (malloc) failed",
+            "native memory allocation (mmap) failed",
+        ],
+        unless: &[],
+    },
+    Rule {
+        cause: CrashCause::DuplicateMods,
+        patterns: &["duplicatemodsfoundexception", "found duplicate mods", "duplicate mods found"],
+        unless: &[],
+    },
+    Rule {
+        cause: CrashCause::MissingDependency,
+        patterns: &[
+            "which is missing",
+            "missing or unsupported mandatory dependencies",
+            "missingmodsexception",
+            "java.lang.noclassdeffounderror:",
+        ],
+        // A class whose static initializer failed, or a game class a mod for
+        // another Minecraft version expects, is not a missing mod
+        unless: &["could not initialize class", "net/minecraft/", "net.minecraft.", "com/mojang/", "com.mojang."],
+    },
+    Rule {
+        cause: CrashCause::MixinFailure,
+        patterns: &[
+            "mixinapplyerror",
+            "mixin apply failed",
+            "mixintransformererror",
+            "invalidinjectionexception",
+            "org.spongepowered.asm.mixin.transformer.throwables",
+        ],
+        unless: &[],
+    },
+];
+
+// Match the text against the rule set, one entry per cause
+pub fn match_causes(text: &str) -> Vec<MatchedCause> {
+    let lines: Vec<(&str, String)> = text.lines().map(|line| (line.trim(), line.to_lowercase())).collect();
+
+    RULES
+        .iter()
+        .filter_map(|rule| {
+            lines
+                .iter()
+                .find(|(_, lower)| {
+                    rule.patterns.iter().any(|pattern| lower.contains(pattern))
+                        && !rule.unless.iter().any(|exception| lower.contains(exception))
+                })
+                .map(|(line, _)| MatchedCause { cause: rule.cause, line: line.trim_start_matches('#').trim().to_string() })
+        })
+        .collect()
+}
+
+// Value of a `Key: value` line, wherever it is indented
+fn field(lines: &[&str], key: &str) -> Option<String> {
+    lines
+        .iter()
+        .find_map(|line| line.trim_start_matches(['#', ' ', '\t']).strip_prefix(key)?.strip_prefix(':').map(str::trim))
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+}
+
+// Mods listed under "Suspected Mod(s):", inline or one per indented line
+fn suspected_mods(lines: &[&str]) -> Vec<String> {
+    let Some(index) = lines.iter().position(|line| line.trim_start().starts_with("Suspected Mod")) else {
+        return Vec::new();
+    };
+
+    let mut mods = Vec::new();
+    let inline = lines[index].split_once(':').map(|(_, value)| value.trim()).unwrap_or_default();
+    if !inline.is_empty() && !inline.eq_ignore_ascii_case("none") {
+        mods.push(inline.to_string());
+    }
+
+    // Entries share the indent of the first one, their details are nested deeper
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let heading_indent = indent(lines[index]);
+    let mut entry_indent = None;
+    for line in &lines[index + 1..] {
+        if line.trim().is_empty() || indent(line) <= heading_indent {
+            break;
+        }
+        if *entry_indent.get_or_insert(indent(line)) == indent(line) {
+            mods.push(line.trim().to_string());
+        }
+    }
+    mods
+}
+
+fn parse_crash_report(path: &Path, text: &str) -> CrashSummary {
+    let lines: Vec<&str> = text.lines().collect();
+
+    // The stack trace follows the description, up to the first blank line
+    let stack_trace: Vec<String> = lines
+        .iter()
+        .position(|line| line.starts_with("Description:"))
+        .map(|index| {
+            lines[index + 1..]
+                .iter()
+                .skip_while(|line| line.trim().is_empty())
+                .take_while(|line| !line.trim().is_empty())
+                .take(MAX_STACK_LINES)
+                .map(|line| line.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    CrashSummary {
+        kind: CrashKind::CrashReport,
+        path: path.to_path_buf(),
+        time: field(&lines, "Time"),
+        description: field(&lines, "Description"),
+        exception: stack_trace.first().cloned(),
+        suspected_mods: suspected_mods(&lines),
+        jvm: JvmDetails {
+            java_version: field(&lines, "Java Version"),
+            vm: field(&lines, "Java VM Version"),
+            flags: field(&lines, "JVM Flags"),
+            memory: field(&lines, "Memory"),
+        },
+        causes: match_causes(text),
+        stack_trace,
+    }
+}
+
+fn parse_jvm_error(path: &Path, text: &str) -> CrashSummary {
+    let lines: Vec<&str> = text.lines().collect();
+
+    // The header is a block of `#` lines, the first one after the banner says what happened
+    let header: Vec<&str> = lines
+        .iter()
+        .take_while(|line| line.starts_with('#'))
+        .map(|line| line.trim_start_matches('#').trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let description = header
+        .iter()
+        .find(|line| !line.starts_with("A fatal error has been detected"))
+        .map(|line| line.to_string());
+    let exception = header
+        .iter()
+        .position(|line| line.starts_with("Problematic frame"))
+        .and_then(|index| header.get(index + 1))
+        .map(|line| line.to_string());
+
+    let stack_trace = lines
+        .iter()
+        .position(|line| line.starts_with("Native frames:") || line.starts_with("Java frames:"))
+        .map(|index| {
+            lines[index + 1..]
+                .iter()
+                .take_while(|line| !line.trim().is_empty())
+                .take(MAX_STACK_LINES)
+                .map(|line| line.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    CrashSummary {
+        kind: CrashKind::JvmFatalError,
+        path: path.to_path_buf(),
+        time: field(&lines, "Time").or_else(|| field(&lines, "time")),
+        description,
+        exception,
+        suspected_mods: Vec::new(),
+        jvm: JvmDetails {
+            java_version: field(&lines, "JRE version"),
+            vm: field(&lines, "Java VM"),
+            flags: field(&lines, "jvm_args"),
+            memory: field(&lines, "Memory"),
+        },
+        causes: match_causes(text),
+        stack_trace,
+    }
+}
+
+// Parse a crash report or JVM error log
+pub fn analyze_file(path: &Path) -> Result<CrashSummary, Box<dyn Error + Send + Sync>> {
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let is_jvm_error = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with("hs_err_pid"));
+
+    Ok(if is_jvm_error { parse_jvm_error(path, &text) } else { parse_crash_report(path, &text) })
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+// Newest crash report or JVM error log written since `since` (unix seconds)
+pub fn find_report(game_dir: &Path, since: u64) -> Option<PathBuf> {
+    let crash_reports = fs::read_dir(game_dir.join("crash-reports"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"));
+    let jvm_errors = fs::read_dir(game_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("hs_err_pid") && name.ends_with(".log")
+        });
+
+    crash_reports
+        .chain(jvm_errors)
+        .filter_map(|path| modified_secs(&path).filter(|modified| *modified >= since).map(|modified| (modified, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+// Analyze the report of a session started at `since`, if it left one
+pub fn analyze_session(game_dir: &Path, since: u64) -> Option<CrashSummary> {
+    let path = find_report(game_dir, since)?;
+    analyze_file(&path)
+        .map_err(|e| eprintln!("Failed to read crash report {:?}: {}", path, e))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRASH_REPORT: &str = "---- Minecraft Crash Report ----
+// Who set us up the TNT?
+
+Time: 2024-05-01 12:00:00
+Description: Initializing game
+
+java.lang.NoClassDefFoundError: me/jellysquid/mods/sodium/client/SodiumClientMod
+	at net.example.addon.AddonMod.onInitialize(AddonMod.java:20)
+	at net.fabricmc.loader.impl.FabricLoaderImpl.invokeEntrypoints(FabricLoaderImpl.java:384)
+Caused by: java.lang.ClassNotFoundException: me.jellysquid.mods.sodium.client.SodiumClientMod
+	at java.base/jdk.internal.loader.BuiltinClassLoader.loadClass(BuiltinClassLoader.java:641)
+
+
+A detailed walkthrough of the error, its code path and all known details is as follows:
+---------------------------------------------------------------------------------------
+
+-- Head --
+Thread: Render thread
+Stacktrace:
+	at net.example.addon.AddonMod.onInitialize(AddonMod.java:20)
+	Suspected Mods: 
+		Addon (addon)
+			Issue tracker URL: https://example.com/addon/issues
+		Fabric Loader (fabricloader)
+
+-- System Details --
+Details:
+	Minecraft Version: 1.20.1
+	Java Version: 17.0.8, Eclipse Adoptium
+	Java VM Version: OpenJDK 64-Bit Server VM (mixed mode, sharing), Eclipse Adoptium
+	Memory: 123456789 bytes (117 MiB) / 536870912 bytes (512 MiB) up to 2147483648 bytes (2048 MiB)
+	JVM Flags: 2 total; -Xmx2G -Xms1G
+	Sodium Memory: chunk arena never ran out of memory
+";
+
+    const HS_ERR: &str = "#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffb1c2d3e4f, pid=1234, tid=5678
+#
+# JRE version: OpenJDK Runtime Environment Temurin-17.0.8+7 (17.0.8+7) (build 17.0.8+7)
+# Java VM: OpenJDK 64-Bit Server VM Temurin-17.0.8+7 (17.0.8+7, mixed mode, tiered, g1 gc, windows-amd64)
+# Problematic frame:
+# C  [atio6axx.dll+0x1a3e4f]
+#
+# If you would like to submit a bug report, please visit:
+#   https://github.com/adoptium/adoptium-support/issues
+#
+
+---------------  S U M M A R Y ------------
+
+Command Line: -Xmx4G net.fabricmc.loader.impl.launch.knot.KnotClient
+
+Time: Mon May  1 12:00:00 2024 W. Europe Summer Time elapsed time: 12.345 seconds (0d 0h 0m 12s)
+
+---------------  T H R E A D  ---------------
+
+Native frames: (J=compiled Java code, j=interpreted, Vv=VM code, C=native code)
+C  [atio6axx.dll+0x1a3e4f]
+C  [atio6axx.dll+0x1a2b10]
+j  org.lwjgl.opengl.GL11C.glDrawElements(IJJ)V+0
+
+jvm_args: -Xmx4G -Xms1G
+";
+
+    fn causes(text: &str) -> Vec<CrashCause> {
+        match_causes(text).into_iter().map(|matched| matched.cause).collect()
+    }
+
+    #[test]
+    fn crash_report() {
+        let summary = parse_crash_report(Path::new("crash-2024-05-01_12.00.00-client.txt"), CRASH_REPORT);
+
+        assert_eq!(summary.kind, CrashKind::CrashReport);
+        assert_eq!(summary.time.as_deref(), Some("2024-05-01 12:00:00"));
+        assert_eq!(summary.description.as_deref(), Some("Initializing game"));
+        assert_eq!(summary.exception.as_deref(), Some("java.lang.NoClassDefFoundError: me/jellysquid/mods/sodium/client/SodiumClientMod"));
+        assert_eq!(summary.stack_trace.len(), 5);
+        assert_eq!(summary.stack_trace[3], "Caused by: java.lang.ClassNotFoundException: me.jellysquid.mods.sodium.client.SodiumClientMod");
+        assert_eq!(summary.suspected_mods, vec!["Addon (addon)", "Fabric Loader (fabricloader)"]);
+        assert_eq!(summary.jvm.java_version.as_deref(), Some("17.0.8, Eclipse Adoptium"));
+        assert_eq!(summary.jvm.vm.as_deref(), Some("OpenJDK 64-Bit Server VM (mixed mode, sharing), Eclipse Adoptium"));
+        assert_eq!(summary.jvm.flags.as_deref(), Some("2 total; -Xmx2G -Xms1G"));
+        assert!(summary.jvm.memory.as_deref().is_some_and(|memory| memory.starts_with("123456789 bytes")));
+
+        // Only the missing class, not the memory line mentioning "out of memory"
+        assert_eq!(summary.causes.len(), 1);
+        assert_eq!(summary.causes[0].cause, CrashCause::MissingDependency);
+        assert_eq!(summary.causes[0].line, summary.exception.unwrap());
+    }
+
+    #[test]
+    fn jvm_fatal_error() {
+        let summary = parse_jvm_error(Path::new("hs_err_pid1234.log"), HS_ERR);
+
+        assert_eq!(summary.kind, CrashKind::JvmFatalError);
+        assert_eq!(
+            summary.description.as_deref(),
+            Some("EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffb1c2d3e4f, pid=1234, tid=5678")
+        );
+        assert_eq!(summary.exception.as_deref(), Some("C  [atio6axx.dll+0x1a3e4f]"));
+        assert_eq!(
+            summary.stack_trace,
+            vec!["C  [atio6axx.dll+0x1a3e4f]", "C  [atio6axx.dll+0x1a2b10]", "j  org.lwjgl.opengl.GL11C.glDrawElements(IJJ)V+0"]
+        );
+        assert!(summary.time.as_deref().is_some_and(|time| time.starts_with("Mon May  1 12:00:00 2024")));
+        assert_eq!(summary.jvm.java_version.as_deref(), Some("OpenJDK Runtime Environment Temurin-17.0.8+7 (17.0.8+7) (build 17.0.8+7)"));
+        assert!(summary.jvm.vm.as_deref().is_some_and(|vm| vm.starts_with("OpenJDK 64-Bit Server VM")));
+        assert_eq!(summary.jvm.flags.as_deref(), Some("-Xmx4G -Xms1G"));
+        assert!(summary.suspected_mods.is_empty());
+        assert!(summary.causes.is_empty());
+    }
+
+    #[test]
+    fn cause_patterns() {
+        assert_eq!(causes("java.lang.OutOfMemoryError: Java heap space"), vec![CrashCause::OutOfMemory]);
+        assert_eq!(causes("# Native memory allocation (mmap) failed to map 65536 bytes"), vec![CrashCause::OutOfMemory]);
+        assert_eq!(causes("[Worker-Main-3/WARN]: Texture atlas ran out of memory budget, shrinking"), vec![]);
+
+        assert_eq!(causes("java.lang.NoClassDefFoundError: Could not initialize class com.example.Foo"), vec![]);
+        assert_eq!(causes("java.lang.NoClassDefFoundError: net/minecraft/class_1234"), vec![]);
+        assert_eq!(causes("Mod 'Addon' (addon) requires any version of fabric-api, which is missing!"), vec![CrashCause::MissingDependency]);
+
+        assert_eq!(causes("Loading duplicate model for block stone"), vec![]);
+        assert_eq!(causes("net.minecraftforge.fml.DuplicateModsFoundException"), vec![CrashCause::DuplicateMods]);
+        assert_eq!(
+            causes("java.lang.UnsupportedClassVersionError: Foo has been compiled by a more recent version of the Java Runtime\norg.spongepowered.asm.mixin.transformer.throwables.MixinTransformerError"),
+            vec![CrashCause::WrongJava, CrashCause::MixinFailure]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+// intelligence:// links and per-instance desktop shortcuts.
+//
+//   intelligence://launch/<instance>                  launch an instance
+//   intelligence://join/<host[:port]>?instance=<id>   launch and join a server
+//   intelligence://modpack?url=<url>                  install a modpack from a URL
+//
+// On Linux and Windows links arrive as a command line argument, macOS delivers
+// them through RunEvent::Opened (the scheme is declared in Info.plist)
+use std::error::Error;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::path::PathBuf;
+use reqwest::Url;
+use serde::Serialize;
+
+use crate::instances::{self, Instance};
+
+pub const SCHEME: &str = "intelligence";
+
+// A parsed intelligence:// link
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLink {
+    Launch { instance: String },
+    JoinServer { address: String, instance: Option<String> },
+    InstallModpack { url: String },
+}
+
+// A link that could not be opened, sent to the frontend as `deep-link-failed`
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DeepLinkFailed {
+    pub link: String,
+    pub message: String,
+}
+
+// Decode %XX escapes in a path segment
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            },
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Parse an intelligence:// link
+pub fn parse(link: &str) -> Result<DeepLink, String> {
+    let url = Url::parse(link.trim()).map_err(|e| format!("Invalid link {}: {}", link, e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("Not an {}:// link: {}", SCHEME, link));
+    }
+
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let target = url
+        .path_segments()
+        .and_then(|mut segments| segments.find(|segment| !segment.is_empty()))
+        .map(percent_decode);
+
+    // Instance ids are directory names, anything else could point outside instances/
+    let instance_id = |id: String| {
+        if instances::is_valid_id(&id) {
+            Ok(id)
+        } else {
+            Err(format!("Invalid instance id in link: {}", id))
+        }
+    };
+
+    match url.host_str().unwrap_or_default() {
+        "launch" => {
+            let instance = target.or_else(|| query("instance")).ok_or("Launch link has no instance")?;
+            Ok(DeepLink::Launch { instance: instance_id(instance)? })
+        },
+        "join" => {
+            let address = target.or_else(|| query("address")).ok_or("Join link has no server address")?;
+            let instance = query("instance").map(instance_id).transpose()?;
+            Ok(DeepLink::JoinServer { address, instance })
+        },
+        "modpack" => {
+            let modpack_url = query("url").ok_or("Modpack link has no url")?;
+            if !modpack_url.starts_with("https://") && !modpack_url.starts_with("http://") {
+                return Err(format!("Unsupported modpack URL: {}", modpack_url));
+            }
+            Ok(DeepLink::InstallModpack { url: modpack_url })
+        },
+        other => Err(format!("Unknown link action: {}", other)),
+    }
+}
+
+// Find a link among command line arguments
+pub fn from_args(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let prefix = format!("{}://", SCHEME);
+    args.into_iter().find(|arg| arg.starts_with(&prefix))
+}
+
+// Link that launches an instance
+#[cfg(target_os = "linux")]
+fn launch_link(instance_id: &str) -> String {
+    format!("{}://launch/{}", SCHEME, instance_id)
+}
+
+// Quote a value for an Exec key of a desktop entry
+#[cfg(target_os = "linux")]
+fn quote_exec_arg(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('`', "\\`")
+        .replace('$', "\\$");
+    format!("\"{}\"", escaped.replace('%', "%%"))
+}
+
+// Desktop entry that launches an instance through the launcher
+#[cfg(target_os = "linux")]
+fn instance_desktop_entry(instance: &Instance, exe: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={name}\n\
+         Comment=Minecraft {version}\n\
+         Exec={exe} {link}\n\
+         Icon=intelligence\n\
+         Terminal=false\n\
+         Categories=Game;\n",
+        name = instance.name.replace('\n', " "),
+        version = instance.game_version,
+        exe = quote_exec_arg(&exe.to_string_lossy()),
+        link = quote_exec_arg(&launch_link(&instance.id)),
+    )
+}
+
+// Desktop entry registering the launcher as the intelligence:// handler
+#[cfg(target_os = "linux")]
+fn handler_desktop_entry(exe: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Intelligence\n\
+         Exec={exe} %u\n\
+         Icon=intelligence\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         MimeType=x-scheme-handler/{scheme};\n",
+        exe = quote_exec_arg(&exe.to_string_lossy()),
+        scheme = SCHEME,
+    )
+}
+
+// Directory for user desktop entries, $XDG_DATA_HOME/applications
+#[cfg(target_os = "linux")]
+fn applications_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let data_home = dirs::data_dir().ok_or("Could not determine data directory")?;
+    let path = data_home.join("applications");
+    fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+// Write a desktop shortcut for an instance, returning its path
+#[cfg(target_os = "linux")]
+pub fn create_instance_shortcut(instance: &Instance) -> Result<PathBuf, Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let path = applications_dir()?.join(format!("intelligence-{}.desktop", instance.id));
+    fs::write(&path, instance_desktop_entry(instance, &exe))?;
+    Ok(path)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn create_instance_shortcut(_instance: &Instance) -> Result<PathBuf, Box<dyn Error>> {
+    Err("Instance shortcuts are only supported on Linux".into())
+}
+
+// Register the launcher as the handler for intelligence:// links
+#[cfg(target_os = "linux")]
+pub fn register_scheme() -> Result<(), Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let file_name = "intelligence-url-handler.desktop";
+    let path = applications_dir()?.join(file_name);
+
+    let entry = handler_desktop_entry(&exe);
+    if fs::read_to_string(&path).is_ok_and(|existing| existing == entry) {
+        return Ok(());
+    }
+    fs::write(&path, entry)?;
+
+    std::process::Command::new("xdg-mime")
+        .args(["default", file_name, &format!("x-scheme-handler/{}", SCHEME)])
+        .status()?;
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn register_scheme() -> Result<(), Box<dyn Error>> {
+    use std::os::windows::process::CommandExt;
+    // Don't flash a console window for reg.exe
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let exe = std::env::current_exe()?;
+    let key = format!("HKCU\\Software\\Classes\\{}", SCHEME);
+    let command = format!("\"{}\" \"%1\"", exe.to_string_lossy());
+    let entries = [
+        (key.clone(), Some("/ve"), "URL:Intelligence".to_string()),
+        (key.clone(), None, String::new()),
+        (format!("{}\\shell\\open\\command", key), Some("/ve"), command),
+    ];
+
+    for (key, default_value, data) in entries {
+        let mut reg = std::process::Command::new("reg");
+        reg.args(["add", &key]);
+        match default_value {
+            Some(flag) => reg.arg(flag),
+            None => reg.args(["/v", "URL Protocol"]),
+        };
+        let status = reg.args(["/d", &data, "/f"]).creation_flags(CREATE_NO_WINDOW).status()?;
+        if !status.success() {
+            return Err(format!("reg add {} failed", key).into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn register_scheme() -> Result<(), Box<dyn Error>> {
+    // Registered by the bundle's Info.plist
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_launch_and_join_links() {
+        assert_eq!(parse("intelligence://launch/my%20pack"), Ok(DeepLink::Launch { instance: "my pack".to_string() }));
+        assert_eq!(
+            parse("intelligence://join/mc.example.com:25565?instance=vanilla"),
+            Ok(DeepLink::JoinServer { address: "mc.example.com:25565".to_string(), instance: Some("vanilla".to_string()) })
+        );
+    }
+
+    #[test]
+    fn rejects_instance_ids_outside_instances() {
+        assert!(parse("intelligence://launch/..%2F..%2FDownloads%2Fx").is_err());
+        assert!(parse("intelligence://launch/%2Fetc").is_err());
+        assert!(parse("intelligence://launch/..").is_err());
+        assert!(parse("intelligence://join/mc.example.com?instance=..%2Fx").is_err());
+    }
+}
@@ -0,0 +1,470 @@
+// System diagnostics for the settings page and bug reports: hardware, free
+// space next to the game data, Java runtimes found on the machine and the
+// renderer the last game reported
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::thread;
+use std::time::{Duration, Instant};
+use regex::Regex;
+use serde::Serialize;
+use sysinfo::{CpuRefreshKind, Disks, System};
+
+use crate::instances::{self, Instance};
+use crate::logs::Redactor;
+
+const MIB: u64 = 1024 * 1024;
+
+// `java -version` answers within a second, anything slower is killed
+const JAVA_VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+const JAVA_VERSION_POLL: Duration = Duration::from_millis(50);
+
+// Absolute paths inside free text, after a separator: /x/y, C:\x\y or \\server\share
+static ABSOLUTE_PATH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)(^|[\s=:,;"'(])((?:[A-Za-z]:[\\/]|\\\\|/)[^\s"',;:)]*)"#).unwrap()
+});
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SystemInfo {
+    pub os: String,
+    pub version: String,
+    pub arch: String,
+    pub cpu_model: Option<String>,
+    pub cpu_cores: usize,
+    pub physical_cores: Option<usize>,
+    // Memory and disk space in MiB
+    pub total_memory: u64,
+    pub available_memory: u64,
+    pub data_free_space: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct JavaRuntime {
+    pub path: PathBuf,
+    // As printed by `java -version`, e.g. 17.0.8
+    pub version: Option<String>,
+}
+
+// Renderer lines from the newest game log
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct GraphicsInfo {
+    pub instance_id: Option<String>,
+    pub renderer: Option<String>,
+    pub backend: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InstanceSummary {
+    pub id: String,
+    pub game_version: String,
+    pub loader: Option<String>,
+    pub java_path: Option<PathBuf>,
+    pub max_memory: Option<u64>,
+    pub jvm_args: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DiagnosticsReport {
+    pub launcher_version: String,
+    pub system: SystemInfo,
+    pub java_runtimes: Vec<JavaRuntime>,
+    pub graphics: GraphicsInfo,
+    pub instances: Vec<InstanceSummary>,
+}
+
+// Total system RAM in MiB
+pub fn total_memory() -> u64 {
+    let mut system = System::new();
+    system.refresh_memory();
+    system.total_memory() / MIB
+}
+
+// Free space on the disk holding `path`, in MiB
+fn free_space(path: &Path) -> Option<u64> {
+    let path = path.canonicalize().ok()?;
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space() / MIB)
+}
+
+pub fn system_info(data_path: Option<&Path>) -> SystemInfo {
+    let os_info = os_info::get();
+    let os = match os_info.os_type() {
+        os_info::Type::Macos => "macOS".to_string(),
+        _ => std::env::consts::OS.to_string(),
+    };
+
+    let mut system = System::new();
+    system.refresh_memory();
+    system.refresh_cpu_list(CpuRefreshKind::nothing());
+
+    SystemInfo {
+        os,
+        version: os_info.version().to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        cpu_model: system
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .filter(|brand| !brand.is_empty()),
+        cpu_cores: std::thread::available_parallelism().map(|count| count.get()).unwrap_or(1),
+        physical_cores: system.physical_core_count(),
+        total_memory: system.total_memory() / MIB,
+        available_memory: system.available_memory() / MIB,
+        data_free_space: data_path.and_then(free_space),
+    }
+}
+
+// Version from `java -version` output: openjdk version "17.0.8" 2023-07-18.
+// A binary that doesn't exit in time is killed
+fn java_version(java: &Path) -> Option<String> {
+    let mut child = Command::new(java)
+        .arg("-version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + JAVA_VERSION_TIMEOUT;
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            eprintln!("{:?} -version did not exit in time", java);
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(JAVA_VERSION_POLL);
+    }
+
+    let mut text = String::new();
+    child.stderr.take()?.read_to_string(&mut text).ok()?;
+    let line = text.lines().find(|line| line.contains(" version "))?;
+    line.split('"').nth(1).map(String::from)
+}
+
+// Whether a directory is a JDK or JRE install, which ship a `release` file
+// naming the Java version
+fn is_java_home(dir: &Path) -> bool {
+    fs::read_to_string(dir.join("release")).is_ok_and(|release| release.contains("JAVA_VERSION="))
+}
+
+// Places Java is usually installed, JDK directories are scanned one level deep
+fn java_candidates() -> Vec<PathBuf> {
+    let executable = if cfg!(windows) { "java.exe" } else { "java" };
+    let mut candidates = Vec::new();
+
+    if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+        candidates.push(PathBuf::from(java_home).join("bin").join(executable));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        candidates.extend(std::env::split_paths(&path).map(|dir| dir.join(executable)));
+    }
+
+    let (roots, bin): (Vec<PathBuf>, &str) = if cfg!(windows) {
+        let program_files = std::env::var_os("ProgramFiles").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(r"C:\Program Files"));
+        (
+            ["Java", "Eclipse Adoptium", "Microsoft", "Zulu", "BellSoft"].iter().map(|vendor| program_files.join(vendor)).collect(),
+            "bin",
+        )
+    } else if cfg!(target_os = "macos") {
+        (vec![PathBuf::from("/Library/Java/JavaVirtualMachines")], "Contents/Home/bin")
+    } else {
+        (vec![PathBuf::from("/usr/lib/jvm"), PathBuf::from("/usr/java"), PathBuf::from("/opt")], "bin")
+    };
+    for root in roots {
+        let Ok(entries) = fs::read_dir(&root) else { continue };
+        // /opt holds all kinds of software, only run what is clearly a Java install
+        let shared = root == Path::new("/opt");
+        candidates.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|dir| !shared || is_java_home(dir))
+                .map(|dir| dir.join(bin).join(executable)),
+        );
+    }
+    candidates
+}
+
+// Java runtimes on this machine, plus the ones instances are set to use. The
+// versions are queried in parallel
+pub fn java_runtimes(instances: &[Instance]) -> Vec<JavaRuntime> {
+    let mut seen = BTreeSet::new();
+    let paths: Vec<PathBuf> = java_candidates()
+        .into_iter()
+        .chain(instances.iter().filter_map(|instance| instance.java_path.clone()))
+        .filter(|path| path.is_file())
+        .filter(|path| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())))
+        .collect();
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = paths.iter().map(|path| scope.spawn(move || java_version(path))).collect();
+        paths
+            .iter()
+            .zip(handles)
+            .map(|(path, handle)| JavaRuntime { path: path.clone(), version: handle.join().unwrap_or_default() })
+            .collect()
+    })
+}
+
+// Log message without the `[time] [thread/LEVEL]: ` prefix
+fn message(line: &str) -> &str {
+    line.split_once("]: ").map(|(_, message)| message).unwrap_or(line).trim()
+}
+
+// Renderer details from the game log of the most recently played instance
+pub fn graphics_info(data_path: &Path, instances: &[Instance]) -> GraphicsInfo {
+    let Some(instance) = instances.iter().find(|instance| instance.last_played.is_some()) else {
+        return GraphicsInfo::default();
+    };
+    let mut info = GraphicsInfo { instance_id: Some(instance.id.clone()), ..Default::default() };
+
+    let log_path = instance.game_dir(data_path).join("logs").join("latest.log");
+    let Ok(file) = fs::File::open(log_path) else { return info };
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let message = message(&line);
+        if info.renderer.is_none() && (message.starts_with("OpenGL:") || message.starts_with("GL_RENDERER")) {
+            info.renderer = Some(message.to_string());
+        } else if info.backend.is_none() && (message.starts_with("Backend library:") || message.starts_with("LWJGL Version:")) {
+            info.backend = Some(message.to_string());
+        }
+        if info.renderer.is_some() && info.backend.is_some() {
+            break;
+        }
+    }
+    info
+}
+
+pub fn build_report(data_path: &Path) -> DiagnosticsReport {
+    let instances = instances::list_instances(data_path).unwrap_or_default();
+
+    DiagnosticsReport {
+        launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+        system: system_info(Some(data_path)),
+        java_runtimes: java_runtimes(&instances),
+        graphics: graphics_info(data_path, &instances),
+        instances: instances
+            .iter()
+            .map(|instance| InstanceSummary {
+                id: instance.id.clone(),
+                game_version: instance.game_version.clone(),
+                loader: instance.loader.clone(),
+                java_path: instance.java_path.clone(),
+                max_memory: instance.jvm.max_memory,
+                jvm_args: instance.jvm.jvm_args.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn or_unknown<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|value| value.to_string()).unwrap_or_else(|| "unknown".to_string())
+}
+
+// Plain text version of a report for pasting into an issue
+pub fn format_text(report: &DiagnosticsReport) -> String {
+    let system = &report.system;
+    let mut lines = vec![
+        format!("Intelligence {}", report.launcher_version),
+        String::new(),
+        "System".to_string(),
+        format!("  OS: {} {} ({})", system.os, system.version, system.arch),
+        format!("  CPU: {} ({} threads, {} cores)", or_unknown(&system.cpu_model), system.cpu_cores, or_unknown(&system.physical_cores)),
+        format!("  Memory: {} MB available of {} MB", system.available_memory, system.total_memory),
+        format!("  Free space for game data: {} MB", or_unknown(&system.data_free_space)),
+        String::new(),
+        "Java".to_string(),
+    ];
+    if report.java_runtimes.is_empty() {
+        lines.push("  none found".to_string());
+    }
+    for runtime in &report.java_runtimes {
+        lines.push(format!("  {} ({})", runtime.path.display(), or_unknown(&runtime.version)));
+    }
+
+    lines.push(String::new());
+    lines.push("Graphics".to_string());
+    lines.push(format!("  Renderer: {}", or_unknown(&report.graphics.renderer)));
+    lines.push(format!("  Backend: {}", or_unknown(&report.graphics.backend)));
+
+    lines.push(String::new());
+    lines.push("Instances".to_string());
+    for instance in &report.instances {
+        let loader = instance.loader.as_ref().map(|loader| format!(" {}", loader)).unwrap_or_default();
+        let memory = instance.max_memory.map(|max| format!(", {} MB", max)).unwrap_or_default();
+        lines.push(format!("  {}: {}{}{}", instance.id, instance.game_version, loader, memory));
+    }
+    lines.join("\n")
+}
+
+// Path as it may appear in a shared report: inside the data directory or home
+// relative to it, anything else reduced to its file name
+fn redact_path(path: &Path, data_path: &Path, home: Option<&Path>) -> PathBuf {
+    if let Ok(relative) = path.strip_prefix(data_path) {
+        return Path::new("<data>").join(relative);
+    }
+    if let Some(relative) = home.and_then(|home| path.strip_prefix(home).ok()) {
+        return Path::new("~").join(relative);
+    }
+    if !path.has_root() && !path.components().any(|component| matches!(component, Component::Prefix(_))) {
+        return path.to_path_buf();
+    }
+    let name = path.file_name().map(PathBuf::from).unwrap_or_default();
+    Path::new("<path>").join(name)
+}
+
+// Paths left in free text such as JVM arguments, once the data directory and
+// home have been replaced
+fn redact_text_paths(text: &str) -> String {
+    ABSOLUTE_PATH
+        .replace_all(text, |caps: &regex::Captures| {
+            let path = &caps[2];
+            // URLs are not paths
+            if path.starts_with("//") && caps[1].ends_with(':') {
+                return caps[0].to_string();
+            }
+            let name = path.rsplit(['/', '\\']).find(|part| !part.is_empty() && !part.ends_with(':')).unwrap_or_default();
+            format!("{}<path>/{}", &caps[1], name)
+        })
+        .into_owned()
+}
+
+// Copy of a report with the paths it holds redacted
+fn redact_report_paths(report: &DiagnosticsReport, data_path: &Path, home: Option<&Path>) -> DiagnosticsReport {
+    let mut report = report.clone();
+    for runtime in &mut report.java_runtimes {
+        runtime.path = redact_path(&runtime.path, data_path, home);
+    }
+    for instance in &mut report.instances {
+        instance.java_path = instance.java_path.as_deref().map(|path| redact_path(path, data_path, home));
+    }
+    report
+}
+
+// Apply the redactor to every string in a JSON value
+fn redact_value(value: &mut serde_json::Value, redact: &impl Fn(&str) -> String) {
+    match value {
+        serde_json::Value::String(text) => *text = redact(text),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| redact_value(item, redact)),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(|field| redact_value(field, redact)),
+        _ => {},
+    }
+}
+
+// Report as text or JSON, with the data directory, home and other absolute
+// paths and the username redacted
+pub fn render(report: &DiagnosticsReport, json: bool, username: &str, data_path: &Path) -> Result<String, String> {
+    render_with(report, json, username, data_path, dirs::home_dir().as_deref())
+}
+
+fn render_with(report: &DiagnosticsReport, json: bool, username: &str, data_path: &Path, home: Option<&Path>) -> Result<String, String> {
+    let report = redact_report_paths(report, data_path, home);
+    // The data directory goes first, it is often inside home
+    let redactor = Redactor::new(&[username.to_string()], home);
+    let redact = |text: &str| {
+        let data = data_path.to_string_lossy();
+        let text = if data.len() > 1 { text.replace(data.as_ref(), "<data>") } else { text.to_string() };
+        redact_text_paths(&redactor.redact(&text))
+    };
+    if !json {
+        return Ok(redact(&format_text(&report)));
+    }
+
+    let mut value = serde_json::to_value(&report).map_err(|e| e.to_string())?;
+    redact_value(&mut value, &redact);
+    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn report() -> DiagnosticsReport {
+        DiagnosticsReport {
+            launcher_version: "0.1.0".to_string(),
+            system: SystemInfo {
+                os: "Linux".to_string(),
+                version: "6.1".to_string(),
+                arch: "x86_64".to_string(),
+                cpu_model: None,
+                cpu_cores: 8,
+                physical_cores: Some(4),
+                total_memory: 16000,
+                available_memory: 8000,
+                data_free_space: None,
+            },
+            java_runtimes: vec![
+                JavaRuntime { path: PathBuf::from("/usr/lib/jvm/java-17/bin/java"), version: Some("17.0.8".to_string()) },
+                JavaRuntime { path: PathBuf::from("/home/alex/.jdks/temurin-21/bin/java"), version: None },
+            ],
+            graphics: GraphicsInfo { instance_id: None, renderer: Some("NVIDIA GeForce GTX 1060/PCIe/SSE2".to_string()), backend: None },
+            instances: vec![InstanceSummary {
+                id: "survival".to_string(),
+                game_version: "1.21".to_string(),
+                loader: None,
+                java_path: Some(PathBuf::from("/mnt/games/intelligence/runtimes/java-21/bin/java")),
+                max_memory: Some(4096),
+                jvm_args: vec![
+                    "-Djava.library.path=/srv/alex/natives:/mnt/games/intelligence/natives".to_string(),
+                    "-Dlog4j.configurationFile=/home/alex/log4j.xml".to_string(),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn paths_are_redacted() {
+        let data = Path::new("/mnt/games/intelligence");
+        let home = Some(Path::new("/home/alex"));
+        assert_eq!(redact_path(Path::new("/mnt/games/intelligence/a/java"), data, home), PathBuf::from("<data>/a/java"));
+        assert_eq!(redact_path(Path::new("/home/alex/jdk/bin/java"), data, home), PathBuf::from("~/jdk/bin/java"));
+        assert_eq!(redact_path(Path::new("/opt/secret-corp/java"), data, home), PathBuf::from("<path>/java"));
+        assert_eq!(redact_path(Path::new("java"), data, home), PathBuf::from("java"));
+
+        assert_eq!(redact_text_paths("-Dx=/srv/a/lib:/usr/lib"), "-Dx=<path>/lib:<path>/lib");
+        assert_eq!(redact_text_paths(r"-Dx=C:\Users\alex\natives"), "-Dx=<path>/natives");
+        assert_eq!(redact_text_paths("see https://example.com/a"), "see https://example.com/a");
+        assert_eq!(redact_text_paths("GTX 1060/PCIe/SSE2"), "GTX 1060/PCIe/SSE2");
+    }
+
+    #[test]
+    fn rendered_reports_leak_no_paths() {
+        let data = Path::new("/mnt/games/intelligence");
+        for json in [false, true] {
+            let rendered = render_with(&report(), json, "alex", data, Some(Path::new("/home/alex"))).unwrap();
+            for leak in ["alex", "/mnt/games", "/srv", "/usr/lib/jvm"] {
+                assert!(!rendered.contains(leak), "{} leaks {}:\n{}", if json { "JSON" } else { "text" }, leak, rendered);
+            }
+            assert!(rendered.contains("<path>/java"));
+            assert!(rendered.contains("~/.jdks/temurin-21/bin/java"));
+            assert!(rendered.contains("GTX 1060/PCIe/SSE2"));
+        }
+
+        let rendered = render_with(&report(), true, "alex", data, Some(Path::new("/home/alex"))).unwrap();
+        assert!(rendered.contains("<data>/runtimes/java-21/bin/java"));
+        assert!(rendered.contains("-Djava.library.path=<path>/natives:<data>/natives"));
+        assert!(rendered.contains("-Dlog4j.configurationFile=~/log4j.xml"));
+    }
+
+    #[test]
+    fn hanging_java_is_killed() {
+        let dir = std::env::temp_dir().join(format!("intelligence-java-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fake_java = dir.join("java");
+        fs::write(&fake_java, "#!/bin/sh\nsleep 60\n").unwrap();
+        fs::set_permissions(&fake_java, <fs::Permissions as std::os::unix::fs::PermissionsExt>::from_mode(0o755)).unwrap();
+
+        let started = Instant::now();
+        assert_eq!(java_version(&fake_java), None);
+        assert!(started.elapsed() < JAVA_VERSION_TIMEOUT + Duration::from_secs(2));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,336 @@
+// Minimal Discord RPC client over the local IPC socket (discord-ipc-N).
+//
+// Frames are a little-endian opcode and payload length followed by JSON. After
+// a handshake with the application id, SET_ACTIVITY commands update the rich
+// presence of the current process. connect_to takes an explicit socket path so
+// the client can be pointed at a fake server
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Serialize;
+use serde_json::{json, Value};
+#[cfg(windows)]
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+
+#[cfg(unix)]
+type IpcStream = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type IpcStream = std::fs::File;
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+const OP_CLOSE: u32 = 2;
+const OP_PING: u32 = 3;
+const OP_PONG: u32 = 4;
+
+// Number of discord-ipc-N sockets Discord may create
+const MAX_PIPES: u32 = 10;
+
+// Discord replies quickly, don't let a stuck client hang the presence thread
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Largest frame accepted from Discord
+const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Timestamps {
+    pub start: u64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Assets {
+    pub large_image: String,
+    pub large_text: String,
+}
+
+// Rich presence shown on the user's profile
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Activity {
+    pub details: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<Timestamps>,
+    pub assets: Assets,
+}
+
+// Encode a frame: opcode, payload length, JSON payload
+pub fn encode_frame(opcode: u32, payload: &Value) -> Vec<u8> {
+    let body = payload.to_string().into_bytes();
+    let mut frame = Vec::with_capacity(8 + body.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&body);
+    frame
+}
+
+// Read one frame
+pub fn read_frame(reader: &mut impl Read) -> io::Result<(u32, Value)> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)?;
+    let opcode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Frame of {} bytes is too large", length)));
+    }
+
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    let payload = serde_json::from_slice(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((opcode, payload))
+}
+
+// Directories Discord puts its socket in, including Flatpak and Snap installs
+#[cfg(unix)]
+fn socket_dirs() -> Vec<PathBuf> {
+    let base = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(|var| std::env::var_os(var).filter(|value| !value.is_empty()))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+
+    ["", "app/com.discordapp.Discord", "snap.discord", ".flatpak/dev.vencord.Vesktop/xdg-run"]
+        .iter()
+        .map(|sub| base.join(sub))
+        .collect()
+}
+
+// Socket paths to try, in order
+pub fn candidate_paths() -> Vec<PathBuf> {
+    #[cfg(unix)]
+    let dirs = socket_dirs();
+    #[cfg(windows)]
+    let dirs = vec![PathBuf::from(r"\\?\pipe")];
+
+    dirs.iter()
+        .flat_map(|dir| (0..MAX_PIPES).map(move |index| dir.join(format!("discord-ipc-{}", index))))
+        .collect()
+}
+
+#[cfg(unix)]
+fn open_stream(path: &Path) -> io::Result<IpcStream> {
+    let stream = IpcStream::connect(path)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok(stream)
+}
+
+#[cfg(windows)]
+fn open_stream(path: &Path) -> io::Result<IpcStream> {
+    std::fs::OpenOptions::new().read(true).write(true).open(path)
+}
+
+// Named pipes opened as files have no read timeout, so frames are read on a
+// separate thread and waited for with a deadline. The thread exits once the
+// pipe is closed or a read fails
+#[cfg(windows)]
+fn spawn_reader(stream: &IpcStream) -> io::Result<Receiver<io::Result<(u32, Value)>>> {
+    let mut reader = stream.try_clone()?;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || loop {
+        let frame = read_frame(&mut reader);
+        let failed = frame.is_err();
+        if sender.send(frame).is_err() || failed {
+            break;
+        }
+    });
+    Ok(receiver)
+}
+
+fn protocol_error(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+// Connection to a Discord client
+pub struct DiscordIpc {
+    stream: IpcStream,
+    #[cfg(windows)]
+    frames: Receiver<io::Result<(u32, Value)>>,
+    nonce: u64,
+}
+
+impl DiscordIpc {
+    // Connect to the first running Discord client
+    pub fn connect(client_id: &str) -> io::Result<Self> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "Discord is not running");
+        for path in candidate_paths() {
+            match Self::connect_to(&path, client_id) {
+                Ok(client) => return Ok(client),
+                Err(e) if e.kind() == io::ErrorKind::NotFound || e.kind() == io::ErrorKind::ConnectionRefused => {},
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    // Connect and handshake over a specific socket
+    pub fn connect_to(path: &Path, client_id: &str) -> io::Result<Self> {
+        let stream = open_stream(path)?;
+        let mut client = Self {
+            #[cfg(windows)]
+            frames: spawn_reader(&stream)?,
+            stream,
+            nonce: 0,
+        };
+        client.stream.write_all(&encode_frame(OP_HANDSHAKE, &json!({ "v": 1, "client_id": client_id })))?;
+
+        let (opcode, payload) = client.read_reply()?;
+        if opcode != OP_FRAME || payload["evt"] != "READY" {
+            return Err(protocol_error(format!("Handshake rejected: {}", payload)));
+        }
+        Ok(client)
+    }
+
+    #[cfg(unix)]
+    fn next_frame(&mut self) -> io::Result<(u32, Value)> {
+        read_frame(&mut self.stream)
+    }
+
+    #[cfg(windows)]
+    fn next_frame(&mut self) -> io::Result<(u32, Value)> {
+        match self.frames.recv_timeout(READ_TIMEOUT) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(io::ErrorKind::TimedOut, "Discord did not reply in time")),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(io::ErrorKind::BrokenPipe, "Discord connection is closed")),
+        }
+    }
+
+    // Read the next frame, answering pings and turning close frames into errors
+    fn read_reply(&mut self) -> io::Result<(u32, Value)> {
+        loop {
+            let (opcode, payload) = self.next_frame()?;
+            match opcode {
+                OP_PING => self.stream.write_all(&encode_frame(OP_PONG, &payload))?,
+                OP_CLOSE => {
+                    return Err(io::Error::new(io::ErrorKind::ConnectionAborted, format!("Discord closed the connection: {}", payload)))
+                },
+                _ => return Ok((opcode, payload)),
+            }
+        }
+    }
+
+    // Send a command and wait for the matching response
+    fn command(&mut self, command: &str, args: Value) -> io::Result<Value> {
+        self.nonce += 1;
+        let nonce = format!("{}-{}", std::process::id(), self.nonce);
+        let request = json!({ "cmd": command, "args": args, "nonce": nonce });
+        self.stream.write_all(&encode_frame(OP_FRAME, &request))?;
+
+        loop {
+            let (_, payload) = self.read_reply()?;
+            if payload["nonce"] != nonce.as_str() {
+                continue;
+            }
+            if payload["evt"] == "ERROR" {
+                return Err(protocol_error(format!("{} failed: {}", command, payload["data"])));
+            }
+            return Ok(payload);
+        }
+    }
+
+    // Show an activity, or clear it with None
+    pub fn set_activity(&mut self, activity: Option<&Activity>) -> io::Result<()> {
+        let args = json!({ "pid": std::process::id(), "activity": activity });
+        self.command("SET_ACTIVITY", args).map(|_| ())
+    }
+
+    // Clear the activity and close the connection
+    pub fn close(mut self) {
+        let _ = self.set_activity(None);
+        let _ = self.stream.write_all(&encode_frame(OP_CLOSE, &json!({})));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::thread;
+
+    fn socket_path(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("intelligence-ipc-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("discord-ipc-0")
+    }
+
+    // Accept a connection and answer the handshake the way Discord does
+    fn accept_handshake(listener: &UnixListener) -> UnixStream {
+        let (mut stream, _) = listener.accept().unwrap();
+        let (opcode, payload) = read_frame(&mut stream).unwrap();
+        assert_eq!(opcode, OP_HANDSHAKE);
+        assert_eq!(payload, json!({ "v": 1, "client_id": "1234" }));
+
+        stream.write_all(&encode_frame(OP_PING, &json!({ "ping": 1 }))).unwrap();
+        stream.write_all(&encode_frame(OP_FRAME, &json!({ "cmd": "DISPATCH", "evt": "READY", "data": {} }))).unwrap();
+        stream
+    }
+
+    #[test]
+    fn handshake_activity_and_reconnect() {
+        let path = socket_path("reconnect");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let mut stream = accept_handshake(&listener);
+            let (opcode, payload) = read_frame(&mut stream).unwrap();
+            assert_eq!(opcode, OP_PONG);
+            assert_eq!(payload, json!({ "ping": 1 }));
+
+            let (opcode, request) = read_frame(&mut stream).unwrap();
+            assert_eq!(opcode, OP_FRAME);
+            assert_eq!(request["cmd"], "SET_ACTIVITY");
+            assert_eq!(request["args"]["pid"], std::process::id());
+            assert_eq!(request["args"]["activity"]["details"], "Playing Survival");
+            assert_eq!(request["args"]["activity"]["timestamps"]["start"], 42);
+            assert!(request["args"]["activity"].get("state").is_none());
+
+            // A reply for someone else's nonce must be skipped
+            stream.write_all(&encode_frame(OP_FRAME, &json!({ "cmd": "SET_ACTIVITY", "nonce": "other" }))).unwrap();
+            let reply = json!({ "cmd": "SET_ACTIVITY", "nonce": request["nonce"], "evt": null, "data": null });
+            stream.write_all(&encode_frame(OP_FRAME, &reply)).unwrap();
+
+            // Drop the client, then accept it again
+            drop(stream);
+            let mut stream = accept_handshake(&listener);
+            read_frame(&mut stream).unwrap();
+            let (_, request) = read_frame(&mut stream).unwrap();
+            assert_eq!(request["args"]["activity"], Value::Null);
+            let reply = json!({ "cmd": "SET_ACTIVITY", "nonce": request["nonce"], "evt": null, "data": null });
+            stream.write_all(&encode_frame(OP_FRAME, &reply)).unwrap();
+        });
+
+        let activity = Activity {
+            details: "Playing Survival".to_string(),
+            state: None,
+            timestamps: Some(Timestamps { start: 42 }),
+            assets: Assets { large_image: "logo".to_string(), large_text: "Intelligence".to_string() },
+        };
+        let mut client = DiscordIpc::connect_to(&path, "1234").unwrap();
+        client.set_activity(Some(&activity)).unwrap();
+        assert!(client.set_activity(Some(&activity)).is_err());
+
+        let mut client = DiscordIpc::connect_to(&path, "1234").unwrap();
+        client.set_activity(None).unwrap();
+
+        server.join().unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn rejected_handshake() {
+        let path = socket_path("rejected");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            read_frame(&mut stream).unwrap();
+            stream.write_all(&encode_frame(OP_CLOSE, &json!({ "code": 4000, "message": "Invalid client ID" }))).unwrap();
+        });
+
+        let error = DiscordIpc::connect_to(&path, "1234").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionAborted);
+        server.join().unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
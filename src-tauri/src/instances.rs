@@ -0,0 +1,210 @@
+// Game instances, each stored as <data>/instances/<id>/instance.json with its
+// own .minecraft game directory next to it
+use std::error::Error;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+use crate::jvm::JvmSettings;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Instance {
+    // Directory name, unique among instances
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    // Minecraft version id, or a loader profile id such as "fabric-loader-0.16.9-1.21.1"
+    pub game_version: String,
+    // Mod loader name shown in the UI, e.g. "fabric"
+    #[serde(default)]
+    pub loader: Option<String>,
+    // Unix timestamp of the last launch
+    #[serde(default)]
+    pub last_played: Option<u64>,
+    // Java executable to use instead of the detected one
+    #[serde(default)]
+    pub java_path: Option<PathBuf>,
+    // Memory, JVM flags, environment, wrapper and hooks
+    #[serde(default)]
+    pub jvm: JvmSettings,
+}
+
+impl Instance {
+    pub fn path(&self, data_path: &Path) -> PathBuf {
+        instances_path(data_path).join(&self.id)
+    }
+
+    // Directory the game runs in (saves, mods, options.txt, logs)
+    pub fn game_dir(&self, data_path: &Path) -> PathBuf {
+        self.path(data_path).join(".minecraft")
+    }
+
+    // Directory native libraries are extracted to before launch
+    pub fn natives_dir(&self, data_path: &Path) -> PathBuf {
+        self.path(data_path).join("natives")
+    }
+}
+
+pub fn instances_path(data_path: &Path) -> PathBuf {
+    data_path.join("instances")
+}
+
+// Ids come from links, the CLI and the frontend, they must name a directory
+// directly inside instances/
+pub fn is_valid_id(id: &str) -> bool {
+    let mut components = Path::new(id).components();
+    matches!((components.next(), components.next()), (Some(Component::Normal(_)), None))
+}
+
+fn instance_file(data_path: &Path, id: &str) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    if !is_valid_id(id) {
+        return Err(format!("Invalid instance id: {}", id).into());
+    }
+    Ok(instances_path(data_path).join(id).join("instance.json"))
+}
+
+// Load a single instance by id
+pub fn load_instance(data_path: &Path, id: &str) -> Result<Instance, Box<dyn Error + Send + Sync>> {
+    let path = instance_file(data_path, id)?;
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Instance {} not found ({})", id, e))?;
+    let mut instance: Instance = serde_json::from_str(&contents)?;
+    instance.id = id.to_string();
+    Ok(instance)
+}
+
+// Find an instance by id or, failing that, by its display name
+pub fn find_instance(data_path: &Path, id_or_name: &str) -> Result<Instance, Box<dyn Error + Send + Sync>> {
+    if instance_file(data_path, id_or_name).is_ok_and(|path| path.exists()) {
+        return load_instance(data_path, id_or_name);
+    }
+
+    list_instances(data_path)?
+        .into_iter()
+        .find(|instance| instance.name.eq_ignore_ascii_case(id_or_name))
+        .ok_or_else(|| format!("Instance {} not found", id_or_name).into())
+}
+
+pub fn save_instance(data_path: &Path, instance: &Instance) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let path = instance_file(data_path, &instance.id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(instance)?)?;
+    Ok(())
+}
+
+// List all instances, most recently played first
+pub fn list_instances(data_path: &Path) -> Result<Vec<Instance>, Box<dyn Error + Send + Sync>> {
+    let mut instances = Vec::new();
+
+    let entries = match fs::read_dir(instances_path(data_path)) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(instances),
+    };
+
+    for entry in entries.flatten() {
+        let id = entry.file_name().to_string_lossy().to_string();
+        if !instance_file(data_path, &id).is_ok_and(|path| path.exists()) {
+            continue;
+        }
+        match load_instance(data_path, &id) {
+            Ok(instance) => instances.push(instance),
+            Err(e) => eprintln!("Error loading instance {}: {}", id, e),
+        }
+    }
+
+    instances.sort_by(|a, b| b.last_played.cmp(&a.last_played).then_with(|| a.name.cmp(&b.name)));
+    Ok(instances)
+}
+
+// Turn a display name into a directory-safe id
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.trim_matches(['-', '.']).to_string();
+
+    if slug.is_empty() {
+        "instance".to_string()
+    } else {
+        slug
+    }
+}
+
+// Create a new instance with a unique id derived from its name
+pub fn create_instance(data_path: &Path, name: &str, game_version: &str) -> Result<Instance, Box<dyn Error + Send + Sync>> {
+    let base = slugify(name);
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while instances_path(data_path).join(&id).exists() {
+        id = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+
+    let instance = Instance {
+        id,
+        name: name.trim().to_string(),
+        game_version: game_version.to_string(),
+        loader: None,
+        last_played: None,
+        java_path: None,
+        jvm: JvmSettings::default(),
+    };
+
+    fs::create_dir_all(instance.game_dir(data_path))?;
+    save_instance(data_path, &instance)?;
+    Ok(instance)
+}
+
+// Record a launch of the instance
+pub fn mark_played(data_path: &Path, instance: &mut Instance) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    instance.last_played = Some(now);
+    save_instance(data_path, instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_ids_name_a_single_directory() {
+        assert!(is_valid_id("survival"));
+        assert!(is_valid_id("fabric-1.21.1"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("."));
+        assert!(!is_valid_id(".."));
+        assert!(!is_valid_id("../survival"));
+        assert!(!is_valid_id("survival/.minecraft"));
+        assert!(!is_valid_id("/survival"));
+    }
+
+    #[test]
+    fn names_are_slugified() {
+        assert_eq!(slugify("My Survival World"), "my-survival-world");
+        assert_eq!(slugify("  Fabric 1.21.1  "), "fabric-1.21.1");
+        assert_eq!(slugify("../.."), "instance");
+        assert_eq!(slugify("Сервер"), "instance");
+    }
+
+    #[test]
+    fn instances_are_created_and_found() {
+        let data_path = std::env::temp_dir().join(format!("intelligence-instances-{}", std::process::id()));
+        let first = create_instance(&data_path, "Survival", "1.21.1").unwrap();
+        let second = create_instance(&data_path, "survival", "1.20.1").unwrap();
+        assert_eq!(first.id, "survival");
+        assert_eq!(second.id, "survival-2");
+        assert!(first.game_dir(&data_path).is_dir());
+
+        assert_eq!(find_instance(&data_path, "survival-2").unwrap().game_version, "1.20.1");
+        assert_eq!(find_instance(&data_path, "SURVIVAL").unwrap().id, "survival");
+        assert!(find_instance(&data_path, "creative").is_err());
+        assert!(load_instance(&data_path, "../instances/survival").unwrap_err().to_string().contains("Invalid instance id"));
+        assert_eq!(list_instances(&data_path).unwrap().len(), 2);
+        fs::remove_dir_all(data_path).unwrap();
+    }
+}
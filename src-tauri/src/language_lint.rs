@@ -0,0 +1,355 @@
+// Validation of language packs against the en_US reference
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+
+use crate::message_format::{self, Part};
+use crate::{Language, LanguageMetadata};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    // The file is not a valid language pack at all
+    ParseError,
+    MissingMetadata,
+    // metadata.version is not MAJOR.MINOR.PATCH
+    InvalidVersion,
+    // metadata.id differs from the file name
+    IdMismatch,
+    DuplicateKey,
+    UnknownKey,
+    EmptyString,
+    InvalidSyntax,
+    PlaceholderMismatch,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LanguageIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub key: Option<String>,
+    pub message: String,
+}
+
+// Overall result of validating a language pack
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationStatus {
+    Valid,
+    Warnings,
+    Invalid,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LanguageReport {
+    pub id: String,
+    pub status: ValidationStatus,
+    // Share of reference keys with a usable translation, 0 to 100
+    pub completeness: f32,
+    pub issues: Vec<LanguageIssue>,
+}
+
+impl LanguageReport {
+    fn push(&mut self, severity: Severity, kind: IssueKind, key: Option<&str>, message: String) {
+        self.issues.push(LanguageIssue {
+            severity,
+            kind,
+            key: key.map(str::to_string),
+            message,
+        });
+    }
+}
+
+// Collects top-level keys that appear more than once, which serde_json silently merges
+struct DuplicateKeys(Vec<String>);
+
+impl<'de> Deserialize<'de> for DuplicateKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = DuplicateKeys;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut seen = BTreeSet::new();
+                let mut duplicates = Vec::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    map.next_value::<IgnoredAny>()?;
+                    if !seen.insert(key.clone()) {
+                        duplicates.push(key);
+                    }
+                }
+                Ok(DuplicateKeys(duplicates))
+            }
+        }
+
+        deserializer.deserialize_map(KeysVisitor)
+    }
+}
+
+// Collect argument names used by a parsed message
+fn collect_placeholders(parts: &[Part], names: &mut BTreeSet<String>) {
+    for part in parts {
+        match part {
+            Part::Argument(name) | Part::Number(name) => {
+                names.insert(name.clone());
+            },
+            Part::Plural { name, options } | Part::Select { name, options } => {
+                names.insert(name.clone());
+                for (_, branch) in options {
+                    collect_placeholders(branch, names);
+                }
+            },
+            Part::Text(_) | Part::Pound => {}
+        }
+    }
+}
+
+fn placeholders(message: &str) -> Option<BTreeSet<String>> {
+    let parsed = message_format::parse(message).ok()?;
+    let mut names = BTreeSet::new();
+    collect_placeholders(&parsed.parts, &mut names);
+    Some(names)
+}
+
+// Check the metadata block for missing fields and an id that doesn't match the file name
+fn lint_metadata(report: &mut LanguageReport, metadata: &LanguageMetadata, file_stem: Option<&str>) {
+    for (field, value) in [("id", &metadata.id), ("version", &metadata.version), ("author", &metadata.author)] {
+        if value.trim().is_empty() {
+            report.push(Severity::Error, IssueKind::MissingMetadata, None, format!("metadata.{} is empty", field));
+        }
+    }
+
+    let version_parts: Vec<&str> = metadata.version.split('.').collect();
+    if !metadata.version.is_empty() && (version_parts.len() != 3 || version_parts.iter().any(|part| part.parse::<u64>().is_err())) {
+        report.push(
+            Severity::Warning,
+            IssueKind::InvalidVersion,
+            None,
+            format!("metadata.version '{}' is not in MAJOR.MINOR.PATCH form", metadata.version),
+        );
+    }
+
+    // Languages are loaded by file name and selected by id, a mismatch leaves
+    // the pack unusable
+    if let Some(stem) = file_stem {
+        if !metadata.id.is_empty() && metadata.id != stem {
+            report.push(
+                Severity::Error,
+                IssueKind::IdMismatch,
+                None,
+                format!("metadata.id '{}' does not match file name '{}.json'", metadata.id, stem),
+            );
+        }
+    }
+}
+
+// Validate the raw JSON of a language pack against reference translations.
+// Usable from tests and tooling without a running app
+pub fn lint_language(source: &str, file_stem: Option<&str>, reference: &HashMap<String, String>) -> LanguageReport {
+    let mut report = LanguageReport {
+        id: file_stem.unwrap_or_default().to_string(),
+        status: ValidationStatus::Valid,
+        completeness: 0.0,
+        issues: Vec::new(),
+    };
+
+    let language = match serde_json::from_str::<Language>(source) {
+        Ok(language) => language,
+        Err(e) => {
+            report.push(Severity::Error, IssueKind::ParseError, None, e.to_string());
+            report.status = ValidationStatus::Invalid;
+            return report;
+        }
+    };
+    if !language.metadata.id.is_empty() {
+        report.id = language.metadata.id.clone();
+    }
+
+    lint_metadata(&mut report, &language.metadata, file_stem);
+
+    if let Ok(DuplicateKeys(duplicates)) = serde_json::from_str::<DuplicateKeys>(source) {
+        for key in duplicates {
+            report.push(Severity::Error, IssueKind::DuplicateKey, Some(&key), format!("key '{}' is defined more than once", key));
+        }
+    }
+
+    let mut keys: Vec<&String> = language.translations.keys().collect();
+    keys.sort();
+
+    let mut translated = 0;
+    for key in keys {
+        let value = &language.translations[key];
+
+        let reference_value = match reference.get(key) {
+            Some(reference_value) => reference_value,
+            None => {
+                report.push(Severity::Warning, IssueKind::UnknownKey, Some(key), format!("key '{}' does not exist in en_US", key));
+                continue;
+            }
+        };
+
+        if value.trim().is_empty() {
+            report.push(Severity::Warning, IssueKind::EmptyString, Some(key), format!("key '{}' has an empty translation", key));
+            continue;
+        }
+
+        let names = match placeholders(value) {
+            Some(names) => names,
+            None => {
+                let error = message_format::parse(value).err().map(|e| e.to_string()).unwrap_or_default();
+                report.push(Severity::Error, IssueKind::InvalidSyntax, Some(key), error);
+                continue;
+            }
+        };
+
+        if let Some(reference_names) = placeholders(reference_value) {
+            if names != reference_names {
+                let list = |names: &BTreeSet<String>| names.iter().cloned().collect::<Vec<_>>().join(", ");
+                report.push(
+                    Severity::Error,
+                    IssueKind::PlaceholderMismatch,
+                    Some(key),
+                    format!("placeholders [{}] do not match en_US [{}]", list(&names), list(&reference_names)),
+                );
+                continue;
+            }
+        }
+
+        translated += 1;
+    }
+
+    report.completeness = if reference.is_empty() {
+        100.0
+    } else {
+        (translated as f32 / reference.len() as f32 * 100.0).min(100.0)
+    };
+
+    report.status = if report.issues.iter().any(|issue| issue.severity == Severity::Error) {
+        ValidationStatus::Invalid
+    } else if report.issues.is_empty() {
+        ValidationStatus::Valid
+    } else {
+        ValidationStatus::Warnings
+    };
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference() -> HashMap<String, String> {
+        [
+            ("app.title", "Brew"),
+            ("instances.count", "{count, plural, one {# instance} other {# instances}}"),
+            ("greeting", "Hello, {username}"),
+            ("settings.save", "Save"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+    }
+
+    fn pack(version: &str, id: &str, translations: &str) -> String {
+        format!(
+            r#"{{"metadata": {{"id": "{}", "version": "{}", "author": "Tester"}}{}}}"#,
+            id, version, translations
+        )
+    }
+
+    fn kinds(report: &LanguageReport) -> Vec<IssueKind> {
+        report.issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn complete_pack_is_valid() {
+        let source = pack(
+            "1.0.0",
+            "de_DE",
+            r#", "app.title": "Brew", "instances.count": "{count, plural, one {# Instanz} other {# Instanzen}}",
+                "greeting": "Hallo, {username}", "settings.save": "Speichern""#,
+        );
+        let report = lint_language(&source, Some("de_DE"), &reference());
+
+        assert_eq!(report.status, ValidationStatus::Valid);
+        assert!(report.issues.is_empty());
+        assert_eq!(report.completeness, 100.0);
+        assert_eq!(report.id, "de_DE");
+    }
+
+    #[test]
+    fn missing_and_extra_keys() {
+        let source = pack("1.0.0", "de_DE", r#", "app.title": "Brew", "settings.removed": "Alt""#);
+        let report = lint_language(&source, Some("de_DE"), &reference());
+
+        // Missing keys only lower completeness, unknown ones are reported
+        assert_eq!(kinds(&report), vec![IssueKind::UnknownKey]);
+        assert_eq!(report.issues[0].key.as_deref(), Some("settings.removed"));
+        assert_eq!(report.status, ValidationStatus::Warnings);
+        assert_eq!(report.completeness, 25.0);
+    }
+
+    #[test]
+    fn placeholder_mismatch() {
+        let source = pack("1.0.0", "de_DE", r#", "greeting": "Hallo, {name}", "settings.save": "Speichern""#);
+        let report = lint_language(&source, Some("de_DE"), &reference());
+
+        assert_eq!(kinds(&report), vec![IssueKind::PlaceholderMismatch]);
+        assert_eq!(report.issues[0].key.as_deref(), Some("greeting"));
+        assert_eq!(report.issues[0].severity, Severity::Error);
+        assert_eq!(report.status, ValidationStatus::Invalid);
+        assert_eq!(report.completeness, 25.0);
+    }
+
+    #[test]
+    fn bad_version_format() {
+        for version in ["1.0", "1.0.0-beta", "v1.0.0"] {
+            let report = lint_language(&pack(version, "de_DE", ""), Some("de_DE"), &reference());
+            assert_eq!(kinds(&report), vec![IssueKind::InvalidVersion], "version {}", version);
+            assert_eq!(report.status, ValidationStatus::Warnings);
+        }
+
+        let report = lint_language(&pack("", "de_DE", ""), Some("de_DE"), &reference());
+        assert_eq!(kinds(&report), vec![IssueKind::MissingMetadata]);
+        assert_eq!(report.status, ValidationStatus::Invalid);
+    }
+
+    #[test]
+    fn id_mismatch() {
+        let report = lint_language(&pack("1.0.0", "de_AT", ""), Some("de_DE"), &reference());
+
+        assert_eq!(kinds(&report), vec![IssueKind::IdMismatch]);
+        assert_eq!(report.id, "de_AT");
+        assert_eq!(report.issues[0].severity, Severity::Error);
+        assert_eq!(report.status, ValidationStatus::Invalid);
+
+        // Without a file name there is nothing to compare against
+        let report = lint_language(&pack("1.0.0", "de_AT", ""), None, &reference());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn invalid_json_and_duplicates() {
+        let report = lint_language("{ not json", Some("de_DE"), &reference());
+        assert_eq!(kinds(&report), vec![IssueKind::ParseError]);
+        assert_eq!(report.status, ValidationStatus::Invalid);
+
+        let source = pack("1.0.0", "de_DE", r#", "settings.save": "Sichern", "settings.save": "Speichern""#);
+        let report = lint_language(&source, Some("de_DE"), &reference());
+        assert_eq!(kinds(&report), vec![IssueKind::DuplicateKey]);
+    }
+}
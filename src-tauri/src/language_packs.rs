@@ -0,0 +1,130 @@
+// Keeps installed language packs up to date without clobbering user edits
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::Language;
+
+// What we installed for a pack, used to tell user edits from our own copies
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstalledPack {
+    pub version: String,
+    pub sha256: String,
+}
+
+// Record of installed packs keyed by file name, stored next to settings.json
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PackManifest {
+    pub packs: HashMap<String, InstalledPack>,
+}
+
+impl PackManifest {
+    // Load the manifest, starting fresh if it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, file_name: &str, version: &str, contents: &[u8]) {
+        self.packs.insert(file_name.to_string(), InstalledPack {
+            version: version.to_string(),
+            sha256: sha256_hex(contents),
+        });
+    }
+}
+
+// Outcome of syncing a single pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncOutcome {
+    Installed,
+    Updated,
+    UpToDate,
+    PreservedUserChanges,
+}
+
+pub fn sha256_hex(contents: &[u8]) -> String {
+    hex::encode(Sha256::digest(contents))
+}
+
+// Compare dotted versions numerically ("1.10.0" > "1.9.2"), missing parts count as zero
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.trim().split('.');
+    let mut b_parts = b.trim().split('.');
+
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (a_part, b_part) => {
+                let a_part = a_part.unwrap_or("0");
+                let b_part = b_part.unwrap_or("0");
+                let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+                    (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+                    _ => a_part.cmp(b_part),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+// Install or update a pack from `source` into `dest`.
+//
+// A pack is only replaced when the new one has a higher metadata version and the
+// installed file still matches what we wrote last time. Files without a record
+// (installed before packs were tracked) are updated too, keeping a `.bak` copy
+pub fn sync_pack(source: &[u8], dest: &Path, manifest: &mut PackManifest) -> Result<SyncOutcome, Box<dyn Error>> {
+    let file_name = dest
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("Invalid language pack file name")?
+        .to_string();
+    let new_version = serde_json::from_slice::<Language>(source)?.metadata.version;
+
+    if !dest.exists() {
+        fs::write(dest, source)?;
+        manifest.record(&file_name, &new_version, source);
+        return Ok(SyncOutcome::Installed);
+    }
+
+    let installed = fs::read(dest)?;
+    let installed_hash = sha256_hex(&installed);
+    if installed_hash == sha256_hex(source) {
+        manifest.record(&file_name, &new_version, source);
+        return Ok(SyncOutcome::UpToDate);
+    }
+
+    let record = manifest.packs.get(&file_name);
+    if record.is_some_and(|record| record.sha256 != installed_hash) {
+        return Ok(SyncOutcome::PreservedUserChanges);
+    }
+
+    let installed_version = serde_json::from_slice::<Language>(&installed)
+        .map(|language| language.metadata.version)
+        .unwrap_or_default();
+    if compare_versions(&new_version, &installed_version) != Ordering::Greater {
+        return Ok(SyncOutcome::UpToDate);
+    }
+
+    if record.is_none() {
+        let mut backup_name = dest.as_os_str().to_os_string();
+        backup_name.push(".bak");
+        fs::write(&backup_name, &installed)?;
+    }
+
+    fs::write(dest, source)?;
+    manifest.record(&file_name, &new_version, source);
+    Ok(SyncOutcome::Updated)
+}
@@ -0,0 +1,208 @@
+// Client for community language pack repositories.
+//
+// A repository is an index.json listing packs, served over HTTP(S) or read from
+// a local directory (file:// URL or plain path) for testing and offline use:
+//
+//   { "packs": [ { "id": "de_DE", "version": "1.0.0", "author": "...",
+//                  "completeness": 87.5, "sha256": "...", "url": "de_DE.json" } ] }
+//
+// Pack URLs are resolved relative to the index.
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::language_packs::{compare_versions, sha256_hex, sync_pack, PackManifest, SyncOutcome};
+use crate::Language;
+
+pub type RepoResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// A stalled server must not hang the settings page or an install forever
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+// A pack listed in the repository index
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexEntry {
+    pub id: String,
+    pub version: String,
+    pub author: String,
+    #[serde(default)]
+    pub completeness: f32,
+    pub sha256: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RepositoryIndex {
+    pub packs: Vec<IndexEntry>,
+}
+
+// Index entry together with what is installed locally
+#[derive(Debug, Serialize, Clone)]
+pub struct RepositoryPack {
+    #[serde(flatten)]
+    pub entry: IndexEntry,
+    pub installed_version: Option<String>,
+    pub update_available: bool,
+}
+
+// Where the index or a pack lives
+#[derive(Debug, Clone, PartialEq)]
+enum Location {
+    Remote(Url),
+    Local(PathBuf),
+}
+
+impl Location {
+    fn parse(location: &str) -> RepoResult<Self> {
+        if location.starts_with("http://") || location.starts_with("https://") {
+            return Ok(Location::Remote(Url::parse(location)?));
+        }
+        if location.starts_with("file://") {
+            let path = Url::parse(location)?
+                .to_file_path()
+                .map_err(|_| format!("Invalid file URL: {}", location))?;
+            return Ok(Location::Local(path));
+        }
+        Ok(Location::Local(PathBuf::from(location)))
+    }
+
+    // Resolve a pack URL from the index relative to the index location. A remote
+    // index may only point at remote packs, never at files on this machine
+    fn join(&self, relative: &str) -> RepoResult<Self> {
+        if relative.contains("://") {
+            let location = Location::parse(relative)?;
+            if matches!((self, &location), (Location::Remote(_), Location::Local(_))) {
+                return Err(format!("Remote index cannot reference local file {}", relative).into());
+            }
+            return Ok(location);
+        }
+        match self {
+            Location::Remote(url) => Ok(Location::Remote(url.join(relative)?)),
+            Location::Local(path) => {
+                let base = path.parent().unwrap_or(Path::new(""));
+                Ok(Location::Local(base.join(relative)))
+            }
+        }
+    }
+
+    async fn fetch(&self) -> RepoResult<Vec<u8>> {
+        match self {
+            Location::Remote(url) => {
+                let client = reqwest::Client::builder()
+                    .connect_timeout(CONNECT_TIMEOUT)
+                    .timeout(REQUEST_TIMEOUT)
+                    .build()?;
+                let response = client.get(url.clone()).send().await?.error_for_status()?;
+                Ok(response.bytes().await?.to_vec())
+            },
+            Location::Local(path) => Ok(fs::read(path)?),
+        }
+    }
+}
+
+// Fetch and parse the repository index
+pub async fn fetch_index(index_url: &str) -> RepoResult<RepositoryIndex> {
+    let bytes = Location::parse(index_url)?.fetch().await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+// Version of a pack installed in the languages directory
+fn installed_version(languages_path: &Path, id: &str) -> Option<String> {
+    let contents = fs::read_to_string(languages_path.join(format!("{}.json", id))).ok()?;
+    serde_json::from_str::<Language>(&contents)
+        .ok()
+        .map(|language| language.metadata.version)
+}
+
+// List repository packs with their local install state
+pub async fn list_packs(index_url: &str, languages_path: &Path) -> RepoResult<Vec<RepositoryPack>> {
+    let index = fetch_index(index_url).await?;
+
+    Ok(index
+        .packs
+        .into_iter()
+        .map(|entry| {
+            let installed_version = installed_version(languages_path, &entry.id);
+            let update_available = installed_version
+                .as_deref()
+                .is_some_and(|installed| compare_versions(&entry.version, installed) == Ordering::Greater);
+            RepositoryPack { entry, installed_version, update_available }
+        })
+        .collect())
+}
+
+// Download, verify and install a pack into the languages directory
+pub async fn install_pack(
+    index_url: &str,
+    id: &str,
+    languages_path: &Path,
+    manifest_path: &Path,
+) -> RepoResult<(Language, SyncOutcome)> {
+    let index_location = Location::parse(index_url)?;
+    let index: RepositoryIndex = serde_json::from_slice(&index_location.fetch().await?)?;
+    let entry = index
+        .packs
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("Language pack {} is not in the repository", id))?;
+
+    // Pack ids end up in file names, don't let an index write outside the languages directory
+    if entry.id.is_empty() || entry.id.contains(['/', '\\', '.']) {
+        return Err(format!("Invalid language pack id: {}", entry.id).into());
+    }
+
+    let bytes = index_location.join(&entry.url)?.fetch().await?;
+
+    let checksum = sha256_hex(&bytes);
+    if !checksum.eq_ignore_ascii_case(&entry.sha256) {
+        return Err(format!("Checksum mismatch for {}: expected {}, got {}", id, entry.sha256, checksum).into());
+    }
+
+    let language: Language = serde_json::from_slice(&bytes)?;
+    if language.metadata.id != entry.id {
+        return Err(format!("Pack metadata id {} does not match index id {}", language.metadata.id, entry.id).into());
+    }
+
+    let mut manifest = PackManifest::load(manifest_path);
+    let outcome = sync_pack(&bytes, &languages_path.join(format!("{}.json", entry.id)), &mut manifest)
+        .map_err(|e| e.to_string())?;
+    manifest.save(manifest_path).map_err(|e| e.to_string())?;
+
+    Ok((language, outcome))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_index_only_references_remote_packs() {
+        let remote = Location::parse("https://example.com/packs/index.json").unwrap();
+        assert_eq!(
+            remote.join("de_DE.json").unwrap(),
+            Location::Remote(Url::parse("https://example.com/packs/de_DE.json").unwrap())
+        );
+        assert_eq!(
+            remote.join("https://cdn.example.com/de_DE.json").unwrap(),
+            Location::Remote(Url::parse("https://cdn.example.com/de_DE.json").unwrap())
+        );
+        assert!(remote.join("file:///etc/passwd").is_err());
+        // A plain relative path stays on the same server
+        assert!(matches!(remote.join("/etc/passwd").unwrap(), Location::Remote(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn local_index_references_local_and_remote_packs() {
+        let local = Location::parse("file:///srv/packs/index.json").unwrap();
+        assert_eq!(local, Location::Local(PathBuf::from("/srv/packs/index.json")));
+        assert_eq!(local.join("de_DE.json").unwrap(), Location::Local(PathBuf::from("/srv/packs/de_DE.json")));
+        assert_eq!(local.join("file:///opt/de_DE.json").unwrap(), Location::Local(PathBuf::from("/opt/de_DE.json")));
+        assert!(matches!(local.join("https://example.com/de_DE.json").unwrap(), Location::Remote(_)));
+    }
+}
@@ -0,0 +1,485 @@
+// Builds the Java command line for an instance and starts the game
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use md5::{Digest, Md5};
+use serde::Serialize;
+
+use crate::crash::CrashSummary;
+use crate::instances::{self, Instance};
+use crate::jvm;
+use crate::versions::{
+    self, artifact_path, rules_allow, rules_allow_with_features, Argument, ArgumentValue, VersionJson,
+};
+
+pub type LaunchResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+const LAUNCHER_NAME: &str = "intelligence";
+
+// JVM arguments used by versions from before the `arguments` format (pre-1.13)
+const LEGACY_JVM_ARGUMENTS: &[&str] = &[
+    "-Djava.library.path=${natives_directory}",
+    "-cp",
+    "${classpath}",
+];
+
+// A game started by the launcher, as shown in the UI and the tray
+#[derive(Debug, Serialize, Clone)]
+pub struct RunningGame {
+    pub pid: u32,
+    pub instance_id: String,
+    pub instance_name: String,
+    pub game_version: String,
+    pub loader: Option<String>,
+    // Server joined on launch
+    pub server: Option<String>,
+    // Unix timestamp of the launch
+    pub started_at: u64,
+}
+
+// Running game together with its process handle
+#[derive(Debug)]
+pub struct GameProcess {
+    pub info: RunningGame,
+    pub child: Child,
+}
+
+// Payload of the `game-exited` event
+#[derive(Debug, Serialize, Clone)]
+pub struct GameExited {
+    pub instance_id: String,
+    pub exit_code: Option<i32>,
+    // Analysis of the crash report when the game didn't exit cleanly
+    pub crash: Option<CrashSummary>,
+}
+
+// Default port used when a server address has none
+const DEFAULT_SERVER_PORT: &str = "25565";
+
+// What to launch an instance with
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    // Offline player name
+    pub username: String,
+    // Server to join right away, "host" or "host:port"
+    pub server: Option<String>,
+}
+
+#[cfg(windows)]
+const CLASSPATH_SEPARATOR: &str = ";";
+#[cfg(not(windows))]
+const CLASSPATH_SEPARATOR: &str = ":";
+
+// UUID the vanilla server assigns to an offline-mode player, matching Java's
+// UUID.nameUUIDFromBytes("OfflinePlayer:" + name)
+pub fn offline_uuid(username: &str) -> String {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&Md5::digest(format!("OfflinePlayer:{}", username).as_bytes()));
+    bytes[6] = (bytes[6] & 0x0f) | 0x30;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+// Java executable for an instance: its own override, then JAVA_HOME, then PATH
+pub fn find_java(instance: &Instance) -> PathBuf {
+    if let Some(java) = &instance.java_path {
+        return java.clone();
+    }
+
+    let executable = if cfg!(windows) { "java.exe" } else { "java" };
+    if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+        let java = PathBuf::from(java_home).join("bin").join(executable);
+        if java.is_file() {
+            return java;
+        }
+    }
+
+    PathBuf::from(executable)
+}
+
+// Replace ${name} placeholders in a launch argument
+fn substitute(argument: &str, variables: &HashMap<&str, String>) -> String {
+    let mut result = argument.to_string();
+    for (name, value) in variables {
+        result = result.replace(&format!("${{{}}}", name), value);
+    }
+    result
+}
+
+// Expand rule-gated arguments into plain strings
+fn expand_arguments(arguments: &[Argument], variables: &HashMap<&str, String>, features: &[&str]) -> Vec<String> {
+    let mut expanded = Vec::new();
+
+    for argument in arguments {
+        match argument {
+            Argument::Plain(value) => expanded.push(substitute(value, variables)),
+            Argument::Conditional { rules, value } => {
+                if !rules_allow_with_features(rules, features) {
+                    continue;
+                }
+                match value {
+                    ArgumentValue::One(value) => expanded.push(substitute(value, variables)),
+                    ArgumentValue::Many(values) => {
+                        expanded.extend(values.iter().map(|value| substitute(value, variables)))
+                    },
+                }
+            }
+        }
+    }
+
+    expanded
+}
+
+// Classpath entries: allowed libraries followed by the client jar
+fn build_classpath(data_path: &Path, version: &VersionJson) -> LaunchResult<Vec<PathBuf>> {
+    let mut classpath = Vec::new();
+
+    for library in version.libraries.iter().filter(|library| rules_allow(&library.rules)) {
+        let Some(artifact) = library.artifact() else { continue };
+        let Some(path) = artifact_path(data_path, library, &artifact) else { continue };
+        if !path.is_file() {
+            return Err(format!("Library {} is missing, reinstall the version", library.name).into());
+        }
+        if !classpath.contains(&path) {
+            classpath.push(path);
+        }
+    }
+
+    let jar_id = version.jar.clone().unwrap_or_else(|| version.id.clone());
+    let jar = versions::version_jar_path(data_path, &jar_id);
+    if !jar.is_file() {
+        return Err(format!("Client jar for {} is missing, reinstall the version", jar_id).into());
+    }
+    classpath.push(jar);
+
+    Ok(classpath)
+}
+
+// Extract pre-1.19 native libraries into the instance's natives directory
+fn extract_natives(data_path: &Path, version: &VersionJson, natives_dir: &Path) -> LaunchResult<()> {
+    fs::create_dir_all(natives_dir)?;
+
+    for library in version.libraries.iter().filter(|library| rules_allow(&library.rules)) {
+        let Some(artifact) = library.native_artifact() else { continue };
+        let Some(path) = artifact_path(data_path, library, &artifact) else { continue };
+        let excluded = library.extract.as_ref().map(|extract| extract.exclude.clone()).unwrap_or_default();
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&path)?)?;
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            let Some(name) = entry.enclosed_name() else { continue };
+            let name_str = name.to_string_lossy().replace('\\', "/");
+            if entry.is_dir() || excluded.iter().any(|prefix| name_str.starts_with(prefix.as_str())) {
+                continue;
+            }
+
+            let dest = natives_dir.join(&name);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut entry, &mut fs::File::create(dest)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Build the command that starts an instance as an offline player
+pub fn build_command(data_path: &Path, instance: &Instance, options: &LaunchOptions) -> LaunchResult<Command> {
+    let username = options.username.as_str();
+    let version = versions::load_version(data_path, &instance.game_version)?;
+    let main_class = version
+        .main_class
+        .clone()
+        .ok_or_else(|| format!("Version {} has no main class", version.id))?;
+
+    let game_dir = instance.game_dir(data_path);
+    let natives_dir = instance.natives_dir(data_path);
+    fs::create_dir_all(&game_dir)?;
+    extract_natives(data_path, &version, &natives_dir)?;
+
+    let classpath = build_classpath(data_path, &version)?
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join(CLASSPATH_SEPARATOR);
+
+    let assets_root = versions::assets_path(data_path);
+    let assets_index = version
+        .asset_index
+        .as_ref()
+        .map(|index| index.id.clone())
+        .or_else(|| version.assets.clone())
+        .unwrap_or_else(|| "legacy".to_string());
+    let path_string = |path: &Path| path.to_string_lossy().to_string();
+
+    let mut variables: HashMap<&str, String> = HashMap::from([
+        ("auth_player_name", username.to_string()),
+        ("auth_uuid", offline_uuid(username).replace('-', "")),
+        ("auth_access_token", "0".to_string()),
+        ("auth_session", "0".to_string()),
+        ("auth_xuid", "0".to_string()),
+        ("clientid", "0".to_string()),
+        ("user_type", "legacy".to_string()),
+        ("user_properties", "{}".to_string()),
+        ("version_name", version.id.clone()),
+        ("version_type", version.kind.clone().unwrap_or_else(|| "release".to_string())),
+        ("game_directory", path_string(&game_dir)),
+        ("assets_root", path_string(&assets_root)),
+        ("game_assets", path_string(&assets_root.join("virtual").join(&assets_index))),
+        ("assets_index_name", assets_index),
+        ("natives_directory", path_string(&natives_dir)),
+        ("library_directory", path_string(&versions::libraries_path(data_path))),
+        ("classpath_separator", CLASSPATH_SEPARATOR.to_string()),
+        ("classpath", classpath),
+        ("launcher_name", LAUNCHER_NAME.to_string()),
+        ("launcher_version", env!("CARGO_PKG_VERSION").to_string()),
+    ]);
+
+    let mut features = Vec::new();
+    if let Some(server) = &options.server {
+        features.push("is_quick_play_multiplayer");
+        variables.insert("quickPlayMultiplayer", server.clone());
+    }
+
+    let (jvm_arguments, mut game_arguments) = match (&version.arguments, &version.minecraft_arguments) {
+        (Some(arguments), _) => (
+            expand_arguments(&arguments.jvm, &variables, &features),
+            expand_arguments(&arguments.game, &variables, &features),
+        ),
+        (None, Some(legacy)) => (
+            LEGACY_JVM_ARGUMENTS.iter().map(|argument| substitute(argument, &variables)).collect(),
+            legacy.split_whitespace().map(|argument| substitute(argument, &variables)).collect(),
+        ),
+        (None, None) => return Err(format!("Version {} has no launch arguments", version.id).into()),
+    };
+
+    // Versions before quick play (1.20) join a server through --server/--port
+    if let Some(server) = &options.server {
+        if !game_arguments.iter().any(|argument| argument == "--quickPlayMultiplayer") {
+            let (host, port) = server.rsplit_once(':').unwrap_or((server, DEFAULT_SERVER_PORT));
+            game_arguments.extend(["--server".to_string(), host.to_string(), "--port".to_string(), port.to_string()]);
+        }
+    }
+
+    // Java goes after the wrapper's own arguments, e.g. `mangohud --dlsym java ...`
+    let java = find_java(instance);
+    let mut command = match instance.jvm.wrapper_command() {
+        Some(wrapper) => {
+            let mut command = Command::new(&wrapper[0]);
+            command.args(&wrapper[1..]).arg(java);
+            command
+        },
+        None => Command::new(java),
+    };
+    command
+        .args(instance.jvm.arguments())
+        .args(jvm_arguments)
+        .arg(main_class)
+        .args(game_arguments)
+        .envs(&instance.jvm.env)
+        .current_dir(&game_dir);
+
+    Ok(command)
+}
+
+// Start an instance and record it as played, after its pre-launch hook succeeds.
+// The caller decides how stdio is handled
+pub fn launch(data_path: &Path, instance: &mut Instance, options: &LaunchOptions, configure: impl FnOnce(&mut Command)) -> LaunchResult<Child> {
+    // The wrapper, environment and flags run code as much as the hooks do
+    if !jvm::commands_approved(&instance.id, &instance.jvm) {
+        return Err(format!(
+            "The launch commands of {} were changed outside the launcher, confirm them in its JVM settings before launching",
+            instance.name
+        )
+        .into());
+    }
+
+    let mut command = build_command(data_path, instance, options)?;
+    configure(&mut command);
+
+    if let Some(script) = instance.jvm.pre_launch.as_deref().filter(|script| !script.trim().is_empty()) {
+        let status = jvm::hook_command(script, data_path, instance)
+            .status()
+            .map_err(|e| format!("Failed to run pre-launch hook: {}", e))?;
+        if !status.success() {
+            return Err(format!("Pre-launch hook failed with {}", status).into());
+        }
+    }
+
+    let child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start Java ({:?}): {}", command.get_program(), e))?;
+
+    if let Err(e) = instances::mark_played(data_path, instance) {
+        eprintln!("Failed to update last played time of {}: {}", instance.id, e);
+    }
+
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_data_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("intelligence-launcher-{}-{}", name, std::process::id()))
+    }
+
+    fn write_file(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"").unwrap();
+    }
+
+    fn write_version(data_path: &Path, version: serde_json::Value) {
+        let path = versions::version_json_path(data_path, version["id"].as_str().unwrap());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, version.to_string()).unwrap();
+    }
+
+    fn instance(game_version: &str) -> Instance {
+        serde_json::from_value(json!({ "id": "test", "name": "Test", "game_version": game_version })).unwrap()
+    }
+
+    fn options(server: Option<&str>) -> LaunchOptions {
+        LaunchOptions { username: "Notch".to_string(), server: server.map(String::from) }
+    }
+
+    fn args(command: &Command) -> Vec<String> {
+        command.get_args().map(|arg| arg.to_string_lossy().to_string()).collect()
+    }
+
+    #[test]
+    fn offline_uuid_matches_the_server() {
+        assert_eq!(offline_uuid("Notch"), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+    }
+
+    #[test]
+    fn arguments_are_substituted_and_filtered() {
+        let variables = HashMap::from([("auth_player_name", "Notch".to_string()), ("quickPlayMultiplayer", "mc.example.org".to_string())]);
+        let arguments: Vec<Argument> = serde_json::from_value(json!([
+            "--username",
+            "${auth_player_name}",
+            { "rules": [{ "action": "allow", "features": { "is_quick_play_multiplayer": true } }], "value": ["--quickPlayMultiplayer", "${quickPlayMultiplayer}"] },
+            { "rules": [{ "action": "allow", "features": { "is_demo_user": true } }], "value": "--demo" },
+            { "rules": [{ "action": "allow", "os": { "name": "no-such-os" } }], "value": "-XstartOnFirstThread" }
+        ]))
+        .unwrap();
+
+        assert_eq!(expand_arguments(&arguments, &variables, &[]), ["--username", "Notch"]);
+        assert_eq!(
+            expand_arguments(&arguments, &variables, &["is_quick_play_multiplayer"]),
+            ["--username", "Notch", "--quickPlayMultiplayer", "mc.example.org"]
+        );
+        assert_eq!(substitute("${unknown}", &variables), "${unknown}");
+    }
+
+    #[test]
+    fn classpath_lists_allowed_libraries_then_the_client_jar() {
+        let data_path = temp_data_path("classpath");
+        write_version(&data_path, json!({
+            "id": "1.21.1",
+            "libraries": [
+                { "name": "org.ow2.asm:asm:9.6" },
+                { "name": "org.ow2.asm:asm:9.6" },
+                { "name": "ca.weblite:java-objc-bridge:1.1", "rules": [{ "action": "allow", "os": { "name": "no-such-os" } }] },
+                { "name": "org.lwjgl:lwjgl-platform:2.9.4", "natives": { "linux": "natives-linux" }, "downloads": { "classifiers": {} } }
+            ]
+        }));
+        write_version(&data_path, json!({ "id": "fabric", "inheritsFrom": "1.21.1", "libraries": [{ "name": "net.fabricmc:fabric-loader:0.16.9" }] }));
+        let version = versions::load_version(&data_path, "fabric").unwrap();
+
+        let error = build_classpath(&data_path, &version).unwrap_err().to_string();
+        assert!(error.contains("net.fabricmc:fabric-loader:0.16.9"));
+
+        let libraries = versions::libraries_path(&data_path);
+        let loader = libraries.join("net/fabricmc/fabric-loader/0.16.9/fabric-loader-0.16.9.jar");
+        let asm = libraries.join("org/ow2/asm/asm/9.6/asm-9.6.jar");
+        write_file(&loader);
+        write_file(&asm);
+        assert!(build_classpath(&data_path, &version).unwrap_err().to_string().contains("Client jar for 1.21.1"));
+
+        let jar = versions::version_jar_path(&data_path, "1.21.1");
+        write_file(&jar);
+        assert_eq!(build_classpath(&data_path, &version).unwrap(), [loader, asm, jar]);
+        fs::remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn legacy_versions_join_servers_with_server_and_port() {
+        let data_path = temp_data_path("legacy");
+        write_version(&data_path, json!({
+            "id": "1.12.2",
+            "mainClass": "net.minecraft.client.main.Main",
+            "minecraftArguments": "--username ${auth_player_name} --uuid ${auth_uuid} --gameDir ${game_directory} --assetIndex ${assets_index_name}",
+            "assets": "1.12"
+        }));
+        write_file(&versions::version_jar_path(&data_path, "1.12.2"));
+        let mut instance = instance("1.12.2");
+        instance.jvm.max_memory = Some(2048);
+
+        let command = build_command(&data_path, &instance, &options(Some("mc.example.org"))).unwrap();
+        let game_dir = instance.game_dir(&data_path);
+        let jar = versions::version_jar_path(&data_path, "1.12.2");
+        assert_eq!(args(&command), [
+            "-Xmx2048M".to_string(),
+            format!("-Djava.library.path={}", instance.natives_dir(&data_path).display()),
+            "-cp".to_string(),
+            jar.to_string_lossy().to_string(),
+            "net.minecraft.client.main.Main".to_string(),
+            "--username".to_string(),
+            "Notch".to_string(),
+            "--uuid".to_string(),
+            "b50ad385829d3141a2167e7d7539ba7f".to_string(),
+            "--gameDir".to_string(),
+            game_dir.to_string_lossy().to_string(),
+            "--assetIndex".to_string(),
+            "1.12".to_string(),
+            "--server".to_string(),
+            "mc.example.org".to_string(),
+            "--port".to_string(),
+            "25565".to_string(),
+        ]);
+        assert_eq!(command.get_current_dir(), Some(game_dir.as_path()));
+        fs::remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn quick_play_versions_use_the_feature_and_the_wrapper() {
+        let data_path = temp_data_path("quick-play");
+        write_version(&data_path, json!({
+            "id": "1.21.1",
+            "mainClass": "net.minecraft.client.main.Main",
+            "arguments": {
+                "game": [
+                    "--version",
+                    "${version_name}",
+                    { "rules": [{ "action": "allow", "features": { "is_quick_play_multiplayer": true } }], "value": ["--quickPlayMultiplayer", "${quickPlayMultiplayer}"] }
+                ],
+                "jvm": ["-cp", "${classpath}"]
+            }
+        }));
+        write_file(&versions::version_jar_path(&data_path, "1.21.1"));
+        let mut instance = instance("1.21.1");
+        instance.jvm.wrapper = Some("gamemoderun  mangohud".to_string());
+        instance.jvm.env.insert("MANGOHUD".to_string(), "1".to_string());
+
+        let command = build_command(&data_path, &instance, &options(Some("mc.example.org:25566"))).unwrap();
+        assert_eq!(command.get_program(), "gamemoderun");
+        let arguments = args(&command);
+        assert_eq!(arguments[0], "mangohud");
+        assert_eq!(arguments[1], find_java(&instance).to_string_lossy());
+        assert_eq!(&arguments[4..], ["net.minecraft.client.main.Main", "--version", "1.21.1", "--quickPlayMultiplayer", "mc.example.org:25566"]);
+        assert!(command.get_envs().any(|(name, value)| name == "MANGOHUD" && value == Some("1".as_ref())));
+
+        let command = build_command(&data_path, &instance, &options(None)).unwrap();
+        assert!(!args(&command).iter().any(|arg| arg.starts_with("--quickPlay")));
+        fs::remove_dir_all(data_path).unwrap();
+    }
+}
@@ -3,38 +3,249 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use std::error::Error;
 use std::fs::create_dir_all;
+use std::io::Write;
+use std::process::Stdio;
+
+mod backups;
+pub mod cli;
+mod crash;
+mod deep_link;
+mod diagnostics;
+mod discord_ipc;
+mod instances;
+mod jvm;
+mod language_lint;
+mod language_packs;
+mod language_repository;
+mod launcher;
+mod locale;
+mod logs;
+mod message_format;
+mod nbt;
+mod packs;
+mod paths;
+mod presence;
+mod saves;
+pub mod single_instance;
+mod tray;
+mod versions;
+mod watcher;
+
+// Built-in defaults and known keys generated from public/languages/en_US.json
+mod translations {
+    include!(concat!(env!("OUT_DIR"), "/translations.rs"));
+}
+
+// Color theme of the launcher UI
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+    Oled,
+}
+
+impl Theme {
+    // Serialized names, reported when a value is rejected
+    const VALUES: &'static [&'static str] = &["system", "light", "dark", "oled"];
+}
+
+// Window titlebar style, applied on startup
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TitlebarStyle {
+    #[default]
+    Custom,
+    Native,
+    Macos,
+}
+
+impl TitlebarStyle {
+    const VALUES: &'static [&'static str] = &["custom", "native", "macos"];
+}
 
 // Define settings structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Settings {
-    pub theme: String,
+    pub theme: Theme,
     pub discord_rpc: bool,
     pub advanced_rendering: bool,
     pub language: String,
-    pub titlebar_style: String,
+    pub titlebar_style: TitlebarStyle,
+    // Custom location of game data, set by migrate_data_directory
+    #[serde(default)]
+    pub data_directory: Option<PathBuf>,
+    // Index of community language packs, an http(s)/file URL or a local path
+    #[serde(default)]
+    pub language_repository_url: Option<String>,
+    // Offline player name used when launching the game
+    #[serde(default = "default_username")]
+    pub username: String,
+    // Hide the window to the system tray instead of quitting when it is closed
+    #[serde(default)]
+    pub minimize_to_tray: bool,
+    // World backup schedule and retention
+    #[serde(default)]
+    pub backups: backups::BackupSettings,
+}
+
+fn default_username() -> String {
+    "Player".to_string()
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
-            theme: "system".to_string(),
+            theme: Theme::default(),
             discord_rpc: true,
             advanced_rendering: true,
-            language: "en_US".to_string(),
-            titlebar_style: "custom".to_string(),
+            language: translations::DEFAULT_LANGUAGE_ID.to_string(),
+            titlebar_style: TitlebarStyle::default(),
+            data_directory: None,
+            language_repository_url: None,
+            username: default_username(),
+            minimize_to_tray: false,
+            backups: backups::BackupSettings::default(),
         }
     }
 }
 
-// Define language metadata structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Structured error returned by settings commands
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SettingsError {
+    // A field holds a value that is not accepted
+    InvalidValue {
+        field: String,
+        value: String,
+        expected: Vec<String>,
+    },
+    // A settings patch is malformed or touches unknown keys
+    InvalidPatch { message: String },
+    // Settings could not be stored or applied
+    Internal { message: String },
+}
+
+impl SettingsError {
+    fn internal(message: impl Into<String>) -> Self {
+        SettingsError::Internal { message: message.into() }
+    }
+}
+
+// Deserialize settings sent by the frontend. Enum fields are checked first so a
+// bad theme or titlebar style is reported with the accepted values
+fn settings_from_value(value: serde_json::Value) -> Result<Settings, SettingsError> {
+    for (field, expected) in [("theme", Theme::VALUES), ("titlebar_style", TitlebarStyle::VALUES)] {
+        let Some(found) = value.get(field) else { continue };
+        if !found.as_str().is_some_and(|found| expected.contains(&found)) {
+            return Err(SettingsError::InvalidValue {
+                field: field.to_string(),
+                value: found.as_str().map(str::to_string).unwrap_or_else(|| found.to_string()),
+                expected: expected.iter().map(|value| value.to_string()).collect(),
+            });
+        }
+    }
+
+    serde_json::from_value(value).map_err(|e| SettingsError::InvalidPatch { message: e.to_string() })
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::InvalidValue { field, value, expected } => write!(
+                f,
+                "Invalid value '{}' for '{}', expected one of: {}",
+                value,
+                field,
+                expected.join(", ")
+            ),
+            SettingsError::InvalidPatch { message } => write!(f, "Invalid settings patch: {}", message),
+            SettingsError::Internal { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for SettingsError {}
+
+// Payload of the `settings-changed` event
+#[derive(Debug, Serialize, Clone)]
+pub struct SettingsChanged {
+    pub settings: Settings,
+    pub changed: Vec<String>,
+    pub restart_required: Vec<String>,
+}
+
+impl SettingsChanged {
+    // The tray menu shows translated labels and the instances in the data directory
+    fn tray_refresh_needed(&self) -> bool {
+        self.changed.iter().any(|key| TRAY_KEYS.contains(&key.as_str()))
+    }
+}
+
+// Settings keys that only take effect after restarting the app
+const RESTART_REQUIRED_KEYS: &[&str] = &["titlebar_style"];
+
+// Settings keys the tray menu depends on
+const TRAY_KEYS: &[&str] = &["language", "data_directory"];
+
+// Writing direction of a language
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TextDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl TextDirection {
+    // Guess the direction from the language code for packs that don't declare it
+    fn for_language(id: &str) -> Self {
+        let language = id.split(['_', '-']).next().unwrap_or_default();
+        match language {
+            "ar" | "he" | "fa" | "ur" | "yi" | "ps" | "sd" | "ug" | "dv" | "ckb" => TextDirection::Rtl,
+            _ => TextDirection::Ltr,
+        }
+    }
+}
+
+// Define language metadata structure, fields after author are optional for older packs
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct LanguageMetadata {
     pub id: String,
     pub version: String,
     pub author: String,
+    // Name of the language in itself, e.g. "Русский"
+    #[serde(default)]
+    pub native_name: Option<String>,
+    // Name of the language in English, e.g. "Russian"
+    #[serde(default)]
+    pub english_name: Option<String>,
+    #[serde(default)]
+    pub direction: Option<TextDirection>,
+    // Font family the UI should prefer for this script
+    #[serde(default)]
+    pub font_family: Option<String>,
+    // Locale used for dates and numbers, e.g. "pt_BR" for a generic "pt" pack
+    #[serde(default)]
+    pub format_locale: Option<String>,
+}
+
+impl LanguageMetadata {
+    // Fill in fields older packs don't declare
+    fn with_defaults(mut self) -> Self {
+        if self.direction.is_none() {
+            self.direction = Some(TextDirection::for_language(&self.id));
+        }
+        if self.format_locale.is_none() {
+            self.format_locale = Some(self.id.clone());
+        }
+        self
+    }
 }
 
 // Define complete language structure
@@ -52,26 +263,16 @@ struct AppState {
     settings: Mutex<Settings>,
     settings_path: Mutex<Option<PathBuf>>,
     current_language: Mutex<Language>,
+    settings_recovery: Mutex<Option<SettingsRecovery>>,
+    // Link the app was started with, sent to the frontend once it has loaded
+    pending_deep_link: Mutex<Option<Result<deep_link::DeepLink, deep_link::DeepLinkFailed>>>,
+    // Games started from this launcher
+    running_games: Mutex<Vec<launcher::GameProcess>>,
 }
 
-// Function to get the settings directory path based on OS
+// Function to get the settings directory path, see paths::resolve for the lookup order
 fn get_settings_path(_app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>> {
-    let path = if cfg!(target_os = "linux") {
-        // Linux: ~/.intelligence-app
-        let home = dirs::home_dir()
-            .ok_or_else(|| "Could not find home directory")?;
-        home.join(".intelligence-app")
-    } else if cfg!(target_os = "macos") {
-        // macOS: ~/Library/Application Support/intelligence-app
-        let home = dirs::home_dir()
-            .ok_or_else(|| "Could not find home directory")?;
-        home.join("Library/Application Support/intelligence-app")
-    } else {
-        // Windows or fallback
-        let home = dirs::config_dir()
-            .ok_or_else(|| "Could not find config directory")?;
-        home.join("intelligence-app")
-    };
+    let path = paths::resolve()?.config;
 
     // Create directory if it doesn't exist
     if !path.exists() {
@@ -81,6 +282,23 @@ fn get_settings_path(_app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>>
     Ok(path)
 }
 
+// Get path to the game data directory, honoring a migrated location from settings
+fn get_data_path(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>> {
+    let custom = app_handle
+        .try_state::<AppState>()
+        .and_then(|state| state.settings.lock().unwrap().data_directory.clone());
+    let path = match custom {
+        Some(path) => path,
+        None => paths::resolve()?.data,
+    };
+    
+    if !path.exists() {
+        create_dir_all(&path)?;
+    }
+    
+    Ok(path)
+}
+
 // Get path to settings.json
 fn get_settings_file_path(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>> {
     let path = get_settings_path(app_handle)?;
@@ -100,24 +318,155 @@ fn get_languages_path(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>>
     Ok(languages_path)
 }
 
+// Get path to user overrides merged on top of installed language packs
+fn get_language_overrides_path(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>> {
+    let overrides_path = get_languages_path(app_handle)?.join("overrides");
+    
+    if !overrides_path.exists() {
+        create_dir_all(&overrides_path)?;
+    }
+    
+    Ok(overrides_path)
+}
+
+// Get path to the record of installed language packs
+fn get_language_manifest_path(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn Error>> {
+    Ok(get_settings_path(app_handle)?.join("language-packs.json"))
+}
+
 // Get path to a specific language file
 fn get_language_file_path(app_handle: &AppHandle, lang_code: &str) -> Result<PathBuf, Box<dyn Error>> {
     let languages_path = get_languages_path(app_handle)?;
     Ok(languages_path.join(format!("{}.json", lang_code)))
 }
 
-// Load settings from file
-fn load_settings(path: &Path) -> Result<Settings, Box<dyn Error>> {
-    if path.exists() {
-        let contents = fs::read_to_string(path)?;
-        let settings = serde_json::from_str(&contents)?;
-        Ok(settings)
-    } else {
-        Ok(Settings::default())
+// Describes where settings came from after the main file failed to load
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoverySource {
+    Backup,
+    Defaults,
+    // The file was kept, only fields with invalid values were reset
+    Fields,
+}
+
+// Recovery notice sent to the frontend through the `settings-recovered` event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SettingsRecovery {
+    pub source: RecoverySource,
+    pub error: String,
+    // Dotted names of the fields reset to their defaults
+    #[serde(default)]
+    pub reset_fields: Vec<String>,
+}
+
+// Build a path next to the settings file with an extra suffix (e.g. settings.json.bak)
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+// Read and parse a settings file, see parse_settings
+fn read_settings_file(path: &Path) -> Result<(Settings, Vec<String>), Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(parse_settings(&contents)?)
+}
+
+// Parse settings, resetting fields that don't deserialize to their defaults
+// instead of rejecting the whole file. Returns the names of the reset fields
+fn parse_settings(contents: &str) -> Result<(Settings, Vec<String>), serde_json::Error> {
+    let file: serde_json::Value = serde_json::from_str(contents)?;
+    let fields = match &file {
+        serde_json::Value::Object(fields) => fields,
+        _ => return serde_json::from_value(file).map(|settings| (settings, Vec::new())),
+    };
+    if let Ok(settings) = serde_json::from_value(file.clone()) {
+        return Ok((settings, Vec::new()));
+    }
+
+    let mut value = serde_json::to_value(Settings::default())?;
+    let mut reset = Vec::new();
+    overlay_valid_fields(&mut value, "", fields, &mut reset);
+    Ok((serde_json::from_value(value)?, reset))
+}
+
+// Copy fields into the object at `pointer` one at a time, keeping each only if the
+// settings still deserialize. An object that doesn't fit as a whole is merged
+// field by field, so one bad value inside it only resets itself
+fn overlay_valid_fields(
+    root: &mut serde_json::Value,
+    pointer: &str,
+    fields: &serde_json::Map<String, serde_json::Value>,
+    reset: &mut Vec<String>,
+) {
+    for (key, value) in fields {
+        let Some(target) = root.pointer_mut(pointer).and_then(serde_json::Value::as_object_mut) else { return };
+        let previous = target.insert(key.clone(), value.clone());
+        if serde_json::from_value::<Settings>(root.clone()).is_ok() {
+            continue;
+        }
+
+        if let Some(target) = root.pointer_mut(pointer).and_then(serde_json::Value::as_object_mut) {
+            match previous {
+                Some(previous) => target.insert(key.clone(), previous),
+                None => target.remove(key),
+            };
+        }
+
+        let field_pointer = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+        match (root.pointer(&field_pointer), value) {
+            (Some(serde_json::Value::Object(_)), serde_json::Value::Object(nested)) => {
+                overlay_valid_fields(root, &field_pointer, nested, reset)
+            },
+            _ => reset.push(if pointer.is_empty() { key.clone() } else { format!("{}.{}", pointer[1..].replace('/', "."), key) }),
+        }
+    }
+}
+
+// Load settings from file, falling back to the backup or defaults if it is corrupted
+fn load_settings(path: &Path) -> Result<(Settings, Option<SettingsRecovery>), Box<dyn Error>> {
+    if !path.exists() {
+        return Ok((Settings::default(), None));
+    }
+
+    let error = match read_settings_file(path) {
+        Ok((settings, reset_fields)) if reset_fields.is_empty() => return Ok((settings, None)),
+        // Keep the file, the invalid fields are replaced on the next save
+        Ok((settings, reset_fields)) => {
+            let error = format!("Invalid values reset to defaults: {}", reset_fields.join(", "));
+            eprintln!("Settings in {:?}: {}", path, error);
+            return Ok((settings, Some(SettingsRecovery { source: RecoverySource::Fields, error, reset_fields })));
+        },
+        Err(e) => e.to_string(),
+    };
+    eprintln!("Failed to load settings from {:?}: {}", path, error);
+
+    // Keep the broken file around for inspection instead of silently overwriting it
+    let corrupt_path = sibling_path(path, ".corrupt");
+    if let Err(e) = fs::rename(path, &corrupt_path) {
+        eprintln!("Failed to move corrupted settings to {:?}: {}", corrupt_path, e);
     }
+
+    let backup_path = sibling_path(path, ".bak");
+    let (settings, source) = match read_settings_file(&backup_path) {
+        Ok((settings, _)) => {
+            println!("Recovered settings from backup: {:?}", backup_path);
+            (settings, RecoverySource::Backup)
+        },
+        Err(e) => {
+            eprintln!("Settings backup unavailable ({}), using defaults", e);
+            (Settings::default(), RecoverySource::Defaults)
+        }
+    };
+
+    // Write the recovered settings back so the next startup reads a valid file
+    save_settings(&settings, path)?;
+
+    Ok((settings, Some(SettingsRecovery { source, error, reset_fields: Vec::new() })))
 }
 
-// Save settings to file
+// Save settings to file atomically, keeping the previous valid file as a backup
 fn save_settings(settings: &Settings, path: &Path) -> Result<(), Box<dyn Error>> {
     let json = serde_json::to_string_pretty(&settings)?;
     
@@ -128,14 +477,31 @@ fn save_settings(settings: &Settings, path: &Path) -> Result<(), Box<dyn Error>>
         }
     }
     
-    fs::write(path, json)?;
+    // Only rotate the current file into the backup if it is valid, so a corrupted
+    // file never replaces a good backup
+    if path.exists() && read_settings_file(path).is_ok_and(|(_, reset_fields)| reset_fields.is_empty()) {
+        fs::copy(path, sibling_path(path, ".bak"))?;
+    }
+    
+    // Write to a temporary file first and rename it over the target, so a crash
+    // mid-write never leaves a truncated settings.json behind
+    let tmp_path = sibling_path(path, ".tmp");
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)?;
+    
     Ok(())
 }
 
-// Create default language files if they don't exist
+// Install bundled language files and update them when a newer version ships,
+// leaving packs the user edited alone
 fn ensure_language_files_exist(app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
     // Get languages directory path
     let languages_path = get_languages_path(app_handle)?;
+    get_language_overrides_path(app_handle)?;
     
     // Tauri v2 approach to get resource directory
     let resource_dir = match app_handle.app_handle().path().resource_dir() {
@@ -148,300 +514,1394 @@ fn ensure_language_files_exist(app_handle: &AppHandle) -> Result<(), Box<dyn Err
     
     // Create the full path to the languages directory in resources
     let resource_languages_dir = resource_dir.join("languages");
+    let entries = match fs::read_dir(&resource_languages_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Languages resource directory not found: {:?} ({})", resource_languages_dir, e);
+            return Ok(());
+        }
+    };
     
-    // Check if the resource languages directory exists
-    if resource_languages_dir.exists() && resource_languages_dir.is_dir() {
-        // Read all files from the resource languages directory
-        if let Ok(entries) = fs::read_dir(&resource_languages_dir) {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let source_path = entry.path();
-                    
-                    // Only process JSON files
-                    if let Some(extension) = source_path.extension() {
-                        if extension == "json" {
-                            if let Some(filename) = source_path.file_name() {
-                                let dest_path = languages_path.join(filename);
-                                
-                                // Copy file if it doesn't exist in the destination
-                                if !dest_path.exists() {
-                                    // Try to read and copy the file
-                                    if let Ok(content) = fs::read_to_string(&source_path) {
-                                        fs::write(&dest_path, content)?;
-                                        println!("Copied language file: {:?}", dest_path);
-                                    } else {
-                                        eprintln!("Failed to read language file: {:?}", source_path);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    let manifest_path = get_language_manifest_path(app_handle)?;
+    let mut manifest = language_packs::PackManifest::load(&manifest_path);
+    
+    for entry in entries.flatten() {
+        let source_path = entry.path();
+        
+        // Only process JSON files
+        if source_path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let filename = match source_path.file_name() {
+            Some(filename) => filename,
+            None => continue,
+        };
+        let dest_path = languages_path.join(filename);
+        
+        let source = match fs::read(&source_path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Failed to read language file {:?}: {}", source_path, e);
+                continue;
             }
-        } else {
-            eprintln!("Could not read from languages resource directory");
+        };
+        
+        match language_packs::sync_pack(&source, &dest_path, &mut manifest) {
+            Ok(language_packs::SyncOutcome::Installed) => println!("Copied language file: {:?}", dest_path),
+            Ok(language_packs::SyncOutcome::Updated) => println!("Updated language file: {:?}", dest_path),
+            Ok(language_packs::SyncOutcome::PreservedUserChanges) => {
+                println!("Keeping user-modified language file: {:?}", dest_path)
+            },
+            Ok(language_packs::SyncOutcome::UpToDate) => {},
+            Err(e) => eprintln!("Failed to install language file {:?}: {}", source_path, e),
         }
-    } else {
-        eprintln!("Languages resource directory not found: {:?}", resource_languages_dir);
     }
     
+    manifest.save(&manifest_path)?;
+    
     Ok(())
 }
 
-// Load language from file
-fn load_language(app_handle: &AppHandle, lang_code: &str) -> Result<Language, Box<dyn Error>> {
-    let lang_path = get_language_file_path(app_handle, lang_code)?;
+// Language used as the last file-based fallback and as the reference for translators
+const FALLBACK_LANGUAGE: &str = translations::DEFAULT_LANGUAGE_ID;
+
+// Build the lookup chain for a locale, most specific first: ru_RU -> ru -> en_US
+fn language_chain(lang_code: &str) -> Vec<String> {
+    let mut chain = vec![lang_code.to_string()];
     
-    if lang_path.exists() {
-        let contents = fs::read_to_string(lang_path)?;
-        match serde_json::from_str(&contents) {
-            Ok(language) => Ok(language),
-            Err(e) => {
-                eprintln!("Error parsing language file {}: {}", lang_code, e);
-                create_default_language()
-            }
-        }
-    } else {
-        // If the requested language doesn't exist and it's not already English,
-        // fall back to English
-        if lang_code != "en_US" {
-            eprintln!("Language {} not found, falling back to en_US", lang_code);
-            load_language(app_handle, "en_US")
-        } else {
-            // If even English doesn't exist, create a default language instance
-            eprintln!("Default language (en_US) not found, using built-in defaults");
-            create_default_language()
+    if let Some((base, _)) = lang_code.split_once(['_', '-']) {
+        if !base.is_empty() {
+            chain.push(base.to_string());
         }
     }
+    
+    if !chain.iter().any(|code| code == FALLBACK_LANGUAGE) {
+        chain.push(FALLBACK_LANGUAGE.to_string());
+    }
+    
+    chain
 }
 
-// Create a default language instance with basic English translations
-fn create_default_language() -> Result<Language, Box<dyn Error>> {
-    Ok(Language {
-        metadata: LanguageMetadata {
-            id: "en_US".to_string(),
-            version: "1.0.0".to_string(),
-            author: "Intelligence team".to_string(),
-        },
-        translations: [
-            ("app.title".to_string(), "Intelligence".to_string()),
-            ("app.status.no_instances".to_string(), "No instances running".to_string()),
-            ("settings.title".to_string(), "Settings".to_string()),
-            ("settings.tab.appearance".to_string(), "Appearance".to_string()),
-            ("settings.tab.privacy".to_string(), "Privacy".to_string()),
-            ("settings.appearance.color_theme".to_string(), "Color theme".to_string()),
-            ("settings.appearance.color_theme.description".to_string(), "Select your preferred color theme for Intelligence App.".to_string()),
-            ("settings.appearance.theme.dark".to_string(), "Dark".to_string()),
-            ("settings.appearance.theme.light".to_string(), "Light".to_string()),
-            ("settings.appearance.theme.oled".to_string(), "OLED".to_string()),
-            ("settings.appearance.theme.sync".to_string(), "Sync".to_string()),
-            ("settings.privacy.discord_rpc".to_string(), "Discord RPC".to_string()),
-            ("settings.privacy.discord_rpc.description".to_string(), "Manages the Discord Rich Presence integration. Disabling this will cause 'Intelligence' to no longer show up as a game or app you are using on your Discord profile.".to_string()),
-            ("settings.privacy.discord_rpc.note".to_string(), "Note: This will not prevent any instance-specific Discord Rich Presence integrations, such as those added by mods. (app restart required to take effect)".to_string()),
-            // New translations for titlebar styles <Intelligence app v0.0.5>
-            ("settings.appearance.titlebar_style".to_string(), "Titlebar Style".to_string()),
-            ("settings.appearance.titlebar_style.description".to_string(), "Choose how the application window titlebar should look.".to_string()),
-            ("settings.appearance.titlebar.custom".to_string(), "Custom".to_string()),
-            ("settings.appearance.titlebar.native".to_string(), "Native".to_string()),
-            ("settings.appearance.titlebar.macos".to_string(), "macOS".to_string()),
-            ("settings.appearance.titlebar.note".to_string(), "Note: Changing the titlebar style requires restarting the application.".to_string()),
-            ("settings.appearance.titlebar.restart_required".to_string(), "The application needs to be restarted to apply the titlebar style change.".to_string()),
-        ].iter().cloned().collect(),
-    })
+// Read a single language file without any fallback
+fn read_language_file(path: &Path) -> Result<Language, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let mut language: Language = serde_json::from_str(&contents)?;
+    language.metadata = language.metadata.with_defaults();
+    Ok(language)
 }
 
-// Command to get available languages
-#[tauri::command]
-async fn get_available_languages(app_handle: AppHandle) -> Result<Vec<LanguageMetadata>, String> {
-    // Get languages directory
-    let languages_path = get_languages_path(&app_handle).map_err(|e| e.to_string())?;
+// Read a user override file: any subset of keys, metadata is optional
+fn read_language_override(path: &Path, metadata: LanguageMetadata) -> Result<Language, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let values: std::collections::HashMap<String, serde_json::Value> = serde_json::from_str(&contents)?;
     
-    // Read directory and find language files
-    let mut languages = Vec::new();
+    let translations = values
+        .into_iter()
+        .filter_map(|(key, value)| match value {
+            serde_json::Value::String(text) if key != "metadata" => Some((key, text)),
+            _ => None,
+        })
+        .collect();
     
-    // Read from user languages directory first
-    if let Ok(entries) = fs::read_dir(&languages_path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if let Some(ext) = path.extension() {
-                    if ext == "json" {
-                        if let Ok(content) = fs::read_to_string(&path) {
-                            match serde_json::from_str::<Language>(&content) {
-                                Ok(language) => {
-                                    languages.push(language.metadata);
-                                },
-                                Err(e) => {
-                                    eprintln!("Error parsing language file {:?}: {}", path, e);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+    Ok(Language { metadata, translations })
+}
+
+// Load every available layer of a locale's chain, most specific first.
+// With overrides, each pack is preceded by its entry from languages/overrides.
+// Missing or broken files are skipped so the remaining layers still apply
+fn load_language_layers(app_handle: &AppHandle, lang_code: &str, include_overrides: bool) -> Result<Vec<Language>, Box<dyn Error>> {
+    let mut layers = Vec::new();
     
-    // If no languages found, check the resources directory
-    if languages.is_empty() {
-        // In Tauri v2, resource_dir() returns Result<PathBuf, Error> instead of Option<PathBuf>
-        match app_handle.path().resource_dir() {
-            Ok(resource_dir) => {
-                let resource_languages_dir = resource_dir.join("languages");
-                
-                if resource_languages_dir.exists() && resource_languages_dir.is_dir() {
-                    if let Ok(entries) = fs::read_dir(&resource_languages_dir) {
-                        for entry in entries {
-                            if let Ok(entry) = entry {
-                                let path = entry.path();
-                                if let Some(ext) = path.extension() {
-                                    if ext == "json" {
-                                        if let Ok(content) = fs::read_to_string(&path) {
-                                            match serde_json::from_str::<Language>(&content) {
-                                                Ok(language) => {
-                                                    languages.push(language.metadata);
-                                                },
-                                                Err(e) => {
-                                                    eprintln!("Error parsing language file {:?}: {}", path, e);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+    for code in language_chain(lang_code) {
+        let lang_path = get_language_file_path(app_handle, &code)?;
+        let pack = if lang_path.exists() {
+            match read_language_file(&lang_path) {
+                Ok(mut language) => {
+                    drop_invalid_messages(&mut language);
+                    Some(language)
+                },
+                Err(e) => {
+                    eprintln!("Error parsing language file {}: {}", code, e);
+                    None
                 }
-            },
-            Err(e) => {
-                eprintln!("Error getting resource directory: {}", e);
+            }
+        } else {
+            None
+        };
+        
+        let override_path = get_language_overrides_path(app_handle)?.join(format!("{}.json", code));
+        if include_overrides && override_path.exists() {
+            // Overrides take the pack's metadata so they never change which language is reported
+            let metadata = pack.as_ref().map(|pack| pack.metadata.clone()).unwrap_or_else(|| LanguageMetadata {
+                id: code.clone(),
+                ..Default::default()
+            }.with_defaults());
+            match read_language_override(&override_path, metadata) {
+                Ok(mut language) => {
+                    drop_invalid_messages(&mut language);
+                    layers.push(language);
+                },
+                Err(e) => eprintln!("Error parsing language override {}: {}", code, e),
             }
         }
+        
+        layers.extend(pack);
     }
     
-    // Ensure we have at least one language
-    if languages.is_empty() {
-        // Create a default English metadata if no languages found
-        languages.push(LanguageMetadata {
-            id: "en_US".to_string(),
-            version: "1.0.0".to_string(),
-            author: "Intelligence team".to_string(),
-        });
-    }
-    
-    Ok(languages)
+    Ok(layers)
 }
 
-// Command to get translations for current language
-#[tauri::command]
-async fn get_translations(_app_handle: AppHandle, state: State<'_, AppState>) -> Result<Language, String> {
-    // Return current language from state
-    let current_language = state.current_language.lock().unwrap().clone();
-    Ok(current_language)
+// Remove translations with broken message syntax so those keys fall through to the next layer
+fn drop_invalid_messages(language: &mut Language) {
+    let id = language.metadata.id.clone();
+    language.translations.retain(|key, value| match message_format::parse(value) {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("Invalid message for {} in language {}: {}", key, id, e);
+            false
+        }
+    });
 }
 
-// Command to change language
-#[tauri::command]
-async fn change_language(lang_code: String, app_handle: AppHandle, state: State<'_, AppState>) -> Result<Language, String> {
-    // Load the requested language
-    let language = load_language(&app_handle, &lang_code).map_err(|e| e.to_string())?;
-    
-    // Update current language in state
-    *state.current_language.lock().unwrap() = language.clone();
+// Merge layers on top of the built-in defaults so missing keys fall through per key
+fn merge_language_layers(layers: &[Language]) -> Result<Language, Box<dyn Error>> {
+    let mut language = create_default_language()?;
     
-    // Update language in settings
-    let mut settings = state.settings.lock().unwrap().clone();
-    settings.language = lang_code;
-    
-    // Save settings to file
-    if let Some(path) = &*state.settings_path.lock().unwrap() {
-        save_settings(&settings, path).map_err(|e| e.to_string())?;
+    for layer in layers.iter().rev() {
+        // Empty strings are treated as untranslated
+        language.translations.extend(
+            layer.translations.iter()
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
     }
     
-    // Update settings in state
-    *state.settings.lock().unwrap() = settings;
+    if let Some(most_specific) = layers.first() {
+        language.metadata = most_specific.metadata.clone();
+    }
     
     Ok(language)
 }
 
-// Command to get current settings
-#[tauri::command]
-async fn get_settings(app_handle: AppHandle, state: State<'_, AppState>) -> Result<Settings, String> {
-    let mut settings_path_guard = state.settings_path.lock().unwrap();
+// Load language through its fallback chain: requested locale -> base language -> en_US -> built-in defaults
+fn load_language(app_handle: &AppHandle, lang_code: &str) -> Result<Language, Box<dyn Error>> {
+    let layers = load_language_layers(app_handle, lang_code, true)?;
     
-    if settings_path_guard.is_none() {
-        // Initialize settings path if it's not set yet
-        let path = get_settings_file_path(&app_handle).map_err(|e| e.to_string())?;
-        *settings_path_guard = Some(path);
+    match layers.first() {
+        Some(language) if language.metadata.id != lang_code => {
+            eprintln!("Language {} not found, falling back to {}", lang_code, language.metadata.id);
+        },
+        None => {
+            // If even English doesn't exist, only the built-in defaults are left
+            eprintln!("Default language ({}) not found, using built-in defaults", FALLBACK_LANGUAGE);
+        },
+        _ => {}
     }
     
-    // Return current settings from state
-    let settings = state.settings.lock().unwrap().clone();
-    Ok(settings)
+    merge_language_layers(&layers)
 }
 
-// Command to update settings
-#[tauri::command]
-async fn update_settings(
-    settings: Settings,
-    app_handle: AppHandle,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    // Update settings in state
-    {
-        let mut current_settings = state.settings.lock().map_err(|_| "Failed to lock settings")?;
-        
-        // Check if titlebar style has changed
-        let titlebar_style_changed = current_settings.titlebar_style != settings.titlebar_style;
-        
-        // Update settings
-        *current_settings = settings.clone();
-        
-        // Save settings to file
-        if let Some(path) = &*state.settings_path.lock().map_err(|_| "Failed to lock settings path")? {
-            save_settings(&settings, path).map_err(|e| format!("Failed to save settings: {}", e))?;
+// Create a default language instance from the en_US pack embedded at build time
+fn create_default_language() -> Result<Language, Box<dyn Error>> {
+    let metadata: LanguageMetadata = serde_json::from_str(translations::DEFAULT_LANGUAGE_METADATA)?;
+    
+    Ok(Language {
+        metadata: metadata.with_defaults(),
+        translations: translations::DEFAULT_TRANSLATIONS
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+    })
+}
+
+// Language entry returned by get_available_languages
+#[derive(Debug, Serialize, Clone)]
+pub struct AvailableLanguage {
+    #[serde(flatten)]
+    pub metadata: LanguageMetadata,
+    pub completeness: f32,
+    pub status: language_lint::ValidationStatus,
+    // Whether the file parses at all and can be selected
+    #[serde(skip)]
+    pub loadable: bool,
+}
+
+// Reference translations every language pack is compared against
+fn language_reference(app_handle: &AppHandle) -> Result<std::collections::HashMap<String, String>, Box<dyn Error>> {
+    let layers = load_language_layers(app_handle, FALLBACK_LANGUAGE, false)?;
+    Ok(merge_language_layers(&layers)?.translations)
+}
+
+// Collect and validate all language files in a directory
+fn read_available_languages(
+    dir: &Path,
+    reference: &std::collections::HashMap<String, String>,
+    languages: &mut Vec<AvailableLanguage>,
+) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
         }
         
-        // If language changed, update the current language
-        if current_settings.language != settings.language {
-            let new_language = load_language(&app_handle, &settings.language)
-                .map_err(|e| format!("Failed to load language: {}", e))?;
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string();
+        if let Ok(content) = fs::read_to_string(&path) {
+            let report = language_lint::lint_language(&content, Some(&stem), reference);
+            
+            // Broken files are still listed so the UI can show why they are unusable
+            let (metadata, loadable) = match serde_json::from_str::<Language>(&content) {
+                Ok(language) => (language.metadata.with_defaults(), true),
+                Err(e) => {
+                    eprintln!("Error parsing language file {:?}: {}", path, e);
+                    let metadata = LanguageMetadata {
+                        id: stem,
+                        ..Default::default()
+                    };
+                    (metadata.with_defaults(), false)
+                }
+            };
             
-            let mut current_language = state.current_language.lock()
-                .map_err(|_| "Failed to lock current language")?;
-            *current_language = new_language;
+            languages.push(AvailableLanguage {
+                metadata,
+                completeness: report.completeness,
+                status: report.status,
+                loadable,
+            });
         }
-        
-        // If titlebar style changed, inform the user they need to restart
-        if titlebar_style_changed {
-            println!("Titlebar style changed to: {}", settings.titlebar_style);
-            // Note: The actual change will be applied on next app restart
+    }
+}
+
+// List installed languages, falling back to bundled resources and built-in English
+fn list_languages(app_handle: &AppHandle) -> Result<Vec<AvailableLanguage>, Box<dyn Error>> {
+    let reference = language_reference(app_handle)?;
+    
+    // Read from user languages directory first
+    let languages_path = get_languages_path(app_handle)?;
+    let mut languages = Vec::new();
+    read_available_languages(&languages_path, &reference, &mut languages);
+    
+    // If no languages found, check the resources directory
+    if languages.is_empty() {
+        match app_handle.path().resource_dir() {
+            Ok(resource_dir) => read_available_languages(&resource_dir.join("languages"), &reference, &mut languages),
+            Err(e) => eprintln!("Error getting resource directory: {}", e),
         }
     }
     
-    Ok(())
+    // Ensure we have at least one language
+    if languages.is_empty() {
+        // Create a default English metadata if no languages found
+        languages.push(AvailableLanguage {
+            metadata: create_default_language()?.metadata,
+            completeness: 100.0,
+            status: language_lint::ValidationStatus::Valid,
+            loadable: true,
+        });
+    }
+    
+    languages.sort_by(|a, b| a.metadata.id.cmp(&b.metadata.id));
+    
+    Ok(languages)
 }
 
-// Command to get system information
-#[tauri::command]
-fn get_system_info() -> Result<serde_json::Value, String> {
-    let os_info = os_info::get();
+// Check that a language code refers to an installed language
+fn validate_language_code(app_handle: &AppHandle, lang_code: &str) -> Result<(), SettingsError> {
+    let languages = list_languages(app_handle).map_err(|e| SettingsError::internal(e.to_string()))?;
     
-    // Correctly determine the OS type, taking into account the macOS specifics
-    let os_type = match os_info.os_type() {
-        os_info::Type::Macos => "macOS",
-        _ => std::env::consts::OS
-    };
+    let installed: Vec<String> = languages
+        .into_iter()
+        .filter(|language| language.loadable)
+        .map(|language| language.metadata.id)
+        .collect();
     
-    // Get the OS version
-    let os_version = format!("{}", os_info.version());
-
-    let system_info = serde_json::json!({
-        "os": os_type,
-        "version": os_version
-    });
-
-    Ok(system_info)
+    if installed.iter().any(|id| id == lang_code) {
+        Ok(())
+    } else {
+        Err(SettingsError::InvalidValue {
+            field: "language".to_string(),
+            value: lang_code.to_string(),
+            expected: installed,
+        })
+    }
+}
+
+// Check that a username is a valid Minecraft player name
+fn validate_username(username: &str) -> Result<(), SettingsError> {
+    let valid_length = (3..=16).contains(&username.len());
+    if valid_length && username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(())
+    } else {
+        Err(SettingsError::InvalidValue {
+            field: "username".to_string(),
+            value: username.to_string(),
+            expected: vec!["3 to 16 letters, digits or underscores".to_string()],
+        })
+    }
+}
+
+// Retention has to keep at least the newest backup
+fn validate_backups(backups: &backups::BackupSettings) -> Result<(), SettingsError> {
+    if backups.keep_last == 0 {
+        return Err(SettingsError::InvalidValue {
+            field: "backups.keep_last".to_string(),
+            value: backups.keep_last.to_string(),
+            expected: vec!["at least 1".to_string()],
+        });
+    }
+    Ok(())
+}
+
+// Checks on settings from the frontend or an edited settings.json that
+// deserialization can't do. Theme and titlebar style are checked by
+// settings_from_value, the language against what is actually installed
+fn validate_settings(app_handle: &AppHandle, settings: &Settings) -> Result<(), SettingsError> {
+    validate_language_code(app_handle, &settings.language)?;
+    validate_username(&settings.username)?;
+    validate_backups(&settings.backups)
+}
+
+// Pick the UI language for a new settings file from the OS locale
+fn detect_initial_language(app_handle: &AppHandle) -> String {
+    let fallback = Settings::default().language;
+    
+    let detected = match locale::detect_locale() {
+        Some(detected) => detected,
+        None => return fallback,
+    };
+    
+    let available: Vec<String> = match list_languages(app_handle) {
+        Ok(languages) => languages
+            .into_iter()
+            .filter(|language| language.loadable)
+            .map(|language| language.metadata.id)
+            .collect(),
+        Err(e) => {
+            eprintln!("Could not list languages for locale detection: {}", e);
+            return fallback;
+        }
+    };
+    
+    match locale::match_locale(&detected, &available) {
+        Some(language) => {
+            println!("Detected locale {}, using language {}", detected, language);
+            language
+        },
+        None => {
+            println!("No language pack for locale {}, using {}", detected, fallback);
+            fallback
+        }
+    }
+}
+
+// Command to get available languages
+#[tauri::command]
+async fn get_available_languages(app_handle: AppHandle) -> Result<Vec<AvailableLanguage>, String> {
+    list_languages(&app_handle).map_err(|e| e.to_string())
+}
+
+// Command to check a language pack against en_US for translators
+#[tauri::command]
+async fn validate_language(lang_code: String, app_handle: AppHandle) -> Result<language_lint::LanguageReport, String> {
+    let path = get_language_file_path(&app_handle, &lang_code).map_err(|e| e.to_string())?;
+    let source = fs::read_to_string(&path).map_err(|e| format!("Failed to read language {}: {}", lang_code, e))?;
+    let reference = language_reference(&app_handle).map_err(|e| e.to_string())?;
+    
+    Ok(language_lint::lint_language(&source, Some(&lang_code), &reference))
+}
+
+// Command to list keys translated in en_US but missing (or empty) in a locale
+#[tauri::command]
+async fn get_missing_translations(lang_code: String, app_handle: AppHandle) -> Result<Vec<String>, String> {
+    // Reference keys come from en_US on top of the built-in defaults
+    let reference_layers = load_language_layers(&app_handle, FALLBACK_LANGUAGE, false).map_err(|e| e.to_string())?;
+    let reference = merge_language_layers(&reference_layers).map_err(|e| e.to_string())?;
+    
+    // Only the locale's own layers count, not the English fallback
+    let layers = load_language_layers(&app_handle, &lang_code, false).map_err(|e| e.to_string())?;
+    let own_layers: Vec<&Language> = layers
+        .iter()
+        .filter(|layer| layer.metadata.id != FALLBACK_LANGUAGE || lang_code == FALLBACK_LANGUAGE)
+        .collect();
+    
+    let mut missing: Vec<String> = reference
+        .translations
+        .keys()
+        .filter(|key| {
+            !own_layers.iter().any(|layer| {
+                layer.translations.get(*key).is_some_and(|value| !value.is_empty())
+            })
+        })
+        .cloned()
+        .collect();
+    missing.sort();
+    
+    Ok(missing)
+}
+
+// Command to format a translation with named arguments, plural and select forms
+#[tauri::command]
+async fn format_message(
+    key: String,
+    args: Option<std::collections::HashMap<String, serde_json::Value>>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    translate(&state, &key, &args.unwrap_or_default())
+}
+
+// Format a message of the current language, unknown keys render as the key itself
+fn translate(
+    state: &AppState,
+    key: &str,
+    args: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<String, String> {
+    let (locale, pattern) = {
+        let language = state.current_language.lock().unwrap();
+        let pattern = match language.translations.get(key) {
+            Some(pattern) => pattern.clone(),
+            // Same behavior as the frontend
+            None => return Ok(key.to_string()),
+        };
+        let locale = language.metadata.format_locale.clone().unwrap_or_else(|| language.metadata.id.clone());
+        (locale, pattern)
+    };
+    
+    message_format::format(&locale, &pattern, args).map_err(|e| e.to_string())
+}
+
+// Get the configured community language repository
+fn language_repository_url(state: &AppState) -> Result<String, String> {
+    state
+        .settings
+        .lock()
+        .unwrap()
+        .language_repository_url
+        .clone()
+        .filter(|url| !url.trim().is_empty())
+        .ok_or_else(|| "No language repository configured".to_string())
+}
+
+// Command to list community language packs from the configured repository
+#[tauri::command]
+async fn get_community_languages(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<language_repository::RepositoryPack>, String> {
+    let index_url = language_repository_url(&state)?;
+    let languages_path = get_languages_path(&app_handle).map_err(|e| e.to_string())?;
+    
+    language_repository::list_packs(&index_url, &languages_path)
+        .await
+        .map_err(|e| format!("Failed to load language repository: {}", e))
+}
+
+// Command to install or update a community language pack
+#[tauri::command]
+async fn install_community_language(
+    id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<LanguageMetadata, String> {
+    let index_url = language_repository_url(&state)?;
+    let languages_path = get_languages_path(&app_handle).map_err(|e| e.to_string())?;
+    let manifest_path = get_language_manifest_path(&app_handle).map_err(|e| e.to_string())?;
+    
+    let (language, outcome) = language_repository::install_pack(&index_url, &id, &languages_path, &manifest_path)
+        .await
+        .map_err(|e| format!("Failed to install language pack {}: {}", id, e))?;
+    
+    if outcome == language_packs::SyncOutcome::PreservedUserChanges {
+        return Err(format!("Language pack {} was modified locally, move your changes to languages/overrides first", id));
+    }
+    println!("Installed community language pack {} {} ({:?})", id, language.metadata.version, outcome);
+    
+    Ok(language.metadata)
+}
+
+// Command to get translations for current language
+#[tauri::command]
+async fn get_translations(_app_handle: AppHandle, state: State<'_, AppState>) -> Result<Language, String> {
+    // Return current language from state
+    let current_language = state.current_language.lock().unwrap().clone();
+    Ok(current_language)
+}
+
+// Apply a JSON merge patch (RFC 7396) to a JSON value
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let patch_map = match patch.as_object() {
+        Some(map) => map,
+        None => {
+            *target = patch.clone();
+            return;
+        }
+    };
+    
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    
+    if let Some(target_map) = target.as_object_mut() {
+        for (key, value) in patch_map {
+            if value.is_null() {
+                target_map.remove(key);
+            } else {
+                merge_patch(target_map.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+    }
+}
+
+// First key of a patch that the settings don't have, dotted for nested objects
+// such as backups.keep_last. Serde would drop it silently
+fn unknown_patch_key(
+    current: &serde_json::Value,
+    patch: &serde_json::Map<String, serde_json::Value>,
+) -> Option<String> {
+    patch.iter().find_map(|(key, value)| match (current.get(key), value) {
+        (None, _) => Some(key.clone()),
+        (Some(current @ serde_json::Value::Object(_)), serde_json::Value::Object(nested)) => {
+            unknown_patch_key(current, nested).map(|nested_key| format!("{}.{}", key, nested_key))
+        },
+        _ => None,
+    })
+}
+
+// List top-level settings keys whose values differ
+fn changed_settings_keys(old: &Settings, new: &Settings) -> Vec<String> {
+    let old = serde_json::to_value(old).unwrap_or_default();
+    let new = serde_json::to_value(new).unwrap_or_default();
+    
+    match (old.as_object(), new.as_object()) {
+        (Some(old), Some(new)) => new
+            .iter()
+            .filter(|(key, value)| old.get(*key) != Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Validate, persist and apply new settings while the settings lock is held
+fn commit_settings(
+    app_handle: &AppHandle,
+    state: &AppState,
+    current: &mut Settings,
+    settings: Settings,
+) -> Result<SettingsChanged, SettingsError> {
+    validate_settings(app_handle, &settings)?;
+    
+    let changed = changed_settings_keys(current, &settings);
+    if changed.is_empty() {
+        return Ok(SettingsChanged { settings, changed, restart_required: Vec::new() });
+    }
+    
+    // Save settings to file once for the whole change
+    if let Some(path) = &*state.settings_path.lock().map_err(|_| SettingsError::internal("Failed to lock settings path"))? {
+        save_settings(&settings, path).map_err(|e| SettingsError::internal(format!("Failed to save settings: {}", e)))?;
+    }
+    
+    apply_settings(app_handle, state, current, settings, changed)
+}
+
+// Update in-memory state for settings that are already persisted
+fn apply_settings(
+    app_handle: &AppHandle,
+    state: &AppState,
+    current: &mut Settings,
+    settings: Settings,
+    changed: Vec<String>,
+) -> Result<SettingsChanged, SettingsError> {
+    // If language changed, update the current language
+    if changed.iter().any(|key| key == "language") {
+        let new_language = load_language(app_handle, &settings.language)
+            .map_err(|e| SettingsError::internal(format!("Failed to load language: {}", e)))?;
+        
+        let mut current_language = state.current_language.lock()
+            .map_err(|_| SettingsError::internal("Failed to lock current language"))?;
+        *current_language = new_language;
+        drop(current_language);
+    }
+    
+    *current = settings.clone();
+    
+    let restart_required: Vec<String> = changed
+        .iter()
+        .filter(|key| RESTART_REQUIRED_KEYS.contains(&key.as_str()))
+        .cloned()
+        .collect();
+    
+    Ok(SettingsChanged { settings, changed, restart_required })
+}
+
+// Notify every window and the tray about changed settings. Must be called after
+// the settings lock is released, rebuilding the tray menu reads settings
+fn emit_settings_changed(app_handle: &AppHandle, change: &SettingsChanged) {
+    if change.changed.is_empty() {
+        return;
+    }
+    
+    // Tray labels are translated
+    if change.tray_refresh_needed() {
+        tray::refresh(app_handle);
+    }
+    
+    if let Err(e) = app_handle.emit("settings-changed", change) {
+        eprintln!("Failed to emit settings change event: {}", e);
+    }
+}
+
+// Command to change language
+#[tauri::command]
+async fn change_language(lang_code: String, app_handle: AppHandle, state: State<'_, AppState>) -> Result<Language, SettingsError> {
+    let change = {
+        let mut current_settings = state.settings.lock().map_err(|_| SettingsError::internal("Failed to lock settings"))?;
+        
+        // Update language in settings
+        let mut settings = current_settings.clone();
+        settings.language = lang_code;
+        
+        commit_settings(&app_handle, &state, &mut current_settings, settings)?
+    };
+    
+    emit_settings_changed(&app_handle, &change);
+    
+    let language = state.current_language.lock().unwrap().clone();
+    Ok(language)
+}
+
+// Command to get current settings
+#[tauri::command]
+async fn get_settings(app_handle: AppHandle, state: State<'_, AppState>) -> Result<Settings, String> {
+    let mut settings_path_guard = state.settings_path.lock().unwrap();
+    
+    if settings_path_guard.is_none() {
+        // Initialize settings path if it's not set yet
+        let path = get_settings_file_path(&app_handle).map_err(|e| e.to_string())?;
+        *settings_path_guard = Some(path);
+    }
+    
+    // Return current settings from state
+    let settings = state.settings.lock().unwrap().clone();
+    Ok(settings)
+}
+
+// Command to update settings
+#[tauri::command]
+async fn update_settings(
+    settings: serde_json::Value,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), SettingsError> {
+    let settings = settings_from_value(settings)?;
+    let change = {
+        let mut current_settings = state.settings.lock().map_err(|_| SettingsError::internal("Failed to lock settings"))?;
+        commit_settings(&app_handle, &state, &mut current_settings, settings)?
+    };
+    
+    emit_settings_changed(&app_handle, &change);
+    
+    Ok(())
+}
+
+// Command to update individual settings with a JSON merge patch
+#[tauri::command]
+async fn patch_settings(
+    patch: serde_json::Value,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Settings, SettingsError> {
+    let patch_map = patch.as_object().ok_or_else(|| SettingsError::InvalidPatch {
+        message: "patch must be a JSON object".to_string(),
+    })?;
+    
+    let change = {
+        // Apply the patch to the latest settings under the lock, so concurrent
+        // patches touching different keys never overwrite each other
+        let mut current_settings = state.settings.lock().map_err(|_| SettingsError::internal("Failed to lock settings"))?;
+        let mut value = serde_json::to_value(&*current_settings).map_err(|e| SettingsError::internal(e.to_string()))?;
+        
+        if let Some(unknown) = unknown_patch_key(&value, patch_map) {
+            return Err(SettingsError::InvalidPatch {
+                message: format!("unknown settings key '{}'", unknown),
+            });
+        }
+        
+        merge_patch(&mut value, &patch);
+        let settings = settings_from_value(value)?;
+        
+        commit_settings(&app_handle, &state, &mut current_settings, settings)?
+    };
+    
+    emit_settings_changed(&app_handle, &change);
+    
+    Ok(change.settings)
+}
+
+// Command to get resolved app directories
+#[tauri::command]
+async fn get_app_directories(app_handle: AppHandle) -> Result<paths::AppDirs, String> {
+    let mut dirs = paths::resolve().map_err(|e| e.to_string())?;
+    dirs.data = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    Ok(dirs)
+}
+
+// Command to move game data to a new directory and point settings at it
+#[tauri::command]
+async fn migrate_data_directory(
+    target: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Settings, SettingsError> {
+    let invalid_target = |expected: &str| SettingsError::InvalidValue {
+        field: "data_directory".to_string(),
+        value: target.clone(),
+        expected: vec![expected.to_string()],
+    };
+    
+    let target_path = PathBuf::from(&target);
+    if !target_path.is_absolute() {
+        return Err(invalid_target("an absolute path"));
+    }
+    let is_empty = fs::read_dir(&target_path).map(|mut entries| entries.next().is_none());
+    if matches!(is_empty, Ok(false)) || target_path.is_file() {
+        return Err(invalid_target("an empty or missing directory"));
+    }
+    
+    let current = get_data_path(&app_handle).map_err(|e| SettingsError::internal(e.to_string()))?;
+    if target_path.starts_with(&current) {
+        return Err(invalid_target("a directory outside the current data directory"));
+    }
+    
+    if !state.running_games.lock().unwrap().is_empty() {
+        return Err(SettingsError::internal("Close all games before moving the data directory"));
+    }
+    
+    // Copy without holding the settings lock, the old files stay until the new
+    // location is committed. Only game data moves, config files sharing the
+    // root stay where they are
+    let copied = paths::copy_game_data(&current, &target_path)
+        .map_err(|e| SettingsError::internal(format!("Failed to copy data directory: {}", e)))?;
+    let copies: Vec<PathBuf> = copied.iter().filter_map(|source| source.file_name().map(|name| target_path.join(name))).collect();
+    let roll_back = |error: SettingsError| {
+        if let Err(e) = paths::remove_all(&copies) {
+            eprintln!("Failed to remove copied data in {:?}: {}", target_path, e);
+        }
+        error
+    };
+    
+    let change = {
+        let mut current_settings = state.settings.lock().map_err(|_| roll_back(SettingsError::internal("Failed to lock settings")))?;
+        if current_settings.data_directory.as_ref().is_some_and(|path| path != &current) {
+            return Err(roll_back(SettingsError::internal("The data directory was changed while copying")));
+        }
+        
+        let mut settings = current_settings.clone();
+        settings.data_directory = Some(target_path.clone());
+        commit_settings(&app_handle, &state, &mut current_settings, settings).map_err(roll_back)?
+    };
+    
+    // The new location is in use, anything left behind only takes up space
+    match paths::remove_all(&copied) {
+        Ok(()) => println!("Moved {} entries from {:?} to {:?}", copied.len(), current, target_path),
+        Err(e) => eprintln!("Moved data to {:?} but failed to remove some of {:?}: {}", target_path, current, e),
+    }
+    
+    emit_settings_changed(&app_handle, &change);
+    
+    Ok(change.settings)
+}
+
+// Command to list game instances
+#[tauri::command]
+async fn list_instances(app_handle: AppHandle) -> Result<Vec<instances::Instance>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    instances::list_instances(&data_path).map_err(|e| e.to_string())
+}
+
+// Command to create a new instance for an installed version
+#[tauri::command]
+async fn create_instance(name: String, game_version: String, app_handle: AppHandle) -> Result<instances::Instance, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::create_instance(&data_path, &name, &game_version).map_err(|e| e.to_string())?;
+    tray::refresh(&app_handle);
+    Ok(instance)
+}
+
+// Command to list the JVM presets and what they set
+#[tauri::command]
+async fn get_jvm_presets() -> Result<Vec<jvm::PresetSettings>, String> {
+    Ok(jvm::JvmPreset::ALL.iter().map(|preset| preset.settings()).collect())
+}
+
+// Command to save the JVM settings of an instance after checking them against system RAM.
+// Hooks, the wrapper, environment variables and extra flags can run code on every
+// launch, setting or changing them needs `confirm_commands`
+#[tauri::command]
+async fn update_instance_jvm(
+    instance_id: String,
+    jvm: jvm::JvmSettings,
+    confirm_commands: Option<bool>,
+    app_handle: AppHandle,
+) -> Result<instances::Instance, String> {
+    jvm.validate(diagnostics::total_memory())?;
+    
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let mut instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let new_commands = !jvm::commands_approved(&instance.id, &jvm);
+    if new_commands && !confirm_commands.unwrap_or(false) {
+        return Err("Hooks, the wrapper, environment variables and JVM flags can run programs on every launch, confirm them to save".to_string());
+    }
+    
+    instance.jvm = jvm;
+    instances::save_instance(&data_path, &instance).map_err(|e| e.to_string())?;
+    if new_commands {
+        jvm::approve_commands(&instance.id, &instance.jvm)?;
+    }
+    Ok(instance)
+}
+
+// Command to apply a JVM preset to an instance, keeping its environment, wrapper and hooks
+#[tauri::command]
+async fn apply_jvm_preset(instance_id: String, preset: jvm::JvmPreset, app_handle: AppHandle) -> Result<instances::Instance, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let mut instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let approved = jvm::commands_approved(&instance.id, &instance.jvm);
+    instance.jvm.apply_preset(preset);
+    instance.jvm.validate(diagnostics::total_memory())?;
+    instances::save_instance(&data_path, &instance).map_err(|e| e.to_string())?;
+    // A preset only swaps flags for its own, which need no confirmation
+    if approved {
+        jvm::approve_commands(&instance.id, &instance.jvm)?;
+    }
+    Ok(instance)
+}
+
+// Command to install a game version, progress is reported through `install-progress` events
+#[tauri::command]
+async fn install_version(version: String, app_handle: AppHandle) -> Result<String, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    
+    let emitter = app_handle.clone();
+    let progress = move |progress: versions::InstallProgress| {
+        if let Err(e) = emitter.emit("install-progress", progress) {
+            eprintln!("Failed to emit install progress event: {}", e);
+        }
+    };
+    
+    versions::install_version(&data_path, &version, &progress)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// How often running games are checked for exit
+const GAME_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+// Launch an instance, emitting `game-exited` when the game closes. The username
+// defaults to the one from settings
+fn start_instance(
+    app_handle: &AppHandle,
+    instance_id: &str,
+    server: Option<String>,
+    username: Option<String>,
+) -> Result<u32, String> {
+    let data_path = get_data_path(app_handle).map_err(|e| e.to_string())?;
+    let state = app_handle.state::<AppState>();
+    let username = match username {
+        Some(username) => {
+            validate_username(&username).map_err(|e| e.to_string())?;
+            username
+        },
+        None => state.settings.lock().map_err(|_| "Failed to lock settings")?.username.clone(),
+    };
+    
+    let mut instance = instances::load_instance(&data_path, instance_id).map_err(|e| e.to_string())?;
+    let options = launcher::LaunchOptions { username, server };
+    let mut child = launcher::launch(&data_path, &mut instance, &options, |command| {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    })
+    .map_err(|e| e.to_string())?;
+    let pid = child.id();
+    let started_at = instance.last_played.unwrap_or_default();
+    
+    // Keep the output in the instance's log history
+    if let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) {
+        logs::capture(&logs::logs_dir(&data_path, &instance), &logs::session_id(started_at, pid), stdout, stderr);
+    }
+    
+    let info = launcher::RunningGame {
+        pid,
+        instance_id: instance.id.clone(),
+        instance_name: instance.name.clone(),
+        game_version: instance.game_version.clone(),
+        loader: instance.loader.clone(),
+        server: options.server,
+        started_at,
+    };
+    state.running_games.lock().unwrap().push(launcher::GameProcess { info, child });
+    running_games_changed(app_handle);
+    
+    watch_game(app_handle.clone(), pid, instance.id.clone(), instance.game_dir(&data_path), started_at);
+    Ok(pid)
+}
+
+// Wait for a game to exit on a background thread, analyzing the crash report
+// if it didn't exit cleanly
+fn watch_game(app_handle: AppHandle, pid: u32, instance_id: String, game_dir: PathBuf, started_at: u64) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(GAME_POLL_INTERVAL);
+        
+        let state = app_handle.state::<AppState>();
+        let status = {
+            let mut games = state.running_games.lock().unwrap();
+            let Some(index) = games.iter().position(|game| game.info.pid == pid) else { return };
+            match games[index].child.try_wait() {
+                Ok(None) => continue,
+                Ok(Some(status)) => {
+                    games.remove(index);
+                    Some(status)
+                },
+                Err(e) => {
+                    eprintln!("Lost track of game process {}: {}", pid, e);
+                    games.remove(index);
+                    None
+                }
+            }
+        };
+        
+        let crash = status
+            .filter(|status| !status.success())
+            .and_then(|_| crash::analyze_session(&game_dir, started_at));
+        let payload = launcher::GameExited {
+            instance_id: instance_id.clone(),
+            exit_code: status.and_then(|status| status.code()),
+            crash,
+        };
+        if let Err(e) = app_handle.emit("game-exited", payload) {
+            eprintln!("Failed to emit game exit event: {}", e);
+        }
+        running_games_changed(&app_handle);
+        
+        // The post-exit hook runs once the UI knows the game is gone
+        let Ok(data_path) = get_data_path(&app_handle) else { return };
+        let instance = match instances::load_instance(&data_path, &instance_id) {
+            Ok(instance) => instance,
+            Err(e) => {
+                eprintln!("Failed to load instance {} for its post-exit hook: {}", instance_id, e);
+                return;
+            }
+        };
+        jvm::run_post_exit_hook(&data_path, &instance);
+        
+        // Then worlds played in this session are backed up
+        let settings = state.settings.lock().unwrap().backups.clone();
+        if settings.on_exit {
+            backups::back_up_changed_worlds(
+                &data_path,
+                &instance,
+                started_at,
+                backups::BackupReason::OnExit,
+                &settings,
+                &backup_progress_emitter(&app_handle),
+            );
+        }
+        return;
+    });
+}
+
+// How often the backup schedule is checked
+const BACKUP_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Send backup progress to the frontend as `backup-progress` events
+fn backup_progress_emitter(app_handle: &AppHandle) -> impl Fn(backups::BackupProgress) + Send + Sync {
+    let emitter = app_handle.clone();
+    move |progress| {
+        if let Err(e) = emitter.emit("backup-progress", progress) {
+            eprintln!("Failed to emit backup progress event: {}", e);
+        }
+    }
+}
+
+// Back up changed worlds of running games every `backups.interval_minutes`
+fn spawn_backup_scheduler(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        // Last scheduled backup per running instance, unix seconds
+        let mut last_backup: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
+        let progress = backup_progress_emitter(&app_handle);
+        
+        loop {
+            std::thread::sleep(BACKUP_POLL_INTERVAL);
+            
+            let settings = app_handle.state::<AppState>().settings.lock().unwrap().backups.clone();
+            let Some(interval) = settings.interval_minutes.filter(|minutes| *minutes > 0) else {
+                last_backup.clear();
+                continue;
+            };
+            let Ok(data_path) = get_data_path(&app_handle) else { continue };
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default();
+            
+            let games = running_games(&app_handle);
+            last_backup.retain(|instance_id, _| games.iter().any(|game| &game.instance_id == instance_id));
+            for game in games {
+                let since = *last_backup.entry(game.instance_id.clone()).or_insert(game.started_at);
+                if now < since + u64::from(interval) * 60 {
+                    continue;
+                }
+                last_backup.insert(game.instance_id.clone(), now);
+                
+                match instances::load_instance(&data_path, &game.instance_id) {
+                    Ok(instance) => {
+                        backups::back_up_changed_worlds(&data_path, &instance, since, backups::BackupReason::Scheduled, &settings, &progress);
+                    },
+                    Err(e) => eprintln!("Failed to load instance {} for a scheduled backup: {}", game.instance_id, e),
+                }
+            }
+        }
+    });
+}
+
+// List games started from this launcher
+fn running_games(app_handle: &AppHandle) -> Vec<launcher::RunningGame> {
+    app_handle
+        .state::<AppState>()
+        .running_games
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|game| game.info.clone())
+        .collect()
+}
+
+// Tell the frontend and the tray that games started or exited
+fn running_games_changed(app_handle: &AppHandle) {
+    if let Err(e) = app_handle.emit("running-games-changed", running_games(app_handle)) {
+        eprintln!("Failed to emit running games event: {}", e);
+    }
+    tray::refresh(app_handle);
+}
+
+// Kill a game started from this launcher, the exit is reported by watch_game
+fn kill_running_game(app_handle: &AppHandle, pid: u32) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    let mut games = state.running_games.lock().map_err(|_| "Failed to lock running games")?;
+    let game = games
+        .iter_mut()
+        .find(|game| game.info.pid == pid)
+        .ok_or_else(|| format!("No running game with pid {}", pid))?;
+    game.child.kill().map_err(|e| format!("Failed to stop {}: {}", game.info.instance_name, e))
+}
+
+// Command to analyze the crash report from an instance's last session
+#[tauri::command]
+async fn get_crash_report(instance_id: String, app_handle: AppHandle) -> Result<Option<crash::CrashSummary>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let Some(since) = instance.last_played else { return Ok(None) };
+    Ok(crash::analyze_session(&instance.game_dir(&data_path), since))
+}
+
+// Command to list the logged sessions of an instance
+#[tauri::command]
+async fn list_log_sessions(instance_id: String, app_handle: AppHandle) -> Result<Vec<logs::LogSession>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    logs::list_sessions(&data_path, &instance).map_err(|e| e.to_string())
+}
+
+// Command to search the logs of one instance, or all of them
+#[tauri::command]
+async fn search_logs(query: String, instance_id: Option<String>, app_handle: AppHandle) -> Result<Vec<logs::LogMatch>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    logs::search(&data_path, instance_id.as_deref(), &query).map_err(|e| e.to_string())
+}
+
+// Command to get a session log ready for sharing, with personal details redacted
+#[tauri::command]
+async fn export_log(instance_id: String, session_id: String, app_handle: AppHandle) -> Result<String, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let text = logs::read_session(&data_path, &instance, &session_id).map_err(|e| e.to_string())?;
+    
+    let username = app_handle.state::<AppState>().settings.lock().map_err(|_| "Failed to lock settings")?.username.clone();
+    Ok(logs::Redactor::for_log(&text, &[username]).redact(&text))
+}
+
+// Worlds and options can't be changed under a running game, it would overwrite them
+fn ensure_not_running(app_handle: &AppHandle, instance_id: &str) -> Result<(), String> {
+    if running_games(app_handle).iter().any(|game| game.instance_id == instance_id) {
+        return Err(format!("Close {} before changing it", instance_id));
+    }
+    Ok(())
+}
+
+// Command to list the worlds of an instance
+#[tauri::command]
+async fn list_worlds(instance_id: String, app_handle: AppHandle) -> Result<Vec<saves::World>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    saves::list_worlds(&data_path, &instance).map_err(|e| e.to_string())
+}
+
+// Command to rename a world
+#[tauri::command]
+async fn rename_world(instance_id: String, folder: String, name: String, app_handle: AppHandle) -> Result<saves::World, String> {
+    ensure_not_running(&app_handle, &instance_id)?;
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    saves::rename_world(&data_path, &instance, &folder, &name).map_err(|e| e.to_string())
+}
+
+// Command to move a world to the trash
+#[tauri::command]
+async fn delete_world(instance_id: String, folder: String, app_handle: AppHandle) -> Result<(), String> {
+    ensure_not_running(&app_handle, &instance_id)?;
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    saves::delete_world(&data_path, &instance, &folder).map_err(|e| e.to_string())
+}
+
+// Command to duplicate a world within its instance
+#[tauri::command]
+async fn duplicate_world(instance_id: String, folder: String, app_handle: AppHandle) -> Result<saves::World, String> {
+    // A running game may be writing the world while it is copied
+    ensure_not_running(&app_handle, &instance_id)?;
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    saves::duplicate_world(&data_path, &instance, &folder).map_err(|e| e.to_string())
+}
+
+// Command to copy a world to another instance
+#[tauri::command]
+async fn copy_world(
+    instance_id: String,
+    folder: String,
+    target_instance_id: String,
+    app_handle: AppHandle,
+) -> Result<saves::CopiedWorld, String> {
+    ensure_not_running(&app_handle, &instance_id)?;
+    ensure_not_running(&app_handle, &target_instance_id)?;
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let source = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let target = instances::load_instance(&data_path, &target_instance_id).map_err(|e| e.to_string())?;
+    saves::copy_world(&data_path, &source, &folder, &target).map_err(|e| e.to_string())
+}
+
+// Command to list the backups of an instance, or of one of its worlds
+#[tauri::command]
+async fn list_backups(instance_id: String, world: Option<String>, app_handle: AppHandle) -> Result<Vec<backups::BackupInfo>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    backups::list_backups(&data_path, &instance_id, world.as_deref()).map_err(|e| e.to_string())
+}
+
+// Command to back up a world, progress is reported through `backup-progress` events
+#[tauri::command]
+async fn create_backup(instance_id: String, world: String, app_handle: AppHandle) -> Result<backups::BackupInfo, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let settings = app_handle.state::<AppState>().settings.lock().map_err(|_| "Failed to lock settings")?.backups.clone();
+    let progress = backup_progress_emitter(&app_handle);
+    
+    tauri::async_runtime::spawn_blocking(move || {
+        backups::back_up_world(&data_path, &instance, &world, backups::BackupReason::Manual, &settings, &progress)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Command to replace a world with one of its backups, the current world is backed up first
+#[tauri::command]
+async fn restore_backup(instance_id: String, world: String, backup_id: String, app_handle: AppHandle) -> Result<(), String> {
+    ensure_not_running(&app_handle, &instance_id)?;
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let settings = app_handle.state::<AppState>().settings.lock().map_err(|_| "Failed to lock settings")?.backups.clone();
+    let progress = backup_progress_emitter(&app_handle);
+    
+    tauri::async_runtime::spawn_blocking(move || {
+        backups::restore_backup(&data_path, &instance, &world, &backup_id, &settings, &progress)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Command to delete a backup
+#[tauri::command]
+async fn delete_backup(instance_id: String, world: String, backup_id: String, app_handle: AppHandle) -> Result<(), String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        backups::delete_backup(&data_path, &instance_id, &world, &backup_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Resource pack format of an instance's game version, None when it isn't installed or predates version.json
+fn instance_pack_format(data_path: &Path, instance: &instances::Instance) -> Option<i64> {
+    versions::resource_pack_format(data_path, &instance.game_version).ok().flatten()
+}
+
+// Command to list the resource packs of an instance, flagged against its game version
+#[tauri::command]
+async fn list_resource_packs(instance_id: String, app_handle: AppHandle) -> Result<Vec<packs::ResourcePack>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let game_format = instance_pack_format(&data_path, &instance);
+    packs::list_resource_packs(&data_path, &instance, game_format).map_err(|e| e.to_string())
+}
+
+// Command to set the enabled resource packs, highest priority first
+#[tauri::command]
+async fn set_resource_packs(instance_id: String, enabled: Vec<String>, app_handle: AppHandle) -> Result<Vec<packs::ResourcePack>, String> {
+    ensure_not_running(&app_handle, &instance_id)?;
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    let game_format = instance_pack_format(&data_path, &instance);
+    packs::set_resource_packs(&data_path, &instance, game_format, &enabled).map_err(|e| e.to_string())
+}
+
+// Command to list the shader packs of an instance
+#[tauri::command]
+async fn list_shader_packs(instance_id: String, app_handle: AppHandle) -> Result<Vec<packs::ShaderPack>, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    packs::list_shader_packs(&data_path, &instance).map_err(|e| e.to_string())
+}
+
+// Command to list games started from this launcher
+#[tauri::command]
+async fn get_running_games(app_handle: AppHandle) -> Result<Vec<launcher::RunningGame>, String> {
+    Ok(running_games(&app_handle))
+}
+
+// Command to stop a running game
+#[tauri::command]
+async fn kill_game(pid: u32, app_handle: AppHandle) -> Result<(), String> {
+    kill_running_game(&app_handle, pid)
+}
+
+// Bring the main window back from the tray or the background
+fn show_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+// Command to launch an instance, optionally joining a server, `game-exited` is
+// emitted when the game closes
+#[tauri::command]
+async fn launch_instance(instance_id: String, server: Option<String>, app_handle: AppHandle) -> Result<u32, String> {
+    start_instance(&app_handle, &instance_id, server, None)
+}
+
+// Command to create a desktop shortcut that launches an instance
+#[tauri::command]
+async fn create_instance_shortcut(instance_id: String, app_handle: AppHandle) -> Result<PathBuf, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let instance = instances::load_instance(&data_path, &instance_id).map_err(|e| e.to_string())?;
+    deep_link::create_instance_shortcut(&instance).map_err(|e| e.to_string())
+}
+
+// Resolve an intelligence:// link without acting on it. Anyone can send a link,
+// so the frontend asks the user before launching through launch_instance
+fn handle_deep_link(app_handle: &AppHandle, link: &str) -> Result<deep_link::DeepLink, String> {
+    let parsed = deep_link::parse(link)?;
+    let data_path = get_data_path(app_handle).map_err(|e| e.to_string())?;
+    
+    match parsed {
+        deep_link::DeepLink::Launch { instance } => {
+            instances::load_instance(&data_path, &instance).map_err(|e| e.to_string())?;
+            Ok(deep_link::DeepLink::Launch { instance })
+        },
+        deep_link::DeepLink::JoinServer { address, instance } => {
+            // Without an instance, join with the most recently played one
+            let instance = match instance {
+                Some(instance) => instances::load_instance(&data_path, &instance).map_err(|e| e.to_string())?.id,
+                None => instances::list_instances(&data_path)
+                    .map_err(|e| e.to_string())?
+                    .into_iter()
+                    .next()
+                    .map(|instance| instance.id)
+                    .ok_or("No instance to join the server with")?,
+            };
+            Ok(deep_link::DeepLink::JoinServer { address, instance: Some(instance) })
+        },
+        // There is no modpack installer yet, say so instead of ignoring the link
+        deep_link::DeepLink::InstallModpack { .. } => Err("Installing modpacks from links is not supported yet".to_string()),
+    }
+}
+
+// Send a resolved link, or why it couldn't be opened, to the frontend
+fn emit_deep_link<R: tauri::Runtime>(emitter: &impl Emitter<R>, result: Result<deep_link::DeepLink, deep_link::DeepLinkFailed>) {
+    let emitted = match result {
+        Ok(parsed) => emitter.emit("deep-link", parsed),
+        Err(failed) => emitter.emit("deep-link-failed", failed),
+    };
+    if let Err(e) = emitted {
+        eprintln!("Failed to emit deep link event: {}", e);
+    }
+}
+
+// Handle a link and notify the frontend. Links the app was started with are
+// queued until the frontend has loaded
+fn open_deep_link(app_handle: &AppHandle, link: &str, queue: bool) {
+    let result = handle_deep_link(app_handle, link).map_err(|message| {
+        eprintln!("Failed to open link {}: {}", link, message);
+        deep_link::DeepLinkFailed { link: link.to_string(), message }
+    });
+    
+    if queue {
+        *app_handle.state::<AppState>().pending_deep_link.lock().unwrap() = Some(result);
+    } else {
+        emit_deep_link(app_handle, result);
+    }
+}
+
+// Handle arguments forwarded by a second launcher process. Errors are sent
+// back to it
+fn handle_forwarded_args(app_handle: &AppHandle, args: Vec<String>) -> Result<(), String> {
+    show_main_window(app_handle);
+    
+    if let Some(link) = deep_link::from_args(args.iter().cloned()) {
+        open_deep_link(app_handle, &link, false);
+        return Ok(());
+    }
+    
+    // `intelligence launch <instance> [--username <name>]`
+    if let (Some("launch"), Some(instance)) = (args.first().map(String::as_str), args.get(1)) {
+        let username = cli::option_value(&args[1..], "--username").map(str::to_string);
+        let data_path = get_data_path(app_handle).map_err(|e| e.to_string())?;
+        let instance = instances::find_instance(&data_path, instance).map_err(|e| e.to_string())?;
+        start_instance(app_handle, &instance.id, None, username)?;
+    }
+    Ok(())
+}
+
+// Command to get system information
+#[tauri::command]
+async fn get_system_info(app_handle: AppHandle) -> Result<diagnostics::SystemInfo, String> {
+    let data_path = get_data_path(&app_handle).ok();
+    Ok(diagnostics::system_info(data_path.as_deref()))
+}
+
+// Command to build a diagnostics report for bug reports, as text or JSON,
+// with the username and paths (data directory, home, any other absolute path) redacted
+#[tauri::command]
+async fn generate_diagnostics_report(json: bool, app_handle: AppHandle) -> Result<String, String> {
+    let data_path = get_data_path(&app_handle).map_err(|e| e.to_string())?;
+    let username = app_handle.state::<AppState>().settings.lock().map_err(|_| "Failed to lock settings")?.username.clone();
+    
+    let report = diagnostics::build_report(&data_path);
+    diagnostics::render(&report, json, &username, &data_path)
 }
 
 #[tauri::command]
@@ -459,18 +1919,14 @@ fn apply_titlebar_style(app: &tauri::App) -> Result<(), Box<dyn Error>> {
     let settings = state.settings.lock().map_err(|_| "Failed to lock settings")?;
     
     // Apply titlebar style based on settings
-    match settings.titlebar_style.as_str() {
-        "native" => {
+    match settings.titlebar_style {
+        TitlebarStyle::Native => {
             // Enable native decorations
             window.set_decorations(true)?;
         },
-        "custom" | "macos" => {
+        TitlebarStyle::Custom | TitlebarStyle::Macos => {
             // Disable native decorations for custom titlebar
             window.set_decorations(false)?;
-        },
-        _ => {
-            // Default to custom titlebar
-            window.set_decorations(false)?;
         }
     }
     
@@ -485,18 +1941,21 @@ pub fn run() {
             // Initialize settings path
             let settings_path = get_settings_file_path(&app.app_handle())?;
             
+            // Ensure language files exist, first run picks a language from them
+            ensure_language_files_exist(app.app_handle())?;
+            
             // Load or create settings
-            let settings = if settings_path.exists() {
+            let (settings, settings_recovery) = if settings_path.exists() {
                 load_settings(&settings_path)?
             } else {
-                let default_settings = Settings::default();
+                let default_settings = Settings {
+                    language: detect_initial_language(app.app_handle()),
+                    ..Settings::default()
+                };
                 save_settings(&default_settings, &settings_path)?;
-                default_settings
+                (default_settings, None)
             };
             
-            // Ensure language files exist
-            ensure_language_files_exist(&app.app_handle())?;
-            
             // Load current language
             let current_language = load_language(&app.app_handle(), &settings.language)?;
             
@@ -505,31 +1964,253 @@ pub fn run() {
                 settings: Mutex::new(settings),
                 settings_path: Mutex::new(Some(settings_path)),
                 current_language: Mutex::new(current_language),
+                settings_recovery: Mutex::new(settings_recovery),
+                pending_deep_link: Mutex::new(None),
+                running_games: Mutex::new(Vec::new()),
             });
             
             // Apply titlebar style based on settings
             apply_titlebar_style(app)?;
             
+            // Pick up external edits to settings and language files
+            watcher::spawn(app.app_handle().clone());
+            
+            // Discord Rich Presence, follows settings.discord_rpc
+            presence::spawn(app.app_handle().clone());
+            
+            // Scheduled world backups, follows settings.backups
+            spawn_backup_scheduler(app.app_handle().clone());
+            
+            // Tray icon, unavailable on some Linux desktops
+            if let Err(e) = tray::create(app.app_handle()) {
+                eprintln!("Failed to create tray icon: {}", e);
+            }
+            
+            // Take over links and launch requests from later invocations
+            let forward_handle = app.app_handle().clone();
+            if let Err(e) = single_instance::listen(move |args| handle_forwarded_args(&forward_handle, args)) {
+                eprintln!("Failed to listen for other launcher processes: {}", e);
+            }
+            
+            // Make intelligence:// links open the launcher
+            if let Err(e) = deep_link::register_scheme() {
+                eprintln!("Failed to register {}:// links: {}", deep_link::SCHEME, e);
+            }
+            
+            // Act on a link the app was started with
+            if let Some(link) = deep_link::from_args(std::env::args()) {
+                open_deep_link(app.app_handle(), &link, true);
+            }
+            
             Ok(())
         })
+        .on_page_load(|webview, payload| {
+            // Report settings recovery once the frontend is ready to receive events
+            if payload.event() != tauri::webview::PageLoadEvent::Finished {
+                return;
+            }
+            if let Some(state) = webview.try_state::<AppState>() {
+                let recovery = state.settings_recovery.lock().unwrap().take();
+                if let Some(recovery) = recovery {
+                    if let Err(e) = webview.emit("settings-recovered", recovery) {
+                        eprintln!("Failed to emit settings recovery event: {}", e);
+                    }
+                }
+                
+                let deep_link = state.pending_deep_link.lock().unwrap().take();
+                if let Some(result) = deep_link {
+                    emit_deep_link(webview, result);
+                }
+            }
+        })
+        .on_window_event(|window, event| {
+            // Hide to the tray instead of closing when the user asked for it
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                let minimize_to_tray = window
+                    .try_state::<AppState>()
+                    .is_some_and(|state| state.settings.lock().unwrap().minimize_to_tray);
+                if minimize_to_tray && tray::is_available(window.app_handle()) {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             get_system_info,
+            generate_diagnostics_report,
             get_settings,
             update_settings,
+            patch_settings,
+            get_app_directories,
+            migrate_data_directory,
             get_available_languages,
             get_translations,
+            get_missing_translations,
+            validate_language,
+            get_community_languages,
+            install_community_language,
+            format_message,
             change_language,
+            list_instances,
+            create_instance,
+            get_jvm_presets,
+            update_instance_jvm,
+            apply_jvm_preset,
+            install_version,
+            launch_instance,
+            create_instance_shortcut,
+            get_running_games,
+            kill_game,
+            get_crash_report,
+            list_log_sessions,
+            search_logs,
+            export_log,
+            list_worlds,
+            rename_world,
+            delete_world,
+            duplicate_world,
+            copy_world,
+            list_backups,
+            create_backup,
+            restore_backup,
+            delete_backup,
+            list_resource_packs,
+            set_resource_packs,
+            list_shader_packs,
         ])
         .build(tauri::generate_context!())
         .expect("error while running tauri application");
         
-    app.run(|_app_handle, event| {
+    app.run(|app_handle, event| {
         match event {
-            tauri::RunEvent::ExitRequested { api, .. } => {
-                api.prevent_exit();
+            tauri::RunEvent::ExitRequested { api, code, .. } => {
+                // Closing the last window keeps the app in the tray, Quit passes an exit code
+                let minimize_to_tray = app_handle
+                    .try_state::<AppState>()
+                    .is_some_and(|state| state.settings.lock().unwrap().minimize_to_tray);
+                if code.is_none() && minimize_to_tray {
+                    api.prevent_exit();
+                }
+            }
+            tauri::RunEvent::Exit => {
+                single_instance::release();
+            }
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            tauri::RunEvent::Opened { urls } => {
+                for url in urls {
+                    open_deep_link(app_handle, url.as_str(), false);
+                }
             }
             _ => {}
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn change(edit: impl FnOnce(&mut Settings)) -> SettingsChanged {
+        let old = Settings::default();
+        let mut settings = old.clone();
+        edit(&mut settings);
+        SettingsChanged { changed: changed_settings_keys(&old, &settings), settings, restart_required: Vec::new() }
+    }
+    
+    // Tray labels are translated
+    #[test]
+    fn language_change_needs_tray_refresh() {
+        let change = change(|settings| settings.language = "ru_RU".to_string());
+        assert_eq!(change.changed, vec!["language".to_string()]);
+        assert!(change.tray_refresh_needed());
+    }
+    
+    #[test]
+    fn data_directory_change_refreshes_tray() {
+        assert!(change(|settings| settings.data_directory = Some(PathBuf::from("/games"))).tray_refresh_needed());
+        assert!(!change(|settings| settings.theme = Theme::Dark).tray_refresh_needed());
+    }
+    
+    #[test]
+    fn unknown_patch_keys_are_found_at_any_depth() {
+        let settings = serde_json::to_value(Settings::default()).unwrap();
+        let unknown = |patch: serde_json::Value| unknown_patch_key(&settings, patch.as_object().unwrap());
+        
+        assert_eq!(unknown(serde_json::json!({ "theme": "dark", "backups": { "keep_last": 3 } })), None);
+        assert_eq!(unknown(serde_json::json!({ "data_directory": "/games" })), None);
+        assert_eq!(unknown(serde_json::json!({ "thme": "dark" })), Some("thme".to_string()));
+        assert_eq!(
+            unknown(serde_json::json!({ "backups": { "on_exit": true, "keep_lst": 1 } })),
+            Some("backups.keep_lst".to_string())
+        );
+        // Replacing an object with a non-object is left to deserialization
+        assert_eq!(unknown(serde_json::json!({ "backups": null })), None);
+    }
+    
+    #[test]
+    fn enum_values_match_serialized_names() {
+        for value in Theme::VALUES {
+            let theme: Theme = serde_json::from_value(serde_json::json!(value)).unwrap();
+            assert_eq!(serde_json::to_value(theme).unwrap(), *value);
+        }
+        for value in TitlebarStyle::VALUES {
+            let style: TitlebarStyle = serde_json::from_value(serde_json::json!(value)).unwrap();
+            assert_eq!(serde_json::to_value(style).unwrap(), *value);
+        }
+    }
+    
+    #[test]
+    fn invalid_enum_values_are_reported() {
+        let mut value = serde_json::to_value(Settings::default()).unwrap();
+        value["theme"] = serde_json::json!("purple");
+        match settings_from_value(value).unwrap_err() {
+            SettingsError::InvalidValue { field, value, expected } => {
+                assert_eq!(field, "theme");
+                assert_eq!(value, "purple");
+                assert_eq!(expected, Theme::VALUES);
+            },
+            other => panic!("unexpected error {:?}", other),
+        }
+        
+        let mut value = serde_json::to_value(Settings::default()).unwrap();
+        value["titlebar_style"] = serde_json::json!(5);
+        assert!(matches!(
+            settings_from_value(value),
+            Err(SettingsError::InvalidValue { field, value, .. }) if field == "titlebar_style" && value == "5"
+        ));
+        
+        let value = serde_json::json!({ "theme": "dark" });
+        assert!(matches!(settings_from_value(value), Err(SettingsError::InvalidPatch { .. })));
+    }
+    
+    #[test]
+    fn invalid_fields_reset_to_defaults() {
+        let contents = r#"{
+            "theme": "purple",
+            "discord_rpc": false,
+            "advanced_rendering": true,
+            "language": "de_DE",
+            "titlebar_style": "native",
+            "username": "Steve",
+            "backups": { "interval_minutes": 30, "on_exit": true, "keep_last": "many", "keep_daily": 7, "keep_weekly": 4 }
+        }"#;
+        let (settings, mut reset) = parse_settings(contents).unwrap();
+        reset.sort();
+        
+        assert_eq!(reset, vec!["backups.keep_last", "theme"]);
+        assert_eq!(settings.theme, Theme::System);
+        assert!(!settings.discord_rpc);
+        assert_eq!(settings.language, "de_DE");
+        assert_eq!(settings.titlebar_style, TitlebarStyle::Native);
+        assert_eq!(settings.username, "Steve");
+        assert_eq!(settings.backups.interval_minutes, Some(30));
+        assert!(settings.backups.on_exit);
+        assert_eq!(settings.backups.keep_last, backups::BackupSettings::default().keep_last);
+        
+        let valid = serde_json::to_string(&Settings::default()).unwrap();
+        assert!(parse_settings(&valid).unwrap().1.is_empty());
+        assert!(parse_settings("{ truncated").is_err());
+        assert!(parse_settings("[]").is_err());
+    }
+}
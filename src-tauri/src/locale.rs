@@ -0,0 +1,131 @@
+// Detects the OS locale and maps it to an installed language pack
+use std::env;
+
+// Environment variables consulted for the UI language, highest priority first
+const LOCALE_VARS: &[&str] = &["LC_ALL", "LC_MESSAGES", "LANG"];
+
+// Normalize a POSIX locale such as "ru_RU.UTF-8@euro" to "ru_RU".
+// Returns None for the "C"/"POSIX" locales which carry no language
+pub fn normalize_locale(value: &str) -> Option<String> {
+    let locale = value
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .replace('-', "_");
+
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+
+    // ll_CC: lowercase language, uppercase territory
+    Some(match locale.split_once('_') {
+        Some((language, territory)) => format!("{}_{}", language.to_lowercase(), territory.to_uppercase()),
+        None => locale.to_lowercase(),
+    })
+}
+
+// Detect the locale from an environment lookup, following POSIX precedence
+pub fn detect_locale_with(env: impl Fn(&str) -> Option<String>) -> Option<String> {
+    LOCALE_VARS
+        .iter()
+        .filter_map(|var| env(var))
+        .find(|value| !value.trim().is_empty())
+        .and_then(|value| normalize_locale(&value))
+}
+
+// Detect the locale of the current process
+pub fn detect_locale() -> Option<String> {
+    detect_locale_with(|var| env::var(var).ok())
+}
+
+// Pick the best available language id for a locale: an exact match first, then a
+// pack for the same language ("ru" before any other "ru_*" pack)
+pub fn match_locale(locale: &str, available: &[String]) -> Option<String> {
+    let locale = normalize_locale(locale)?;
+    let language = locale.split('_').next().unwrap_or_default();
+
+    if let Some(exact) = available.iter().find(|id| normalize_locale(id).as_deref() == Some(locale.as_str())) {
+        return Some(exact.clone());
+    }
+
+    if let Some(language_only) = available.iter().find(|id| normalize_locale(id).as_deref() == Some(language)) {
+        return Some(language_only.clone());
+    }
+
+    let mut same_language: Vec<&String> = available
+        .iter()
+        .filter(|id| {
+            normalize_locale(id)
+                .is_some_and(|id| id.split('_').next() == Some(language))
+        })
+        .collect();
+    same_language.sort();
+    same_language.first().map(|id| (*id).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<String> {
+        let env: HashMap<String, String> = vars.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        detect_locale_with(|var| env.get(var).cloned())
+    }
+
+    fn available(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn lc_all_wins_over_lc_messages_and_lang() {
+        let vars = [("LC_ALL", "de_DE.UTF-8"), ("LC_MESSAGES", "fr_FR.UTF-8"), ("LANG", "ru_RU.UTF-8")];
+        assert_eq!(detect(&vars), Some("de_DE".to_string()));
+        assert_eq!(detect(&vars[1..]), Some("fr_FR".to_string()));
+        assert_eq!(detect(&vars[2..]), Some("ru_RU".to_string()));
+    }
+
+    #[test]
+    fn empty_values_fall_through() {
+        assert_eq!(detect(&[("LC_ALL", ""), ("LC_MESSAGES", " "), ("LANG", "ru_RU.UTF-8")]), Some("ru_RU".to_string()));
+        assert_eq!(detect(&[]), None);
+    }
+
+    #[test]
+    fn c_and_posix_have_no_language() {
+        assert_eq!(detect(&[("LANG", "C")]), None);
+        assert_eq!(detect(&[("LANG", "POSIX")]), None);
+        assert_eq!(detect(&[("LANG", "C.UTF-8")]), None);
+        // LC_ALL=C overrides LANG, as it does for every other program
+        assert_eq!(detect(&[("LC_ALL", "C"), ("LANG", "ru_RU.UTF-8")]), None);
+    }
+
+    #[test]
+    fn strips_codeset_and_modifier() {
+        assert_eq!(normalize_locale("ru_RU.UTF-8"), Some("ru_RU".to_string()));
+        assert_eq!(normalize_locale("de_DE@euro"), Some("de_DE".to_string()));
+        assert_eq!(normalize_locale("sr_RS.UTF-8@latin"), Some("sr_RS".to_string()));
+        assert_eq!(normalize_locale("en-us"), Some("en_US".to_string()));
+    }
+
+    #[test]
+    fn matches_exact_locale_first() {
+        assert_eq!(match_locale("ru_RU.UTF-8", &available(&["en_US", "ru_RU"])), Some("ru_RU".to_string()));
+        assert_eq!(match_locale("pt_BR", &available(&["pt_PT", "pt_BR"])), Some("pt_BR".to_string()));
+    }
+
+    #[test]
+    fn falls_back_to_the_same_language() {
+        assert_eq!(match_locale("ru", &available(&["en_US", "ru_RU"])), Some("ru_RU".to_string()));
+        assert_eq!(match_locale("ru_UA", &available(&["en_US", "ru_RU"])), Some("ru_RU".to_string()));
+        assert_eq!(match_locale("pt_AO", &available(&["pt_PT", "pt", "pt_BR"])), Some("pt".to_string()));
+        assert_eq!(match_locale("pt_AO", &available(&["pt_PT", "pt_BR"])), Some("pt_BR".to_string()));
+    }
+
+    #[test]
+    fn no_match_for_other_languages() {
+        assert_eq!(match_locale("ja_JP", &available(&["en_US", "ru_RU"])), None);
+        assert_eq!(match_locale("C", &available(&["en_US"])), None);
+    }
+}
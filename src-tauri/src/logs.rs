@@ -0,0 +1,406 @@
+// Log history. Each launch's output is written to
+// <instance>/logs/<started_at>-<pid>.log, gzipped once the game exits, and the
+// oldest sessions are dropped past MAX_SESSIONS. Logs can be searched and
+// exported with personal details redacted for sharing
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::Ipv6Addr;
+use std::path::{Path, PathBuf};
+use std::process::{ChildStderr, ChildStdout};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::{Captures, Regex};
+use serde::Serialize;
+
+use crate::instances::{self, Instance};
+
+type LogResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// Sessions kept per instance
+const MAX_SESSIONS: usize = 30;
+
+// Output past this size is dropped, a runaway mod shouldn't fill the disk
+const MAX_SESSION_SIZE: u64 = 64 * 1024 * 1024;
+
+// Search stops after this many matching lines
+const MAX_SEARCH_RESULTS: usize = 500;
+
+const LOG_EXTENSION: &str = "log";
+const COMPRESSED_EXTENSION: &str = "log.gz";
+
+// A logged game session
+#[derive(Debug, Serialize, Clone)]
+pub struct LogSession {
+    pub id: String,
+    pub instance_id: String,
+    pub started_at: u64,
+    pub size: u64,
+    // Still being written by a running game
+    pub active: bool,
+}
+
+// A line matching a search
+#[derive(Debug, Serialize, Clone)]
+pub struct LogMatch {
+    pub instance_id: String,
+    pub session_id: String,
+    pub line_number: usize,
+    pub line: String,
+}
+
+pub fn logs_dir(data_path: &Path, instance: &Instance) -> PathBuf {
+    instance.path(data_path).join("logs")
+}
+
+pub fn session_id(started_at: u64, pid: u32) -> String {
+    format!("{}-{}", started_at, pid)
+}
+
+// Session ids come from the frontend, only accept ones we could have made
+fn validate_session_id(id: &str) -> LogResult<()> {
+    let valid = id
+        .split_once('-')
+        .is_some_and(|(time, pid)| time.parse::<u64>().is_ok() && pid.parse::<u32>().is_ok());
+    if !valid {
+        return Err(format!("Invalid log session: {}", id).into());
+    }
+    Ok(())
+}
+
+// Copy the game's output into the session log on background threads. The pipes
+// are drained even if the log can't be written so the game never blocks
+pub fn capture(dir: &Path, session_id: &str, stdout: ChildStdout, stderr: ChildStderr) {
+    let path = dir.join(format!("{}.{}", session_id, LOG_EXTENSION));
+    let file: Box<dyn Write + Send> = match fs::create_dir_all(dir).and_then(|_| File::create(&path)) {
+        Ok(file) => Box::new(file),
+        Err(e) => {
+            eprintln!("Failed to create log {:?}: {}", path, e);
+            Box::new(io::sink())
+        }
+    };
+    let sink = Arc::new(Mutex::new((file, 0u64)));
+
+    let stderr_sink = sink.clone();
+    let dir = dir.to_path_buf();
+    thread::spawn(move || {
+        let stderr_thread = thread::spawn(move || copy_lines(stderr, &stderr_sink));
+        copy_lines(stdout, &sink);
+        let _ = stderr_thread.join();
+        drop(sink);
+
+        if let Err(e) = compress(&path).and_then(|_| rotate(&dir)) {
+            eprintln!("Failed to archive log {:?}: {}", path, e);
+        }
+    });
+}
+
+fn copy_lines(pipe: impl Read, sink: &Mutex<(Box<dyn Write + Send>, u64)>) {
+    let mut reader = BufReader::new(pipe);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line).is_ok_and(|read| read > 0) {
+        let mut sink = sink.lock().unwrap();
+        let (file, written) = &mut *sink;
+        if *written + line.len() as u64 <= MAX_SESSION_SIZE && file.write_all(&line).is_ok() {
+            *written += line.len() as u64;
+        }
+        line.clear();
+    }
+    let _ = sink.lock().unwrap().0.flush();
+}
+
+// Replace a finished log with its gzipped copy
+fn compress(path: &Path) -> LogResult<()> {
+    let compressed_path = path.with_extension(COMPRESSED_EXTENSION);
+    let mut encoder = GzEncoder::new(File::create(&compressed_path)?, Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+// Delete the oldest finished sessions past MAX_SESSIONS
+fn rotate(dir: &Path) -> LogResult<()> {
+    let sessions = list_dir(dir, "")?;
+    for session in sessions.iter().filter(|session| !session.active).skip(MAX_SESSIONS) {
+        fs::remove_file(dir.join(format!("{}.{}", session.id, COMPRESSED_EXTENSION)))?;
+    }
+    Ok(())
+}
+
+// Sessions in a logs directory, newest first
+fn list_dir(dir: &Path, instance_id: &str) -> LogResult<Vec<LogSession>> {
+    let mut sessions = Vec::new();
+    if !dir.exists() {
+        return Ok(sessions);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let (id, active) = match name.strip_suffix(&format!(".{}", COMPRESSED_EXTENSION)) {
+            Some(id) => (id.to_string(), false),
+            None => match name.strip_suffix(&format!(".{}", LOG_EXTENSION)) {
+                Some(id) => (id.to_string(), true),
+                None => continue,
+            },
+        };
+        if validate_session_id(&id).is_err() {
+            continue;
+        }
+
+        sessions.push(LogSession {
+            started_at: id.split_once('-').and_then(|(time, _)| time.parse().ok()).unwrap_or_default(),
+            instance_id: instance_id.to_string(),
+            size: entry.metadata()?.len(),
+            active,
+            id,
+        });
+    }
+
+    sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at).then_with(|| b.id.cmp(&a.id)));
+    Ok(sessions)
+}
+
+// Logged sessions of an instance, newest first
+pub fn list_sessions(data_path: &Path, instance: &Instance) -> LogResult<Vec<LogSession>> {
+    list_dir(&logs_dir(data_path, instance), &instance.id)
+}
+
+// Open a session log, decompressing archived ones
+fn open_session(data_path: &Path, instance: &Instance, session_id: &str) -> LogResult<Box<dyn BufRead>> {
+    validate_session_id(session_id)?;
+    let dir = logs_dir(data_path, instance);
+
+    let compressed = dir.join(format!("{}.{}", session_id, COMPRESSED_EXTENSION));
+    if compressed.exists() {
+        return Ok(Box::new(BufReader::new(GzDecoder::new(File::open(compressed)?))));
+    }
+    let plain = dir.join(format!("{}.{}", session_id, LOG_EXTENSION));
+    if plain.exists() {
+        return Ok(Box::new(BufReader::new(File::open(plain)?)));
+    }
+    Err(format!("No log session {} for {}", session_id, instance.name).into())
+}
+
+pub fn read_session(data_path: &Path, instance: &Instance, session_id: &str) -> LogResult<String> {
+    let mut bytes = Vec::new();
+    open_session(data_path, instance, session_id)?.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+// Case-insensitive search through the logs of one or all instances, newest sessions first
+pub fn search(data_path: &Path, instance_id: Option<&str>, query: &str) -> LogResult<Vec<LogMatch>> {
+    let query = query.to_lowercase();
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return Ok(matches);
+    }
+
+    let instances = match instance_id {
+        Some(id) => vec![instances::load_instance(data_path, id)?],
+        None => instances::list_instances(data_path)?,
+    };
+    for instance in &instances {
+        for session in list_sessions(data_path, instance)? {
+            // A truncated or corrupt archive only costs its own results
+            let reader = match open_session(data_path, instance, &session.id) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    eprintln!("Skipping log {} of {} in search: {}", session.id, instance.id, e);
+                    continue;
+                }
+            };
+            for (index, line) in reader.split(b'\n').enumerate() {
+                let line = match line {
+                    Ok(line) => String::from_utf8_lossy(&line).trim_end().to_string(),
+                    Err(e) => {
+                        eprintln!("Skipping the rest of log {} of {} in search: {}", session.id, instance.id, e);
+                        break;
+                    }
+                };
+                if !line.to_lowercase().contains(&query) {
+                    continue;
+                }
+                matches.push(LogMatch {
+                    instance_id: instance.id.clone(),
+                    session_id: session.id.clone(),
+                    line_number: index + 1,
+                    line,
+                });
+                if matches.len() >= MAX_SEARCH_RESULTS {
+                    return Ok(matches);
+                }
+            }
+        }
+    }
+    Ok(matches)
+}
+
+type Replacer = Box<dyn Fn(&Captures) -> String + Send + Sync>;
+
+// A redaction rule: everything the pattern matches is rewritten by the replacer
+struct Rule {
+    pattern: Regex,
+    replace: Replacer,
+}
+
+impl Rule {
+    fn new(pattern: &str, replace: impl Fn(&Captures) -> String + Send + Sync + 'static) -> Self {
+        Self { pattern: Regex::new(pattern).expect("invalid redaction pattern"), replace: Box::new(replace) }
+    }
+
+    // Replace matches with a fixed placeholder, keeping the first group if there is one
+    fn placeholder(pattern: &str, placeholder: &'static str) -> Self {
+        Self::new(pattern, move |caps| {
+            let prefix = caps.get(1).map(|group| group.as_str()).unwrap_or_default();
+            format!("{}{}", prefix, placeholder)
+        })
+    }
+}
+
+// Removes usernames, UUIDs, access tokens, home paths and IP addresses from logs
+pub struct Redactor {
+    rules: Vec<Rule>,
+}
+
+impl Redactor {
+    pub fn new(usernames: &[String], home: Option<&Path>) -> Self {
+        let mut rules = Vec::new();
+
+        // Home paths first, they usually contain the account name
+        if let Some(home) = home.map(|home| home.to_string_lossy().to_string()).filter(|home| home.len() > 1) {
+            let variants = [home.clone(), home.replace('\\', "/")];
+            let alternatives: Vec<String> = variants.iter().map(|variant| regex::escape(variant)).collect();
+            rules.push(Rule::new(&format!("(?i){}", alternatives.join("|")), |_| "~".to_string()));
+        }
+
+        rules.extend([
+            Rule::placeholder(r"(--accessToken\s+)\S+", "<token>"),
+            Rule::placeholder(r#"(?i)((?:access_?token|session_?id)["']?\s*[=:]\s*["']?)[^\s"',]+"#, "<token>"),
+            Rule::placeholder(r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+", "<token>"),
+            Rule::placeholder(
+                r"(?i)\b[0-9a-f]{8}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{12}\b",
+                "<uuid>",
+            ),
+            Rule::placeholder(
+                r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b",
+                "<ip>",
+            ),
+            // Candidates are checked by the parser so times like 12:00:00 survive, and
+            // need two groups and a digit so Java's Foo::bar does too
+            Rule::new(r"(?i)(?:[0-9a-f]{0,4}:){2,7}[0-9a-f]{0,4}", |caps| {
+                let candidate = &caps[0];
+                let groups = candidate.split(':').filter(|group| !group.is_empty()).count();
+                let is_address = groups >= 2
+                    && candidate.chars().any(|c| c.is_ascii_digit())
+                    && candidate.parse::<Ipv6Addr>().is_ok_and(|address| !address.is_unspecified());
+                if is_address { "<ip>".to_string() } else { candidate.to_string() }
+            }),
+            Rule::placeholder(r"(Setting user: )\S+", "<username>"),
+        ]);
+
+        let names: Vec<String> = usernames
+            .iter()
+            .filter(|name| !name.is_empty())
+            .map(|name| regex::escape(name))
+            .collect();
+        if !names.is_empty() {
+            rules.push(Rule::placeholder(&format!(r"\b(?:{})\b", names.join("|")), "<username>"));
+        }
+
+        Self { rules }
+    }
+
+    // Redactor for a log: the given usernames plus any the log logged in with
+    pub fn for_log(text: &str, usernames: &[String]) -> Self {
+        let mut usernames = usernames.to_vec();
+        usernames.extend(
+            text.lines()
+                .filter_map(|line| line.split_once("Setting user: "))
+                .filter_map(|(_, name)| name.split_whitespace().next())
+                .map(String::from),
+        );
+        Self::new(&usernames, dirs::home_dir().as_deref())
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        self.rules.iter().fold(text.to_string(), |text, rule| {
+            rule.pattern.replace_all(&text, |caps: &Captures| (rule.replace)(caps)).to_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(text: &str) -> String {
+        Redactor::new(&[], None).redact(text)
+    }
+
+    #[test]
+    fn redacts_access_token_argument() {
+        assert_eq!(redact("--username Steve --accessToken abc.def-123 --version 1.20.1"), "--username Steve --accessToken <token> --version 1.20.1");
+    }
+
+    #[test]
+    fn redacts_access_token_assignment() {
+        assert_eq!(redact("accessToken=xyz123, other=1"), "accessToken=<token>, other=1");
+        assert_eq!(redact(r#"{"access_token": "xyz123"}"#), r#"{"access_token": "<token>"}"#);
+    }
+
+    #[test]
+    fn redacts_jwt() {
+        assert_eq!(redact("token eyJhbGciOi.eyJzdWIiOi.c2lnbmF0dXJl end"), "token <token> end");
+    }
+
+    #[test]
+    fn redacts_dashed_and_undashed_uuids() {
+        assert_eq!(redact("UUID of player is 069a79f4-44e9-4726-a5be-fca90e38aaf5"), "UUID of player is <uuid>");
+        assert_eq!(redact("uuid 069a79f444e94726a5befca90e38aaf5."), "uuid <uuid>.");
+    }
+
+    #[test]
+    fn redacts_ipv4() {
+        assert_eq!(redact("Connecting to 192.168.1.20, 25565"), "Connecting to <ip>, 25565");
+        assert_eq!(redact("version 1.20.1"), "version 1.20.1");
+    }
+
+    #[test]
+    fn redacts_ipv6_but_not_times_or_method_references() {
+        assert_eq!(redact("Connecting to [2001:db8::1]:25565"), "Connecting to [<ip>]:25565");
+        assert_eq!(redact("[12:00:00] [main/INFO]: Done"), "[12:00:00] [main/INFO]: Done");
+        assert_eq!(redact("at Foo::bar and Baz::add"), "at Foo::bar and Baz::add");
+    }
+
+    #[test]
+    fn redacts_home_with_either_slash() {
+        let redactor = Redactor::new(&[], Some(Path::new(r"C:\Users\Steve")));
+        assert_eq!(redactor.redact(r"Loading C:\Users\Steve\AppData\mods"), r"Loading ~\AppData\mods");
+        assert_eq!(redactor.redact("Loading C:/Users/Steve/AppData/mods"), "Loading ~/AppData/mods");
+
+        let redactor = Redactor::new(&[], Some(Path::new("/home/steve")));
+        assert_eq!(redactor.redact("Loading /home/steve/.minecraft"), "Loading ~/.minecraft");
+    }
+
+    #[test]
+    fn redacts_logged_in_user() {
+        assert_eq!(redact("[main/INFO]: Setting user: Steve"), "[main/INFO]: Setting user: <username>");
+    }
+
+    #[test]
+    fn redacts_listed_usernames_as_whole_words() {
+        let redactor = Redactor::new(&["Alex".to_string()], None);
+        assert_eq!(redactor.redact("Alex joined, Alexander did not"), "<username> joined, Alexander did not");
+    }
+
+    #[test]
+    fn for_log_picks_up_the_session_user() {
+        let text = "Setting user: Steve\nSteve joined the game";
+        assert_eq!(Redactor::for_log(text, &[]).redact(text), "Setting user: <username>\n<username> joined the game");
+    }
+}
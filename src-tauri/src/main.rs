@@ -1,6 +1,41 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+// Release builds use the windows subsystem and get no console, so command
+// line output would be lost. Write to the console of the shell that started
+// us, if there is one
+#[cfg(windows)]
+fn attach_parent_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    
+    // Fails when started from Explorer or a link, there is nothing to attach to then
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    
+    #[cfg(windows)]
+    if !args.is_empty() {
+        attach_parent_console();
+    }
+    
+    // A launcher that is already running takes over links, launch requests and
+    // anything else that would open a second window
+    if let Some(code) = intelligence_lib::single_instance::forward(&args) {
+        std::process::exit(code);
+    }
+    
+    // Subcommands such as `launch` or `install` run headless without a window
+    if let Some(code) = intelligence_lib::cli::run(&args) {
+        std::process::exit(code);
+    }
+    
     intelligence_lib::run()
 }
@@ -0,0 +1,686 @@
+// ICU-style message formatting for translations: named placeholders,
+// CLDR plural categories, select forms and locale-aware numbers.
+//
+//   "{count, plural, one {# instance running} few {# instances} other {# instances running}}"
+//   "{gender, select, female {She} male {He} other {They}} joined {server}"
+use std::collections::HashMap;
+use std::fmt;
+use serde_json::Value;
+
+// Error raised while parsing or formatting a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatError {
+    pub message: String,
+}
+
+impl FormatError {
+    fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+// CLDR plural category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+const PLURAL_KEYWORDS: &[&str] = &["zero", "one", "two", "few", "many", "other"];
+
+// Parsed piece of a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    Text(String),
+    // {name}
+    Argument(String),
+    // {name, number}
+    Number(String),
+    // {name, plural, one {...} other {...}}
+    Plural { name: String, options: Vec<(String, Vec<Part>)> },
+    // {name, select, male {...} other {...}}
+    Select { name: String, options: Vec<(String, Vec<Part>)> },
+    // # inside a plural branch
+    Pound,
+}
+
+// A parsed message ready to be formatted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub parts: Vec<Part>,
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    source: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> FormatError {
+        FormatError::new(format!("{} at position {} in \"{}\"", message, self.pos, self.source))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), FormatError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", expected)))
+        }
+    }
+
+    // Read an argument name, type or option selector
+    fn read_word(&mut self) -> Result<String, FormatError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | ',' | '#' | '\''))
+        {
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return Err(self.error("expected a name"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    // Parse text and arguments until the end of input or a closing brace of the enclosing branch
+    fn parse_parts(&mut self, in_plural: bool, nested: bool) -> Result<Vec<Part>, FormatError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    self.pos += 1;
+                    parts.push(self.parse_argument(in_plural)?);
+                },
+                '}' => {
+                    if nested {
+                        break;
+                    }
+                    return Err(self.error("unmatched '}'"));
+                },
+                '#' if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    self.pos += 1;
+                    parts.push(Part::Pound);
+                },
+                '\'' => {
+                    self.pos += 1;
+                    self.parse_quoted(in_plural, &mut text)?;
+                },
+                _ => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    // Apostrophes quote syntax characters: '' is a literal apostrophe, '{' is a literal brace
+    fn parse_quoted(&mut self, in_plural: bool, text: &mut String) -> Result<(), FormatError> {
+        match self.peek() {
+            Some('\'') => {
+                text.push('\'');
+                self.pos += 1;
+            },
+            Some(c) if c == '{' || c == '}' || (c == '#' && in_plural) => {
+                loop {
+                    match self.peek() {
+                        None => return Err(self.error("unterminated quote")),
+                        Some('\'') if self.chars.get(self.pos + 1) == Some(&'\'') => {
+                            text.push('\'');
+                            self.pos += 2;
+                        },
+                        Some('\'') => {
+                            self.pos += 1;
+                            break;
+                        },
+                        Some(c) => {
+                            text.push(c);
+                            self.pos += 1;
+                        }
+                    }
+                }
+            },
+            _ => text.push('\''),
+        }
+        Ok(())
+    }
+
+    // Parse an argument after its opening brace
+    fn parse_argument(&mut self, in_plural: bool) -> Result<Part, FormatError> {
+        let name = self.read_word()?;
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('}') => {
+                self.pos += 1;
+                return Ok(Part::Argument(name));
+            },
+            Some(',') => self.pos += 1,
+            _ => return Err(self.error("expected ',' or '}'")),
+        }
+
+        let kind = self.read_word()?;
+        let part = match kind.as_str() {
+            "number" => Part::Number(name),
+            "plural" => {
+                self.expect(',')?;
+                let options = self.parse_options(true)?;
+                for (selector, _) in &options {
+                    let exact = selector.strip_prefix('=').is_some_and(|n| n.parse::<f64>().is_ok());
+                    if !exact && !PLURAL_KEYWORDS.contains(&selector.as_str()) {
+                        return Err(self.error(&format!("unknown plural category '{}'", selector)));
+                    }
+                }
+                Part::Plural { name, options }
+            },
+            "select" => {
+                self.expect(',')?;
+                Part::Select { name, options: self.parse_options(in_plural)? }
+            },
+            other => return Err(self.error(&format!("unknown argument type '{}'", other))),
+        };
+
+        self.expect('}')?;
+        Ok(part)
+    }
+
+    // Parse `selector {message}` pairs of a plural or select argument
+    fn parse_options(&mut self, in_plural: bool) -> Result<Vec<(String, Vec<Part>)>, FormatError> {
+        let mut options = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') || self.peek().is_none() {
+                break;
+            }
+
+            let selector = self.read_word()?;
+            self.expect('{')?;
+            let parts = self.parse_parts(in_plural, true)?;
+            self.expect('}')?;
+            options.push((selector, parts));
+        }
+
+        if !options.iter().any(|(selector, _)| selector == "other") {
+            return Err(self.error("missing 'other' option"));
+        }
+        Ok(options)
+    }
+}
+
+// Parse a message pattern
+pub fn parse(pattern: &str) -> Result<Message, FormatError> {
+    let mut parser = Parser { chars: pattern.chars().collect(), pos: 0, source: pattern };
+    let parts = parser.parse_parts(false, false)?;
+    Ok(Message { parts })
+}
+
+// Parse and format a message pattern in one go
+pub fn format(locale: &str, pattern: &str, args: &HashMap<String, Value>) -> Result<String, FormatError> {
+    parse(pattern)?.format(locale, args)
+}
+
+impl Message {
+    // Format the message with named arguments for a locale such as "ru_RU"
+    pub fn format(&self, locale: &str, args: &HashMap<String, Value>) -> Result<String, FormatError> {
+        let mut out = String::new();
+        format_parts(&self.parts, locale, args, None, &mut out)?;
+        Ok(out)
+    }
+}
+
+// Get the textual representation of a numeric argument
+fn number_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(number) if number.is_f64() => number.as_f64().map(|n| n.to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(s) if s.trim().parse::<f64>().is_ok() => Some(s.trim().to_string()),
+        _ => None,
+    }
+}
+
+fn argument<'v>(args: &'v HashMap<String, Value>, name: &str) -> Result<&'v Value, FormatError> {
+    args.get(name)
+        .ok_or_else(|| FormatError::new(format!("missing argument '{}'", name)))
+}
+
+fn format_parts(
+    parts: &[Part],
+    locale: &str,
+    args: &HashMap<String, Value>,
+    plural_value: Option<&str>,
+    out: &mut String,
+) -> Result<(), FormatError> {
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Argument(name) => match argument(args, name)? {
+                Value::String(s) => out.push_str(s),
+                value => match number_string(value) {
+                    Some(number) => out.push_str(&format_number(locale, &number)),
+                    None => out.push_str(&value.to_string()),
+                },
+            },
+            Part::Number(name) => {
+                let value = argument(args, name)?;
+                let number = number_string(value)
+                    .ok_or_else(|| FormatError::new(format!("argument '{}' is not a number", name)))?;
+                out.push_str(&format_number(locale, &number));
+            },
+            Part::Pound => {
+                if let Some(number) = plural_value {
+                    out.push_str(&format_number(locale, number));
+                }
+            },
+            Part::Plural { name, options } => {
+                let value = argument(args, name)?;
+                let number = number_string(value)
+                    .ok_or_else(|| FormatError::new(format!("argument '{}' is not a number", name)))?;
+                let operands = PluralOperands::parse(&number)
+                    .ok_or_else(|| FormatError::new(format!("argument '{}' is not a number", name)))?;
+
+                // Exact matches (=0) win over plural categories
+                let category = plural_category(locale, &operands).as_str();
+                let branch = options
+                    .iter()
+                    .find(|(selector, _)| {
+                        selector.strip_prefix('=').and_then(|n| n.parse::<f64>().ok()) == Some(operands.n)
+                    })
+                    .or_else(|| options.iter().find(|(selector, _)| selector == category))
+                    .or_else(|| options.iter().find(|(selector, _)| selector == "other"));
+
+                if let Some((_, branch)) = branch {
+                    format_parts(branch, locale, args, Some(&number), out)?;
+                }
+            },
+            Part::Select { name, options } => {
+                let key = match argument(args, name)? {
+                    Value::String(s) => s.clone(),
+                    value => value.to_string(),
+                };
+                let branch = options
+                    .iter()
+                    .find(|(selector, _)| *selector == key)
+                    .or_else(|| options.iter().find(|(selector, _)| selector == "other"));
+
+                if let Some((_, branch)) = branch {
+                    format_parts(branch, locale, args, plural_value, out)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// CLDR plural operands: n absolute value, i integer digits, v count and f value of visible fraction digits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PluralOperands {
+    pub n: f64,
+    pub i: u64,
+    pub v: usize,
+    pub f: u64,
+}
+
+impl PluralOperands {
+    // Parse operands from a decimal string, keeping visible trailing zeros ("1.0" has v = 1)
+    pub fn parse(number: &str) -> Option<Self> {
+        let number = number.trim().trim_start_matches('-');
+        let n = number.parse::<f64>().ok()?;
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+        Some(Self {
+            n,
+            i: integer.parse().unwrap_or(n.trunc() as u64),
+            v: fraction.len(),
+            f: fraction.parse().unwrap_or(0),
+        })
+    }
+}
+
+// Language part of a locale: "ru_RU" -> "ru"
+fn base_language(locale: &str) -> String {
+    locale.split(['_', '-']).next().unwrap_or(locale).to_lowercase()
+}
+
+// Select the CLDR plural category for a number in a locale
+pub fn plural_category(locale: &str, operands: &PluralOperands) -> PluralCategory {
+    use PluralCategory::*;
+
+    let PluralOperands { n, i, v, f } = *operands;
+    let i10 = i % 10;
+    let i100 = i % 100;
+    let integer = n.fract() == 0.0;
+
+    match base_language(locale).as_str() {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => Other,
+        "ru" | "uk" | "be" => {
+            if v == 0 && i10 == 1 && i100 != 11 {
+                One
+            } else if v == 0 && (2..=4).contains(&i10) && !(12..=14).contains(&i100) {
+                Few
+            } else if v == 0 && (i10 == 0 || (5..=9).contains(&i10) || (11..=14).contains(&i100)) {
+                Many
+            } else {
+                Other
+            }
+        },
+        "pl" => {
+            if i == 1 && v == 0 {
+                One
+            } else if v == 0 && (2..=4).contains(&i10) && !(12..=14).contains(&i100) {
+                Few
+            } else if v == 0 && ((i10 <= 1) || (5..=9).contains(&i10) || (12..=14).contains(&i100)) {
+                Many
+            } else {
+                Other
+            }
+        },
+        "cs" | "sk" => {
+            if i == 1 && v == 0 {
+                One
+            } else if (2..=4).contains(&i) && v == 0 {
+                Few
+            } else if v != 0 {
+                Many
+            } else {
+                Other
+            }
+        },
+        "ar" => {
+            let n100 = (n % 100.0) as u64;
+            if n == 0.0 {
+                Zero
+            } else if n == 1.0 {
+                One
+            } else if n == 2.0 {
+                Two
+            } else if integer && (3..=10).contains(&n100) {
+                Few
+            } else if integer && (11..=99).contains(&n100) {
+                Many
+            } else {
+                Other
+            }
+        },
+        "he" => {
+            if (i == 1 && v == 0) || (i == 0 && v != 0) {
+                One
+            } else if i == 2 && v == 0 {
+                Two
+            } else {
+                Other
+            }
+        },
+        "fr" | "pt" => {
+            if i <= 1 {
+                One
+            } else {
+                Other
+            }
+        },
+        "es" | "tr" | "el" | "hu" => {
+            if n == 1.0 {
+                One
+            } else {
+                Other
+            }
+        },
+        "lv" => {
+            // These rules use n, so 10.5 has n % 10 = 0.5 rather than i % 10 = 0
+            let n10 = n % 10.0;
+            let n100 = n % 100.0;
+            let n100_teen = n100.fract() == 0.0 && (11.0..=19.0).contains(&n100);
+            if n10 == 0.0 || n100_teen || (v == 2 && (11..=19).contains(&(f % 100))) {
+                Zero
+            } else if (n10 == 1.0 && n100 != 11.0) || (f % 10 == 1 && (v != 2 || f % 100 != 11)) {
+                One
+            } else {
+                Other
+            }
+        },
+        // en, de, nl, sv, it, fi and most other languages
+        _ => {
+            if i == 1 && v == 0 {
+                One
+            } else {
+                Other
+            }
+        }
+    }
+}
+
+// Group and decimal separators and minimum grouping digits for a locale
+fn number_symbols(locale: &str) -> (&'static str, &'static str, usize) {
+    match base_language(locale).as_str() {
+        "ru" | "uk" | "be" | "cs" | "sk" | "fi" | "sv" | "nb" | "no" | "lv" => ("\u{a0}", ",", 1),
+        "pl" => ("\u{a0}", ",", 2),
+        "fr" => ("\u{202f}", ",", 1),
+        "de" | "nl" | "it" | "pt" | "tr" | "id" | "da" | "el" => (".", ",", 1),
+        "es" => (".", ",", 2),
+        _ => (",", ".", 1),
+    }
+}
+
+// Format a decimal number string with locale separators, at most three fraction
+// digits. Anything else, such as the exponent form JSON uses for 1e21 and up, is
+// returned as is
+pub fn format_number(locale: &str, number: &str) -> String {
+    let (group, decimal, min_grouping) = number_symbols(locale);
+
+    let number = number.trim();
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, number),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() || !integer.bytes().chain(fraction.bytes()).all(|byte| byte.is_ascii_digit()) {
+        return number.to_string();
+    }
+
+    // Round long fractions the way ICU's default pattern does
+    let (integer, fraction) = if fraction.len() > 3 {
+        let rounded = format!("{:.3}", digits.parse::<f64>().unwrap_or(0.0));
+        let (integer, fraction) = rounded.split_once('.').unwrap_or((&rounded, ""));
+        (integer.to_string(), fraction.trim_end_matches('0').to_string())
+    } else {
+        (integer.to_string(), fraction.to_string())
+    };
+
+    let mut out = String::new();
+    if negative {
+        out.push('-');
+    }
+
+    // Only group when there are enough digits, e.g. Polish keeps "1000" but writes "10 000"
+    if integer.len() >= 4 + (min_grouping - 1) {
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                out.push_str(group);
+            }
+            out.push(digit);
+        }
+    } else {
+        out.push_str(&integer);
+    }
+
+    if !fraction.is_empty() {
+        out.push_str(decimal);
+        out.push_str(&fraction);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn category(locale: &str, number: &str) -> PluralCategory {
+        plural_category(locale, &PluralOperands::parse(number).unwrap())
+    }
+
+    fn args(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.clone())).collect()
+    }
+
+    fn parse_error(pattern: &str) -> String {
+        parse(pattern).unwrap_err().message
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse_error("Hello {name").starts_with("expected ',' or '}'"));
+        assert!(parse_error("Hello name}").starts_with("unmatched '}'"));
+        assert!(parse_error("{}").starts_with("expected a name"));
+        assert!(parse_error("{count, plural, one {# item}}").starts_with("missing 'other' option"));
+        assert!(parse_error("{count, plural, single {# item} other {# items}}").starts_with("unknown plural category 'single'"));
+        assert!(parse_error("{count, date}").starts_with("unknown argument type 'date'"));
+        assert!(parse_error("{count, plural, one {# item} other {# items}").starts_with("expected '}'"));
+        assert!(parse_error("Literal '{ brace").starts_with("unterminated quote"));
+        assert!(parse_error("Hello {name}}").contains("at position 12"));
+    }
+
+    #[test]
+    fn quoting_and_arguments() {
+        assert_eq!(format("en_US", "It''s '{literal}'", &HashMap::new()).unwrap(), "It's {literal}");
+        assert_eq!(format("en_US", "Hi {name}", &args(&[("name", json!("Steve"))])).unwrap(), "Hi Steve");
+        assert_eq!(format("de_DE", "{size, number} MB", &args(&[("size", json!(12345.5))])).unwrap(), "12.345,5 MB");
+        assert_eq!(
+            format("en_US", "Hi {name}", &HashMap::new()).unwrap_err().message,
+            "missing argument 'name'"
+        );
+        assert_eq!(
+            format("en_US", "{size, number}", &args(&[("size", json!("big"))])).unwrap_err().message,
+            "argument 'size' is not a number"
+        );
+    }
+
+    #[test]
+    fn plural_and_select() {
+        let pattern = "{count, plural, =0 {No instances} one {# instance} few {# instances} many {# instances!} other {# instances?}}";
+        let format_count = |locale: &str, count: Value| format(locale, pattern, &args(&[("count", count)])).unwrap();
+
+        assert_eq!(format_count("en_US", json!(0)), "No instances");
+        assert_eq!(format_count("en_US", json!(1)), "1 instance");
+        assert_eq!(format_count("en_US", json!(1500)), "1,500 instances?");
+        assert_eq!(format_count("ru_RU", json!(3)), "3 instances");
+        assert_eq!(format_count("ru_RU", json!(11)), "11 instances!");
+
+        let select = "{gender, select, female {She} male {He} other {They}} joined";
+        assert_eq!(format("en_US", select, &args(&[("gender", json!("female"))])).unwrap(), "She joined");
+        assert_eq!(format("en_US", select, &args(&[("gender", json!("robot"))])).unwrap(), "They joined");
+    }
+
+    #[test]
+    fn plural_rules_per_locale() {
+        use PluralCategory::*;
+
+        let cases: &[(&str, &[(&str, PluralCategory)])] = &[
+            ("en_US", &[("0", Other), ("1", One), ("1.0", Other), ("2", Other)]),
+            ("ja_JP", &[("1", Other), ("2", Other)]),
+            ("ru_RU", &[("1", One), ("21", One), ("11", Many), ("2", Few), ("22", Few), ("12", Many), ("5", Many), ("0", Many), ("1.5", Other)]),
+            ("pl_PL", &[("1", One), ("2", Few), ("12", Many), ("21", Many), ("22", Few), ("1.5", Other)]),
+            ("cs_CZ", &[("1", One), ("3", Few), ("5", Other), ("1.5", Many)]),
+            ("ar_SA", &[("0", Zero), ("1", One), ("2", Two), ("5", Few), ("103", Few), ("11", Many), ("100", Other), ("0.5", Other)]),
+            ("he_IL", &[("1", One), ("0.5", One), ("2", Two), ("3", Other)]),
+            ("fr_FR", &[("0", One), ("1.5", One), ("2", Other)]),
+            ("es_ES", &[("1", One), ("0", Other), ("2", Other)]),
+            (
+                "lv_LV",
+                &[
+                    ("0", Zero),
+                    ("10", Zero),
+                    ("11", Zero),
+                    ("19", Zero),
+                    ("30", Zero),
+                    ("1", One),
+                    ("21", One),
+                    ("101", One),
+                    ("2", Other),
+                    ("22", Other),
+                    ("10.0", Zero),
+                    ("10.5", Other),
+                    ("0.1", One),
+                    ("1.1", One),
+                    ("0.11", Zero),
+                    ("0.21", One),
+                    ("1.5", Other),
+                ],
+            ),
+        ];
+
+        for (locale, numbers) in cases {
+            for (number, expected) in numbers.iter() {
+                assert_eq!(category(locale, number), *expected, "{} in {}", number, locale);
+            }
+        }
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(format_number("en_US", "1234567.891"), "1,234,567.891");
+        assert_eq!(format_number("ru_RU", "-1234.5"), "-1\u{a0}234,5");
+        assert_eq!(format_number("pl_PL", "1000"), "1000");
+        assert_eq!(format_number("pl_PL", "10000"), "10\u{a0}000");
+        assert_eq!(format_number("en_US", "2.34567"), "2.346");
+        assert_eq!(format_number("en_US", "1e21"), "1e21");
+        assert_eq!(format_number("de_DE", "-1.5e-7"), "-1.5e-7");
+        assert_eq!(format_number("en_US", "1.2.3"), "1.2.3");
+        assert_eq!(format_number("en_US", "NaN"), "NaN");
+    }
+}
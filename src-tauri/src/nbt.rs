@@ -0,0 +1,350 @@
+// Minimal NBT (Named Binary Tag) reader and writer, enough for level.dat.
+// Files are big-endian and gzipped; compound order is kept so a rewritten
+// file only differs in the values that were changed
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+type NbtResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// Nesting allowed before a file is considered corrupt
+const MAX_DEPTH: usize = 512;
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    // Element type is kept so empty lists round-trip
+    List(u8, Vec<Tag>),
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => TAG_BYTE,
+            Tag::Short(_) => TAG_SHORT,
+            Tag::Int(_) => TAG_INT,
+            Tag::Long(_) => TAG_LONG,
+            Tag::Float(_) => TAG_FLOAT,
+            Tag::Double(_) => TAG_DOUBLE,
+            Tag::ByteArray(_) => TAG_BYTE_ARRAY,
+            Tag::String(_) => TAG_STRING,
+            Tag::List(..) => TAG_LIST,
+            Tag::Compound(_) => TAG_COMPOUND,
+            Tag::IntArray(_) => TAG_INT_ARRAY,
+            Tag::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    // Child of a compound
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, tag)| tag),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Tag> {
+        match self {
+            Tag::Compound(entries) => entries.iter_mut().find(|(name, _)| name == key).map(|(_, tag)| tag),
+            _ => None,
+        }
+    }
+
+    // Follow a path of compound keys
+    pub fn path(&self, keys: &[&str]) -> Option<&Tag> {
+        keys.iter().try_fold(self, |tag, key| tag.get(key))
+    }
+
+    // Any integer tag widened to i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(value) => Some(*value as i64),
+            Tag::Short(value) => Some(*value as i64),
+            Tag::Int(value) => Some(*value as i64),
+            Tag::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+fn read_exact<const N: usize>(reader: &mut impl Read) -> NbtResult<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_length(reader: &mut impl Read) -> NbtResult<usize> {
+    let length = i32::from_be_bytes(read_exact(reader)?);
+    usize::try_from(length).map_err(|_| format!("Negative length {}", length).into())
+}
+
+// Decode Java's modified UTF-8: NUL is the two bytes C0 80, and characters
+// outside the BMP are a surrogate pair with each half encoded as three bytes
+fn decode_modified_utf8(bytes: &[u8]) -> NbtResult<String> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    let continuation = |position: usize| match bytes.get(position) {
+        Some(byte) if byte & 0xC0 == 0x80 => Ok((byte & 0x3F) as u16),
+        _ => Err("Malformed modified UTF-8 in NBT string"),
+    };
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+        let (unit, width) = match byte {
+            0x01..=0x7F => (byte as u16, 1),
+            0xC0..=0xDF => (((byte & 0x1F) as u16) << 6 | continuation(index + 1)?, 2),
+            0xE0..=0xEF => (((byte & 0x0F) as u16) << 12 | continuation(index + 1)? << 6 | continuation(index + 2)?, 3),
+            _ => return Err("Malformed modified UTF-8 in NBT string".into()),
+        };
+        units.push(unit);
+        index += width;
+    }
+
+    // Java strings can hold unpaired surrogates, Rust strings can't
+    Ok(String::from_utf16_lossy(&units))
+}
+
+fn encode_modified_utf8(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007F => bytes.push(unit as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                bytes.push(0xC0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            },
+            _ => {
+                bytes.push(0xE0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+        }
+    }
+    bytes
+}
+
+fn read_string(reader: &mut impl Read) -> NbtResult<String> {
+    let length = u16::from_be_bytes(read_exact(reader)?) as usize;
+    let mut bytes = vec![0u8; length];
+    reader.read_exact(&mut bytes)?;
+    decode_modified_utf8(&bytes)
+}
+
+// Read `length` fixed-size elements without trusting the length for the allocation
+fn read_array<T, const N: usize>(reader: &mut impl Read, convert: fn([u8; N]) -> T) -> NbtResult<Vec<T>> {
+    let length = read_length(reader)?;
+    let mut values = Vec::with_capacity(length.min(4096));
+    for _ in 0..length {
+        values.push(convert(read_exact(reader)?));
+    }
+    Ok(values)
+}
+
+fn read_payload(reader: &mut impl Read, id: u8, depth: usize) -> NbtResult<Tag> {
+    if depth > MAX_DEPTH {
+        return Err("NBT is nested too deeply".into());
+    }
+
+    Ok(match id {
+        TAG_BYTE => Tag::Byte(i8::from_be_bytes(read_exact(reader)?)),
+        TAG_SHORT => Tag::Short(i16::from_be_bytes(read_exact(reader)?)),
+        TAG_INT => Tag::Int(i32::from_be_bytes(read_exact(reader)?)),
+        TAG_LONG => Tag::Long(i64::from_be_bytes(read_exact(reader)?)),
+        TAG_FLOAT => Tag::Float(f32::from_be_bytes(read_exact(reader)?)),
+        TAG_DOUBLE => Tag::Double(f64::from_be_bytes(read_exact(reader)?)),
+        TAG_BYTE_ARRAY => Tag::ByteArray(read_array(reader, i8::from_be_bytes)?),
+        TAG_STRING => Tag::String(read_string(reader)?),
+        TAG_LIST => {
+            let [element] = read_exact(reader)?;
+            let length = read_length(reader)?;
+            let mut items = Vec::with_capacity(length.min(4096));
+            for _ in 0..length {
+                items.push(read_payload(reader, element, depth + 1)?);
+            }
+            Tag::List(element, items)
+        },
+        TAG_COMPOUND => {
+            let mut entries = Vec::new();
+            loop {
+                let [child] = read_exact(reader)?;
+                if child == TAG_END {
+                    break;
+                }
+                let name = read_string(reader)?;
+                entries.push((name, read_payload(reader, child, depth + 1)?));
+            }
+            Tag::Compound(entries)
+        },
+        TAG_INT_ARRAY => Tag::IntArray(read_array(reader, i32::from_be_bytes)?),
+        TAG_LONG_ARRAY => Tag::LongArray(read_array(reader, i64::from_be_bytes)?),
+        other => return Err(format!("Unknown NBT tag type {}", other).into()),
+    })
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> NbtResult<()> {
+    let bytes = encode_modified_utf8(value);
+    let length = u16::try_from(bytes.len()).map_err(|_| "NBT string is too long")?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+fn write_length(writer: &mut impl Write, length: usize) -> NbtResult<()> {
+    let length = i32::try_from(length).map_err(|_| "NBT array is too long")?;
+    writer.write_all(&length.to_be_bytes())?;
+    Ok(())
+}
+
+fn write_payload(writer: &mut impl Write, tag: &Tag) -> NbtResult<()> {
+    match tag {
+        Tag::Byte(value) => writer.write_all(&value.to_be_bytes())?,
+        Tag::Short(value) => writer.write_all(&value.to_be_bytes())?,
+        Tag::Int(value) => writer.write_all(&value.to_be_bytes())?,
+        Tag::Long(value) => writer.write_all(&value.to_be_bytes())?,
+        Tag::Float(value) => writer.write_all(&value.to_be_bytes())?,
+        Tag::Double(value) => writer.write_all(&value.to_be_bytes())?,
+        Tag::ByteArray(values) => {
+            write_length(writer, values.len())?;
+            for value in values {
+                writer.write_all(&value.to_be_bytes())?;
+            }
+        },
+        Tag::String(value) => write_string(writer, value)?,
+        Tag::List(element, items) => {
+            writer.write_all(&[*element])?;
+            write_length(writer, items.len())?;
+            for item in items {
+                write_payload(writer, item)?;
+            }
+        },
+        Tag::Compound(entries) => {
+            for (name, child) in entries {
+                writer.write_all(&[child.id()])?;
+                write_string(writer, name)?;
+                write_payload(writer, child)?;
+            }
+            writer.write_all(&[TAG_END])?;
+        },
+        Tag::IntArray(values) => {
+            write_length(writer, values.len())?;
+            for value in values {
+                writer.write_all(&value.to_be_bytes())?;
+            }
+        },
+        Tag::LongArray(values) => {
+            write_length(writer, values.len())?;
+            for value in values {
+                writer.write_all(&value.to_be_bytes())?;
+            }
+        },
+    }
+    Ok(())
+}
+
+// Read a gzipped NBT file, returning the root name and tag
+pub fn read_gzip(path: &Path) -> NbtResult<(String, Tag)> {
+    let mut reader = BufReader::new(GzDecoder::new(File::open(path)?));
+    let [id] = read_exact(&mut reader)?;
+    if id != TAG_COMPOUND {
+        return Err(format!("{:?} does not start with a compound tag", path).into());
+    }
+    let name = read_string(&mut reader)?;
+    Ok((name, read_payload(&mut reader, id, 0)?))
+}
+
+// Write a gzipped NBT file through a temporary file so a failed write keeps the old one
+pub fn write_gzip(path: &Path, name: &str, root: &Tag) -> NbtResult<()> {
+    let tmp_path = path.with_extension("dat.tmp");
+    {
+        let mut writer = BufWriter::new(GzEncoder::new(File::create(&tmp_path)?, Compression::default()));
+        writer.write_all(&[root.id()])?;
+        write_string(&mut writer, name)?;
+        write_payload(&mut writer, root)?;
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .finish()?
+            .sync_all()?;
+    }
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_nul_and_supplementary_characters_like_java() {
+        assert_eq!(encode_modified_utf8("a\0b"), [b'a', 0xC0, 0x80, b'b']);
+        // U+1F600 is the surrogate pair D83D DE00, three bytes each
+        assert_eq!(encode_modified_utf8("😀"), [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+        assert_eq!(encode_modified_utf8("é€"), [0xC3, 0xA9, 0xE2, 0x82, 0xAC]);
+    }
+
+    #[test]
+    fn decodes_what_java_writes() {
+        assert_eq!(decode_modified_utf8(&[b'a', 0xC0, 0x80, b'b']).unwrap(), "a\0b");
+        assert_eq!(decode_modified_utf8(&[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]).unwrap(), "😀");
+        assert!(decode_modified_utf8(&[0xE2, 0x82]).is_err());
+        assert!(decode_modified_utf8(&[0xF0, 0x9F, 0x98, 0x80]).is_err());
+    }
+
+    #[test]
+    fn round_trips_strings_through_a_file() {
+        let name = "World \0 with 😀 and ünïcode";
+        let root = Tag::Compound(vec![(
+            "Data".to_string(),
+            Tag::Compound(vec![
+                ("LevelName".to_string(), Tag::String(name.to_string())),
+                ("Tags".to_string(), Tag::List(TAG_STRING, vec![Tag::String("🎮\0".to_string())])),
+            ]),
+        )]);
+
+        let dir = std::env::temp_dir().join(format!("nbt-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("level.dat");
+        write_gzip(&path, "", &root).unwrap();
+        let (root_name, read) = read_gzip(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(root_name, "");
+        assert_eq!(read, root);
+        assert_eq!(read.path(&["Data", "LevelName"]).and_then(Tag::as_str), Some(name));
+    }
+}
@@ -0,0 +1,398 @@
+// Resource packs and shader packs of an instance. Resource packs are zips or
+// folders in <game dir>/resourcepacks described by pack.mcmeta, and the enabled
+// ones are the `resourcePacks` list in options.txt. Shader packs live in
+// <game dir>/shaderpacks and are picked in Iris, Oculus or OptiFine settings
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::instances::Instance;
+use crate::saves;
+
+type PacksResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+const OPTIONS: &str = "options.txt";
+const RESOURCE_PACKS_KEY: &str = "resourcePacks";
+const INCOMPATIBLE_PACKS_KEY: &str = "incompatibleResourcePacks";
+
+// pack.mcmeta and pack.png bigger than this are ignored
+const MAX_PACK_FILE_SIZE: u64 = 512 * 1024;
+
+// How a pack's format compares to the instance's game version, named like the game does
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Compatibility {
+    Compatible,
+    // Made for an older game version
+    TooOld,
+    // Made for a newer game version
+    TooNew,
+    // The pack or the game version has no readable format
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ResourcePack {
+    // Id used in options.txt, `file/<file>`
+    pub id: String,
+    pub file: String,
+    pub description: Option<String>,
+    pub pack_format: Option<i64>,
+    // Range of formats the pack declares support for, inclusive
+    pub supported_formats: Option<(i64, i64)>,
+    pub compatibility: Compatibility,
+    // pack.png as a data URL
+    pub icon: Option<String>,
+    pub enabled: bool,
+    // Size in bytes, of the zip or the folder
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ShaderPack {
+    pub file: String,
+    pub size: u64,
+    // Selected in the shader mod's settings
+    pub enabled: bool,
+}
+
+fn resource_packs_dir(data_path: &Path, instance: &Instance) -> PathBuf {
+    instance.game_dir(data_path).join("resourcepacks")
+}
+
+fn shader_packs_dir(data_path: &Path, instance: &Instance) -> PathBuf {
+    instance.game_dir(data_path).join("shaderpacks")
+}
+
+// Read a file from the root of a zip pack or a folder pack
+fn read_pack_file(path: &Path, name: &str, limit: u64) -> Option<Vec<u8>> {
+    let mut contents = Vec::new();
+    if path.is_dir() {
+        File::open(path.join(name)).ok()?.take(limit).read_to_end(&mut contents).ok()?;
+    } else {
+        let mut archive = zip::ZipArchive::new(File::open(path).ok()?).ok()?;
+        let file = archive.by_name(name).ok()?;
+        if file.size() > limit {
+            return None;
+        }
+        file.take(limit).read_to_end(&mut contents).ok()?;
+    }
+    Some(contents)
+}
+
+// Plain text of a chat component: a string, a list of components, or an
+// object with `text` and `extra`. Formatting codes are dropped
+fn component_text(value: &Value) -> String {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts.iter().map(component_text).collect(),
+        Value::Object(fields) => {
+            let mut text = fields.get("text").map(component_text).unwrap_or_default();
+            if let Some(extra) = fields.get("extra") {
+                text.push_str(&component_text(extra));
+            }
+            text
+        },
+        Value::Number(number) => number.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Null => String::new(),
+    };
+
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+// Major version of a format, a number or [major, minor] since 1.21.9
+fn format_major(value: &Value) -> Option<i64> {
+    value.as_i64().or_else(|| value.get(0).and_then(Value::as_i64))
+}
+
+// Format range from `supported_formats` (a number, [min, max] or
+// {min_inclusive, max_inclusive}), `min_format`/`max_format`, or `pack_format`
+fn supported_formats(pack: &Value, pack_format: Option<i64>) -> Option<(i64, i64)> {
+    let supported = &pack["supported_formats"];
+    if let Some(format) = supported.as_i64() {
+        return Some((format, format));
+    }
+    if let (Some(min), Some(max)) = (supported[0].as_i64(), supported[1].as_i64()) {
+        return Some((min, max));
+    }
+    if let (Some(min), Some(max)) = (supported["min_inclusive"].as_i64(), supported["max_inclusive"].as_i64()) {
+        return Some((min, max));
+    }
+    if let (Some(min), Some(max)) = (format_major(&pack["min_format"]), format_major(&pack["max_format"])) {
+        return Some((min, max));
+    }
+    pack_format.map(|format| (format, format))
+}
+
+fn compatibility(supported: Option<(i64, i64)>, game_format: Option<i64>) -> Compatibility {
+    match (supported, game_format) {
+        (Some((min, _)), Some(game)) if game < min => Compatibility::TooNew,
+        (Some((_, max)), Some(game)) if game > max => Compatibility::TooOld,
+        (Some(_), Some(_)) => Compatibility::Compatible,
+        _ => Compatibility::Unknown,
+    }
+}
+
+fn read_resource_pack(path: &Path, game_format: Option<i64>, enabled: bool) -> ResourcePack {
+    let file = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mcmeta: Option<Value> = read_pack_file(path, "pack.mcmeta", MAX_PACK_FILE_SIZE)
+        .and_then(|contents| serde_json::from_slice(&contents).ok());
+    let pack = mcmeta.as_ref().map(|mcmeta| &mcmeta["pack"]).unwrap_or(&Value::Null);
+
+    let pack_format = pack["pack_format"].as_i64();
+    let supported_formats = supported_formats(pack, pack_format);
+    let icon = read_pack_file(path, "pack.png", MAX_PACK_FILE_SIZE)
+        .map(|bytes| format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(bytes)));
+
+    ResourcePack {
+        id: format!("file/{}", file),
+        description: pack.get("description").map(component_text).filter(|text| !text.trim().is_empty()),
+        pack_format,
+        compatibility: compatibility(supported_formats, game_format),
+        supported_formats,
+        icon,
+        enabled,
+        size: if path.is_dir() { saves::dir_size(path) } else { fs::metadata(path).map(|metadata| metadata.len()).unwrap_or_default() },
+        file,
+    }
+}
+
+// Lines of options.txt, empty when the game hasn't written it yet
+fn read_options(data_path: &Path, instance: &Instance) -> PacksResult<Vec<String>> {
+    match fs::read_to_string(instance.game_dir(data_path).join(OPTIONS)) {
+        Ok(contents) => Ok(contents.lines().map(String::from).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// A JSON list option such as resourcePacks:["vanilla","file/Faithful.zip"]
+fn list_option(lines: &[String], key: &str) -> Vec<String> {
+    lines
+        .iter()
+        .find_map(|line| line.strip_prefix(key).and_then(|rest| rest.strip_prefix(':')))
+        .and_then(|value| serde_json::from_str(value).ok())
+        .unwrap_or_default()
+}
+
+fn set_list_option(lines: &mut Vec<String>, key: &str, values: &[String]) -> PacksResult<()> {
+    let line = format!("{}:{}", key, serde_json::to_string(values)?);
+    let prefix = format!("{}:", key);
+    match lines.iter_mut().find(|existing| existing.starts_with(&prefix)) {
+        Some(existing) => *existing = line,
+        None => lines.push(line),
+    }
+    Ok(())
+}
+
+// Resource packs of an instance, enabled ones first from the highest priority
+// down as in the game's pack screen, then the rest by name
+pub fn list_resource_packs(data_path: &Path, instance: &Instance, game_format: Option<i64>) -> PacksResult<Vec<ResourcePack>> {
+    // options.txt lists packs from the lowest priority up
+    let enabled: Vec<String> = list_option(&read_options(data_path, instance)?, RESOURCE_PACKS_KEY).into_iter().rev().collect();
+
+    let mut packs = Vec::new();
+    let Ok(entries) = fs::read_dir(resource_packs_dir(data_path, instance)) else { return Ok(packs) };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_zip = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        if !(is_zip || path.join("pack.mcmeta").is_file()) {
+            continue;
+        }
+        let id = format!("file/{}", entry.file_name().to_string_lossy());
+        packs.push(read_resource_pack(&path, game_format, enabled.contains(&id)));
+    }
+
+    let position = |pack: &ResourcePack| enabled.iter().position(|id| *id == pack.id).unwrap_or(usize::MAX);
+    packs.sort_by(|a, b| position(a).cmp(&position(b)).then_with(|| a.file.to_lowercase().cmp(&b.file.to_lowercase())));
+    Ok(packs)
+}
+
+// Enable resource packs in the given order, highest priority first. Built-in
+// packs already in options.txt that aren't listed stay enabled below them.
+// Packs made for another format are also added to incompatibleResourcePacks,
+// otherwise the game drops them from the list on startup
+pub fn set_resource_packs(
+    data_path: &Path,
+    instance: &Instance,
+    game_format: Option<i64>,
+    enabled: &[String],
+) -> PacksResult<Vec<ResourcePack>> {
+    let available = list_resource_packs(data_path, instance, game_format)?;
+    let mut seen = HashSet::new();
+    for id in enabled {
+        if !seen.insert(id) {
+            return Err(format!("Resource pack {} is listed twice", id).into());
+        }
+        if id.starts_with("file/") && !available.iter().any(|pack| pack.id == *id) {
+            return Err(format!("Resource pack {} not found", id).into());
+        }
+    }
+
+    let mut lines = read_options(data_path, instance)?;
+    let mut resource_packs: Vec<String> = list_option(&lines, RESOURCE_PACKS_KEY)
+        .into_iter()
+        .filter(|id| !id.starts_with("file/") && !enabled.contains(id))
+        .collect();
+    resource_packs.extend(enabled.iter().rev().cloned());
+
+    // Packs the player already confirmed in game stay confirmed while enabled
+    let mut incompatible: Vec<String> = list_option(&lines, INCOMPATIBLE_PACKS_KEY)
+        .into_iter()
+        .filter(|id| resource_packs.contains(id))
+        .collect();
+    for pack in &available {
+        let mismatched = matches!(pack.compatibility, Compatibility::TooOld | Compatibility::TooNew);
+        if mismatched && enabled.contains(&pack.id) && !incompatible.contains(&pack.id) {
+            incompatible.push(pack.id.clone());
+        }
+    }
+
+    set_list_option(&mut lines, RESOURCE_PACKS_KEY, &resource_packs)?;
+    set_list_option(&mut lines, INCOMPATIBLE_PACKS_KEY, &incompatible)?;
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    fs::create_dir_all(instance.game_dir(data_path))?;
+    fs::write(instance.game_dir(data_path).join(OPTIONS), contents)?;
+
+    list_resource_packs(data_path, instance, game_format)
+}
+
+// `shaderPack=` from the shader mod settings: Iris, Oculus, then OptiFine
+fn selected_shader_pack(game_dir: &Path) -> Option<String> {
+    let configs = [
+        game_dir.join("config").join("iris.properties"),
+        game_dir.join("config").join("oculus.properties"),
+        game_dir.join("optionsshaders.txt"),
+    ];
+    configs.iter().find_map(|path| {
+        let contents = fs::read_to_string(path).ok()?;
+        let value = |key: &str| {
+            contents
+                .lines()
+                .find_map(|line| line.strip_prefix(key).and_then(|rest| rest.strip_prefix('=')))
+                .map(|value| value.trim().to_string())
+        };
+        if value("enableShaders").is_some_and(|enabled| enabled == "false") {
+            return None;
+        }
+        value("shaderPack").filter(|pack| !pack.is_empty() && pack != "OFF" && pack != "(internal)")
+    })
+}
+
+// Shader packs of an instance by name
+pub fn list_shader_packs(data_path: &Path, instance: &Instance) -> PacksResult<Vec<ShaderPack>> {
+    let selected = selected_shader_pack(&instance.game_dir(data_path));
+
+    let mut packs = Vec::new();
+    let Ok(entries) = fs::read_dir(shader_packs_dir(data_path, instance)) else { return Ok(packs) };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_dir = path.is_dir();
+        if !is_dir && !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip")) {
+            continue;
+        }
+        let file = entry.file_name().to_string_lossy().to_string();
+        packs.push(ShaderPack {
+            enabled: selected.as_deref() == Some(file.as_str()),
+            size: if is_dir { saves::dir_size(&path) } else { entry.metadata().map(|metadata| metadata.len()).unwrap_or_default() },
+            file,
+        });
+    }
+
+    packs.sort_by_key(|pack| pack.file.to_lowercase());
+    Ok(packs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn supported_formats_shapes() {
+        assert_eq!(supported_formats(&json!({ "supported_formats": 34 }), Some(15)), Some((34, 34)));
+        assert_eq!(supported_formats(&json!({ "supported_formats": [18, 34] }), Some(15)), Some((18, 34)));
+        assert_eq!(
+            supported_formats(&json!({ "supported_formats": { "min_inclusive": 18, "max_inclusive": 34 } }), Some(15)),
+            Some((18, 34))
+        );
+        assert_eq!(supported_formats(&json!({ "min_format": [65, 2], "max_format": 69 }), None), Some((65, 69)));
+        assert_eq!(supported_formats(&json!({ "pack_format": 15 }), Some(15)), Some((15, 15)));
+        assert_eq!(supported_formats(&json!({ "supported_formats": "34" }), None), None);
+    }
+
+    #[test]
+    fn compatibility_against_the_game_format() {
+        assert_eq!(compatibility(Some((18, 34)), Some(18)), Compatibility::Compatible);
+        assert_eq!(compatibility(Some((18, 34)), Some(34)), Compatibility::Compatible);
+        assert_eq!(compatibility(Some((18, 34)), Some(15)), Compatibility::TooNew);
+        assert_eq!(compatibility(Some((18, 34)), Some(46)), Compatibility::TooOld);
+        assert_eq!(compatibility(None, Some(34)), Compatibility::Unknown);
+        assert_eq!(compatibility(Some((18, 34)), None), Compatibility::Unknown);
+    }
+
+    #[test]
+    fn list_options_are_read_and_replaced() {
+        let mut options = lines(&["version:3955", r#"resourcePacks:["vanilla","file/Faithful.zip"]"#, "incompatibleResourcePacks:broken"]);
+        assert_eq!(list_option(&options, RESOURCE_PACKS_KEY), ["vanilla", "file/Faithful.zip"]);
+        assert!(list_option(&options, INCOMPATIBLE_PACKS_KEY).is_empty());
+        assert!(list_option(&options, "resource").is_empty());
+
+        set_list_option(&mut options, RESOURCE_PACKS_KEY, &lines(&["vanilla"])).unwrap();
+        set_list_option(&mut options, "lang", &lines(&["en_us"])).unwrap();
+        assert_eq!(options, ["version:3955", r#"resourcePacks:["vanilla"]"#, "incompatibleResourcePacks:broken", r#"lang:["en_us"]"#]);
+    }
+
+    #[test]
+    fn resource_packs_are_enabled_in_order() {
+        let data_path = std::env::temp_dir().join(format!("intelligence-packs-{}", std::process::id()));
+        let instance: Instance = serde_json::from_value(json!({ "id": "test", "name": "Test", "game_version": "1.21.1" })).unwrap();
+        let packs_dir = resource_packs_dir(&data_path, &instance);
+        for (name, format) in [("Alpha", 34), ("Beta", 34), ("Classic", 3)] {
+            fs::create_dir_all(packs_dir.join(name)).unwrap();
+            let mcmeta = json!({ "pack": { "pack_format": format, "description": [{ "text": "§6" }, name] } });
+            fs::write(packs_dir.join(name).join("pack.mcmeta"), mcmeta.to_string()).unwrap();
+        }
+        let options_path = instance.game_dir(&data_path).join(OPTIONS);
+        fs::write(&options_path, "resourcePacks:[\"vanilla\",\"fabric\",\"file/Alpha\"]\nincompatibleResourcePacks:[\"file/Alpha\"]\n").unwrap();
+
+        let order = |packs: &[ResourcePack]| packs.iter().map(|pack| (pack.file.clone(), pack.enabled)).collect::<Vec<_>>();
+        let packs = list_resource_packs(&data_path, &instance, Some(34)).unwrap();
+        assert_eq!(order(&packs), [("Alpha".to_string(), true), ("Beta".to_string(), false), ("Classic".to_string(), false)]);
+        assert_eq!(packs[1].description.as_deref(), Some("Beta"));
+        assert_eq!(packs[2].compatibility, Compatibility::TooOld);
+
+        let enabled = lines(&["file/Classic", "fabric", "file/Beta"]);
+        let packs = set_resource_packs(&data_path, &instance, Some(34), &enabled).unwrap();
+        assert_eq!(order(&packs), [("Classic".to_string(), true), ("Beta".to_string(), true), ("Alpha".to_string(), false)]);
+        let options = lines(&fs::read_to_string(&options_path).unwrap().lines().collect::<Vec<_>>());
+        assert_eq!(list_option(&options, RESOURCE_PACKS_KEY), ["vanilla", "file/Beta", "fabric", "file/Classic"]);
+        assert_eq!(list_option(&options, INCOMPATIBLE_PACKS_KEY), ["file/Classic"]);
+
+        let twice = set_resource_packs(&data_path, &instance, Some(34), &lines(&["file/Beta", "file/Beta"]));
+        assert!(twice.unwrap_err().to_string().contains("listed twice"));
+        let missing = set_resource_packs(&data_path, &instance, Some(34), &lines(&["file/Missing"]));
+        assert!(missing.unwrap_err().to_string().contains("not found"));
+        fs::remove_dir_all(data_path).unwrap();
+    }
+}
@@ -0,0 +1,303 @@
+// Resolves where the launcher keeps its configuration, game data and cache
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+// Marker file next to the executable that enables portable mode
+pub const PORTABLE_MARKER: &str = "portable.txt";
+
+// Environment variable overriding the whole app directory
+pub const HOME_ENV: &str = "INTELLIGENCE_HOME";
+
+// Name of the app directory inside XDG and platform base directories
+const APP_DIR_NAME: &str = "intelligence-app";
+
+// Top-level entries of the data directory that hold game data. Moving the data
+// directory only moves these, config files sharing the root stay where they are
+pub const GAME_DATA_DIRS: &[&str] = &["instances", "versions", "libraries", "assets", "backups", "logs"];
+
+// Where the directory layout came from
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DirsSource {
+    Portable,
+    Env,
+    Xdg,
+    Default,
+}
+
+// Resolved app directories, config holds settings.json and languages
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AppDirs {
+    pub source: DirsSource,
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub cache: PathBuf,
+}
+
+impl AppDirs {
+    // Keep config and data in one root directory
+    fn single_root(source: DirsSource, root: PathBuf) -> Self {
+        Self {
+            source,
+            config: root.clone(),
+            cache: root.join("cache"),
+            data: root,
+        }
+    }
+}
+
+// Resolve app directories for the current process
+pub fn resolve() -> Result<AppDirs, Box<dyn Error>> {
+    let exe_dir = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    resolve_with(
+        |key| env::var(key).ok(),
+        exe_dir.as_deref(),
+        dirs::home_dir(),
+        dirs::config_dir(),
+    )
+}
+
+// Resolve app directories from explicit inputs, in order of precedence:
+// portable marker, INTELLIGENCE_HOME, XDG base directories unless an older
+// launcher left its data in ~/.intelligence-app on Linux, platform default
+pub fn resolve_with(
+    env: impl Fn(&str) -> Option<String>,
+    exe_dir: Option<&Path>,
+    home: Option<PathBuf>,
+    config_dir: Option<PathBuf>,
+) -> Result<AppDirs, Box<dyn Error>> {
+    // Portable: data lives next to the executable
+    if let Some(exe_dir) = exe_dir {
+        if exe_dir.join(PORTABLE_MARKER).exists() {
+            return Ok(AppDirs::single_root(DirsSource::Portable, exe_dir.join("data")));
+        }
+    }
+
+    if let Some(root) = env(HOME_ENV).filter(|value| !value.is_empty()) {
+        return Ok(AppDirs::single_root(DirsSource::Env, PathBuf::from(root)));
+    }
+
+    if cfg!(target_os = "linux") {
+        let home = home.ok_or("Could not find home directory")?;
+        let legacy = home.join(".intelligence-app");
+
+        // The spec requires absolute paths, relative values are ignored
+        let xdg = |key: &str| {
+            env(key)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        let config = xdg("XDG_CONFIG_HOME");
+        let data = xdg("XDG_DATA_HOME");
+        let cache = xdg("XDG_CACHE_HOME");
+
+        if config.is_none() && data.is_none() && cache.is_none() {
+            // Linux: ~/.intelligence-app
+            return Ok(AppDirs::single_root(DirsSource::Default, legacy));
+        }
+
+        let dirs = AppDirs {
+            source: DirsSource::Xdg,
+            config: config.unwrap_or_else(|| home.join(".config")).join(APP_DIR_NAME),
+            data: data.unwrap_or_else(|| home.join(".local/share")).join(APP_DIR_NAME),
+            cache: cache.unwrap_or_else(|| home.join(".cache")).join(APP_DIR_NAME),
+        };
+
+        // Launchers from before XDG support kept everything in ~/.intelligence-app.
+        // Keep using it while it holds settings and the XDG directories were never
+        // created, so instances don't disappear when a desktop starts exporting
+        // XDG variables
+        let legacy_in_use = legacy.join("settings.json").is_file() && !dirs.config.exists() && !dirs.data.exists();
+        if legacy_in_use {
+            return Ok(AppDirs::single_root(DirsSource::Default, legacy));
+        }
+
+        Ok(dirs)
+    } else if cfg!(target_os = "macos") {
+        // macOS: ~/Library/Application Support/intelligence-app
+        let home = home.ok_or("Could not find home directory")?;
+        Ok(AppDirs::single_root(
+            DirsSource::Default,
+            home.join("Library/Application Support").join(APP_DIR_NAME),
+        ))
+    } else {
+        // Windows or fallback
+        let config_dir = config_dir.ok_or("Could not find config directory")?;
+        Ok(AppDirs::single_root(DirsSource::Default, config_dir.join(APP_DIR_NAME)))
+    }
+}
+
+// Recursively copy a directory
+fn copy_dir_all(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Remove files and directories, carrying on past failures and returning the last one
+pub fn remove_all(paths: &[PathBuf]) -> io::Result<()> {
+    let mut result = Ok(());
+    for path in paths {
+        let removed = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+        if let Err(e) = removed {
+            if e.kind() != io::ErrorKind::NotFound {
+                result = Err(e);
+            }
+        }
+    }
+    result
+}
+
+// Copy the game data in a data directory to a new location, see GAME_DATA_DIRS.
+// Returns the copied source entries. Nothing is left in `to` when copying fails
+// part way
+pub fn copy_game_data(from: &Path, to: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(to)?;
+
+    let mut copied = Vec::new();
+    let mut targets = Vec::new();
+    let result = GAME_DATA_DIRS.iter().try_for_each(|name| {
+        let source = from.join(name);
+        if !source.is_dir() {
+            return Ok(());
+        }
+
+        let target = to.join(name);
+        targets.push(target.clone());
+        copy_dir_all(&source, &target)?;
+        copied.push(source);
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        if let Err(cleanup) = remove_all(&targets) {
+            eprintln!("Failed to clean up partial copy in {:?}: {}", to, cleanup);
+        }
+        return Err(e);
+    }
+    Ok(copied)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    fn temp_home(name: &str) -> PathBuf {
+        let home = env::temp_dir().join(format!("intelligence-paths-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        home
+    }
+
+    // XDG config and data homes inside the temp home
+    fn xdg_env(home: &Path) -> impl Fn(&str) -> Option<String> {
+        let config = home.join("xdg/config");
+        let data = home.join("xdg/data");
+        move |key| match key {
+            "XDG_CONFIG_HOME" => Some(config.to_string_lossy().into_owned()),
+            "XDG_DATA_HOME" => Some(data.to_string_lossy().into_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn legacy_directory_with_settings_wins_over_new_xdg() {
+        let home = temp_home("legacy");
+        fs::create_dir(home.join(".intelligence-app")).unwrap();
+        fs::write(home.join(".intelligence-app/settings.json"), "{}").unwrap();
+
+        let dirs = resolve_with(xdg_env(&home), None, Some(home.clone()), None).unwrap();
+        assert_eq!(dirs, AppDirs::single_root(DirsSource::Default, home.join(".intelligence-app")));
+
+        // Once the XDG directories exist they are used
+        fs::create_dir_all(home.join("xdg/config/intelligence-app")).unwrap();
+        let dirs = resolve_with(xdg_env(&home), None, Some(home.clone()), None).unwrap();
+        assert_eq!(dirs.source, DirsSource::Xdg);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn empty_legacy_directory_does_not_hide_xdg() {
+        let home = temp_home("empty-legacy");
+        fs::create_dir(home.join(".intelligence-app")).unwrap();
+
+        let dirs = resolve_with(xdg_env(&home), None, Some(home.clone()), None).unwrap();
+        assert_eq!(dirs.source, DirsSource::Xdg);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn xdg_without_legacy_directory() {
+        let home = temp_home("xdg");
+
+        let dirs = resolve_with(xdg_env(&home), None, Some(home.clone()), None).unwrap();
+        assert_eq!(dirs.source, DirsSource::Xdg);
+        assert_eq!(dirs.config, home.join("xdg/config/intelligence-app"));
+        assert_eq!(dirs.data, home.join("xdg/data/intelligence-app"));
+        assert_eq!(dirs.cache, home.join(".cache/intelligence-app"));
+        assert!(!home.join(".intelligence-app").exists());
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn no_xdg_uses_home_directory() {
+        let home = temp_home("default");
+
+        let dirs = resolve_with(|_| None, None, Some(home.clone()), None).unwrap();
+        assert_eq!(dirs, AppDirs::single_root(DirsSource::Default, home.join(".intelligence-app")));
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn migration_moves_only_game_data() {
+        let root = temp_home("migrate");
+        let from = root.join("from");
+        let config_files = [
+            "settings.json",
+            "settings.json.bak",
+            "language-packs.json",
+            "instance.lock",
+            "instance.owner",
+            "approved_hooks.json",
+            "languages/de_DE.json",
+            "cache/manifest.json",
+        ];
+        for file in config_files.iter().chain(&["instances/a/instance.json", "versions/1.20.1/1.20.1.json", "backups/a/World/1.json"]) {
+            let path = from.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "{}").unwrap();
+        }
+
+        let to = root.join("to");
+        let copied = copy_game_data(&from, &to).unwrap();
+        assert_eq!(copied, vec![from.join("instances"), from.join("versions"), from.join("backups")]);
+        remove_all(&copied).unwrap();
+
+        for file in config_files {
+            assert!(from.join(file).is_file(), "{} was moved", file);
+            assert!(!to.join(file).exists(), "{} was copied", file);
+        }
+        assert!(to.join("instances/a/instance.json").is_file());
+        assert!(to.join("versions/1.20.1/1.20.1.json").is_file());
+        assert!(to.join("backups/a/World/1.json").is_file());
+        assert!(!from.join("instances").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+// Discord Rich Presence: shows the launcher or the running instance on the
+// user's Discord profile while settings.discord_rpc is on. A background thread
+// follows the setting and running games, and reconnects when Discord restarts
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::discord_ipc::{Activity, Assets, DiscordIpc, Timestamps};
+use crate::translations::keys;
+use crate::{running_games, translate, AppState};
+
+// Discord application the presence belongs to, read at runtime or baked in at build time
+const CLIENT_ID_VAR: &str = "INTELLIGENCE_DISCORD_CLIENT_ID";
+
+// How often the setting and running games are checked
+const TICK: Duration = Duration::from_secs(2);
+
+// Delay between attempts to reach Discord while it isn't running
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(15);
+
+// The unchanged activity is resent this often, which also notices a restarted Discord
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+// Asset uploaded to the Discord application
+const LARGE_IMAGE: &str = "intelligence";
+
+fn client_id() -> Option<String> {
+    std::env::var(CLIENT_ID_VAR)
+        .ok()
+        .or_else(|| option_env!("INTELLIGENCE_DISCORD_CLIENT_ID").map(String::from))
+        .filter(|id| !id.is_empty())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn text(state: &AppState, key: &str, args: &[(&str, &str)]) -> String {
+    let args: HashMap<String, serde_json::Value> = args
+        .iter()
+        .map(|(name, value)| (name.to_string(), serde_json::Value::from(*value)))
+        .collect();
+    translate(state, key, &args).unwrap_or_else(|_| key.to_string())
+}
+
+// Activity for the current launcher state, the most recently started game wins
+fn current_activity(app_handle: &AppHandle, launcher_started: u64) -> Activity {
+    let state = app_handle.state::<AppState>();
+    let Some(game) = running_games(app_handle).pop() else {
+        return Activity {
+            details: text(&state, keys::DISCORD_IDLE, &[]),
+            state: None,
+            timestamps: Some(Timestamps { start: launcher_started }),
+            assets: Assets { large_image: LARGE_IMAGE.to_string(), large_text: "Intelligence".to_string() },
+        };
+    };
+
+    let version = match &game.loader {
+        Some(loader) => text(&state, keys::DISCORD_VERSION_LOADER, &[("version", &game.game_version), ("loader", loader)]),
+        None => text(&state, keys::DISCORD_VERSION, &[("version", &game.game_version)]),
+    };
+    Activity {
+        details: text(&state, keys::DISCORD_PLAYING, &[("name", &game.instance_name)]),
+        state: Some(match &game.server {
+            Some(server) => text(&state, keys::DISCORD_SERVER, &[("server", server)]),
+            None => version.clone(),
+        }),
+        timestamps: Some(Timestamps { start: game.started_at }),
+        assets: Assets { large_image: LARGE_IMAGE.to_string(), large_text: version },
+    }
+}
+
+fn enabled(app_handle: &AppHandle) -> bool {
+    app_handle
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|settings| settings.discord_rpc)
+        .unwrap_or(false)
+}
+
+// Start the presence thread
+pub fn spawn(app_handle: AppHandle) {
+    let Some(client_id) = client_id() else {
+        eprintln!("Discord Rich Presence unavailable: {} is not set", CLIENT_ID_VAR);
+        return;
+    };
+    let launcher_started = now();
+
+    thread::spawn(move || {
+        let mut connection: Option<DiscordIpc> = None;
+        let mut shown: Option<(Activity, Instant)> = None;
+        let mut last_attempt: Option<Instant> = None;
+
+        loop {
+            if !enabled(&app_handle) {
+                // Turned off, take the presence down right away
+                if let Some(client) = connection.take() {
+                    client.close();
+                }
+                shown = None;
+                last_attempt = None;
+                thread::sleep(TICK);
+                continue;
+            }
+
+            if connection.is_none() && last_attempt.is_none_or(|attempt| attempt.elapsed() >= RECONNECT_INTERVAL) {
+                last_attempt = Some(Instant::now());
+                connection = DiscordIpc::connect(&client_id).ok();
+                shown = None;
+            }
+
+            if let Some(client) = connection.as_mut() {
+                let activity = current_activity(&app_handle, launcher_started);
+                let outdated = shown
+                    .as_ref()
+                    .is_none_or(|(previous, sent)| *previous != activity || sent.elapsed() >= REFRESH_INTERVAL);
+                if outdated {
+                    match client.set_activity(Some(&activity)) {
+                        Ok(()) => shown = Some((activity, Instant::now())),
+                        Err(e) => {
+                            eprintln!("Lost connection to Discord: {}", e);
+                            connection = None;
+                            shown = None;
+                        }
+                    }
+                }
+            }
+
+            thread::sleep(TICK);
+        }
+    });
+}
@@ -0,0 +1,255 @@
+// Worlds of an instance, read from <game dir>/saves/<folder>/level.dat.
+// Worlds can be renamed (LevelName only, the folder stays), moved to the
+// system trash, duplicated and copied to another instance
+use std::error::Error;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use base64::Engine;
+use serde::Serialize;
+
+use crate::instances::Instance;
+use crate::nbt::{self, Tag};
+use crate::versions;
+
+type SavesResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// Lock held by a running game, never copied
+pub const SESSION_LOCK: &str = "session.lock";
+
+const LEVEL_DAT: &str = "level.dat";
+const ICON: &str = "icon.png";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct World {
+    // Directory name under saves/, used to address the world
+    pub folder: String,
+    pub name: String,
+    pub game_mode: Option<String>,
+    pub hardcore: bool,
+    pub difficulty: Option<String>,
+    pub seed: Option<i64>,
+    // Unix timestamp in milliseconds
+    pub last_played: Option<i64>,
+    pub version: Option<String>,
+    pub data_version: Option<i64>,
+    // Folder size in bytes
+    pub size: u64,
+    // icon.png as a data URL
+    pub icon: Option<String>,
+}
+
+// Result of copying a world to another instance
+#[derive(Debug, Serialize, Clone)]
+pub struct CopiedWorld {
+    pub world: World,
+    // Set when the target instance's game version is older than the world, or
+    // when its version couldn't be read to compare
+    pub warning: Option<String>,
+}
+
+pub fn saves_dir(data_path: &Path, instance: &Instance) -> PathBuf {
+    instance.game_dir(data_path).join("saves")
+}
+
+// Folder names come from the frontend, keep them inside saves/
+fn world_dir(data_path: &Path, instance: &Instance, folder: &str) -> SavesResult<PathBuf> {
+    let mut components = Path::new(folder).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+        return Err(format!("Invalid world folder: {}", folder).into());
+    }
+
+    let path = saves_dir(data_path, instance).join(folder);
+    if !path.join(LEVEL_DAT).is_file() {
+        return Err(format!("World {} not found in {}", folder, instance.name).into());
+    }
+    Ok(path)
+}
+
+fn game_mode_name(mode: i64) -> Option<String> {
+    let name = match mode {
+        0 => "survival",
+        1 => "creative",
+        2 => "adventure",
+        3 => "spectator",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+fn difficulty_name(difficulty: i64) -> Option<String> {
+    let name = match difficulty {
+        0 => "peaceful",
+        1 => "easy",
+        2 => "normal",
+        3 => "hard",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+// Total size of a directory tree in bytes
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else { return 0 };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|metadata| metadata.len()).unwrap_or_default(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn read_world(path: &Path) -> SavesResult<World> {
+    let (_, root) = nbt::read_gzip(&path.join(LEVEL_DAT))?;
+    let data = root.get("Data").ok_or("level.dat has no Data compound")?;
+    let folder = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    let icon = fs::read(path.join(ICON))
+        .ok()
+        .map(|bytes| format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(bytes)));
+
+    Ok(World {
+        name: data.get("LevelName").and_then(Tag::as_str).unwrap_or(&folder).to_string(),
+        game_mode: data.get("GameType").and_then(Tag::as_i64).and_then(game_mode_name),
+        hardcore: data.get("hardcore").and_then(Tag::as_i64).is_some_and(|value| value != 0),
+        difficulty: data.get("Difficulty").and_then(Tag::as_i64).and_then(difficulty_name),
+        // 1.16 moved the seed into WorldGenSettings
+        seed: data
+            .path(&["WorldGenSettings", "seed"])
+            .or_else(|| data.get("RandomSeed"))
+            .and_then(Tag::as_i64),
+        last_played: data.get("LastPlayed").and_then(Tag::as_i64),
+        version: data.path(&["Version", "Name"]).and_then(Tag::as_str).map(String::from),
+        data_version: data
+            .get("DataVersion")
+            .or_else(|| data.path(&["Version", "Id"]))
+            .and_then(Tag::as_i64),
+        size: dir_size(path),
+        icon,
+        folder,
+    })
+}
+
+// Worlds of an instance, most recently played first
+pub fn list_worlds(data_path: &Path, instance: &Instance) -> SavesResult<Vec<World>> {
+    let mut worlds = Vec::new();
+    let Ok(entries) = fs::read_dir(saves_dir(data_path, instance)) else { return Ok(worlds) };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.join(LEVEL_DAT).is_file() {
+            continue;
+        }
+        match read_world(&path) {
+            Ok(world) => worlds.push(world),
+            Err(e) => eprintln!("Failed to read world {:?}: {}", path, e),
+        }
+    }
+
+    worlds.sort_by(|a, b| b.last_played.cmp(&a.last_played).then_with(|| a.name.cmp(&b.name)));
+    Ok(worlds)
+}
+
+fn set_level_name(path: &Path, name: &str) -> SavesResult<()> {
+    let level_dat = path.join(LEVEL_DAT);
+    let (root_name, mut root) = nbt::read_gzip(&level_dat)?;
+    let data = root.get_mut("Data").ok_or("level.dat has no Data compound")?;
+    match data.get_mut("LevelName") {
+        Some(tag) => *tag = Tag::String(name.to_string()),
+        None => match data {
+            Tag::Compound(entries) => entries.push(("LevelName".to_string(), Tag::String(name.to_string()))),
+            _ => return Err("level.dat Data is not a compound".into()),
+        },
+    }
+    nbt::write_gzip(&level_dat, &root_name, &root)
+}
+
+// Change the name shown in the world list
+pub fn rename_world(data_path: &Path, instance: &Instance, folder: &str, name: &str) -> SavesResult<World> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("World name cannot be empty".into());
+    }
+    let path = world_dir(data_path, instance, folder)?;
+    set_level_name(&path, name)?;
+    read_world(&path)
+}
+
+// Move a world to the system trash
+pub fn delete_world(data_path: &Path, instance: &Instance, folder: &str) -> SavesResult<()> {
+    let path = world_dir(data_path, instance, folder)?;
+    trash::delete(&path).map_err(|e| format!("Failed to move {} to the trash: {}", folder, e))?;
+    Ok(())
+}
+
+// Copy a directory tree, leaving out the session lock
+pub fn copy_dir(source: &Path, dest: &Path) -> SavesResult<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if entry.file_name() != SESSION_LOCK {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+// Free folder name in saves/ based on `base`
+fn unique_folder(saves: &Path, base: &str) -> String {
+    let mut folder = base.to_string();
+    let mut suffix = 2;
+    while saves.join(&folder).exists() {
+        folder = format!("{} ({})", base, suffix);
+        suffix += 1;
+    }
+    folder
+}
+
+// Copy a world into another saves directory under a free folder name
+fn copy_world_to(source: &Path, saves: &Path, folder: &str, name: &str) -> SavesResult<World> {
+    let dest = saves.join(unique_folder(saves, folder));
+    if let Err(e) = copy_dir(source, &dest).and_then(|_| set_level_name(&dest, name)) {
+        let _ = fs::remove_dir_all(&dest);
+        return Err(e);
+    }
+    read_world(&dest)
+}
+
+pub fn duplicate_world(data_path: &Path, instance: &Instance, folder: &str) -> SavesResult<World> {
+    let source = world_dir(data_path, instance, folder)?;
+    let world = read_world(&source)?;
+    copy_world_to(&source, &saves_dir(data_path, instance), folder, &format!("{} - Copy", world.name))
+}
+
+// Copy a world to another instance, warning when that instance's game version
+// predates the world, since opening it there can lose data, or can't be checked
+pub fn copy_world(data_path: &Path, source: &Instance, folder: &str, target: &Instance) -> SavesResult<CopiedWorld> {
+    let source_path = world_dir(data_path, source, folder)?;
+    let world = read_world(&source_path)?;
+
+    // The version jar may not be downloaded yet or predate version.json
+    let target_data_version = versions::data_version(data_path, &target.game_version).ok().flatten();
+    let warning = match (world.data_version, target_data_version) {
+        (Some(world_version), Some(target_version)) if world_version > target_version => Some(format!(
+            "{} was last played in {} which is newer than {} in {}. Opening it there may corrupt the world",
+            world.name,
+            world.version.as_deref().unwrap_or("a newer version"),
+            target.game_version,
+            target.name,
+        )),
+        (_, None) => Some(format!(
+            "Could not verify that {} is compatible with {} in {}. Back it up before opening it there",
+            world.name, target.game_version, target.name,
+        )),
+        _ => None,
+    };
+
+    let saves = saves_dir(data_path, target);
+    fs::create_dir_all(&saves)?;
+    let world = copy_world_to(&source_path, &saves, folder, &world.name)?;
+    Ok(CopiedWorld { world, warning })
+}
@@ -0,0 +1,386 @@
+// Keeps a single launcher process per app directory. The first process holds an
+// OS lock on instance.owner and listens on a loopback port recorded in
+// instance.lock next to settings.json; later invocations send their arguments
+// (links, launch requests) to it, wait for the result and exit. They never start
+// a second launcher while the lock is held
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+const LOCK_FILE: &str = "instance.lock";
+const OWNER_FILE: &str = "instance.owner";
+
+// How long a second process waits for the running one
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+// A launcher that was started at the same moment may still be opening its window
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_INTERVAL: Duration = Duration::from_millis(200);
+// Forwarded requests are answered once they have been carried out
+const REPLY_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Upper bound on a forwarded request, arguments are short
+const MAX_REQUEST_SIZE: u64 = 64 * 1024;
+
+// Contents of the lock file
+#[derive(Debug, Serialize, Deserialize)]
+struct LockInfo {
+    port: u16,
+    // Shared secret proving a request comes from a process of the same user
+    token: String,
+    pid: u32,
+}
+
+// Message sent by a second process
+#[derive(Debug, Serialize, Deserialize)]
+struct ForwardRequest {
+    token: String,
+    args: Vec<String>,
+}
+
+// Why forwarding failed
+#[derive(Debug)]
+enum ForwardError {
+    // The running launcher tried and reported an error
+    Refused(String),
+    // Another process holds the lock but could not be reached
+    Unreachable(Box<dyn Error>),
+    // The lock can't be taken at all, e.g. on a file system without locks
+    NoLock(Box<dyn Error>),
+}
+
+impl<E: Into<Box<dyn Error>>> From<E> for ForwardError {
+    fn from(error: E) -> Self {
+        ForwardError::Unreachable(error.into())
+    }
+}
+
+// Open instance.owner while this process is the running launcher. The OS lock
+// goes away with the process, so a crash never leaves a stale owner behind
+static OWNER: Mutex<Option<File>> = Mutex::new(None);
+
+fn config_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let config = paths::resolve()?.config;
+    fs::create_dir_all(&config)?;
+    Ok(config)
+}
+
+// Try to become the running launcher by locking instance.owner in `config`,
+// keeping the file in `owner`. Returns false when another process already is
+fn claim(owner: &Mutex<Option<File>>, config: &Path) -> io::Result<bool> {
+    let mut owner = owner.lock().map_err(|_| io::Error::other("Failed to lock owner"))?;
+    if owner.is_some() {
+        return Ok(true);
+    }
+
+    let file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(false).open(config.join(OWNER_FILE))?;
+    match file.try_lock() {
+        Ok(()) => {
+            *owner = Some(file);
+            Ok(true)
+        },
+        Err(fs::TryLockError::WouldBlock) => Ok(false),
+        Err(fs::TryLockError::Error(e)) => Err(e),
+    }
+}
+
+fn read_lock(path: &Path) -> Result<LockInfo, Box<dyn Error>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+// Write the lock file readable by the current user only
+fn write_lock(path: &Path, lock: &LockInfo) -> Result<(), Box<dyn Error>> {
+    let tmp_path = path.with_extension("lock.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(serde_json::to_string(lock)?.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+fn new_token() -> Result<String, Box<dyn Error>> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate token: {}", e))?;
+    Ok(hex::encode(bytes))
+}
+
+// Whether arguments belong to a running launcher rather than a new process:
+// anything that opens a window (no arguments, a link, unknown flags) and launch
+// requests. Only the other CLI commands run next to a launcher
+pub fn is_forwardable(args: &[String]) -> bool {
+    !args.first().is_some_and(|command| matches!(command.as_str(), "list" | "install" | "help" | "--help" | "-h"))
+}
+
+// Connect to the launcher recorded in the lock file in `config`
+fn connect(config: &Path) -> Result<(TcpStream, LockInfo), Box<dyn Error>> {
+    let lock = read_lock(&config.join(LOCK_FILE))?;
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, lock.port));
+    Ok((TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?, lock))
+}
+
+// Send arguments with the token and wait for the launcher to carry them out
+fn send(mut stream: TcpStream, token: String, args: &[String]) -> Result<(), ForwardError> {
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+
+    let request = ForwardRequest { token, args: args.to_vec() };
+    writeln!(stream, "{}", serde_json::to_string(&request)?)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    match reply.trim() {
+        "ok" => Ok(()),
+        "" => Err("Running launcher closed the connection".into()),
+        error => Err(ForwardError::Refused(error.strip_prefix("error: ").unwrap_or(error).to_string())),
+    }
+}
+
+// Hand arguments to the process holding the lock in `config`, or claim the
+// lock once it is free. The holder may still be starting up, or be a headless
+// `launch` about to let go of it. Returns false when this process now holds it
+fn forward_in(owner: &Mutex<Option<File>>, config: &Path, args: &[String], timeout: Duration) -> Result<bool, ForwardError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if claim(owner, config).map_err(|e| ForwardError::NoLock(e.into()))? {
+            return Ok(false);
+        }
+        match connect(config) {
+            Ok((stream, lock)) => return send(stream, lock.token, args).map(|()| true),
+            Err(e) if Instant::now() >= deadline => return Err(ForwardError::Unreachable(e)),
+            Err(_) => thread::sleep(RETRY_INTERVAL),
+        }
+    }
+}
+
+// Hand arguments to a running launcher and return the exit code for this
+// process. Returns None when there is no running launcher (this process is now
+// it) and this process should carry on by itself. A launcher that holds the
+// lock but can't be reached is not replaced by a second one
+pub fn forward(args: &[String]) -> Option<i32> {
+    if !is_forwardable(args) {
+        return None;
+    }
+
+    let result = config_dir()
+        .map_err(ForwardError::NoLock)
+        .and_then(|config| forward_in(&OWNER, &config, args, STARTUP_TIMEOUT));
+    match result {
+        Ok(false) => None,
+        Ok(true) => Some(0),
+        Err(ForwardError::Refused(message)) => {
+            eprintln!("Error: {}", message);
+            Some(1)
+        },
+        Err(ForwardError::Unreachable(e)) => {
+            eprintln!("Another launcher is running but can't be reached: {}", e);
+            Some(1)
+        },
+        Err(ForwardError::NoLock(e)) => {
+            eprintln!("Failed to check for a running launcher: {}", e);
+            None
+        },
+    }
+}
+
+// Read one request from a second process
+fn read_request(stream: &TcpStream, token: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(stream).take(MAX_REQUEST_SIZE).read_line(&mut line)?;
+    let request: ForwardRequest = serde_json::from_str(&line)?;
+
+    if request.token != token {
+        let mut stream = stream;
+        stream.write_all(b"error: invalid token\n")?;
+        return Err("Rejected request with an invalid token".into());
+    }
+    Ok(request.args)
+}
+
+// Carry out one request and reply with the handler's result
+fn serve(mut stream: TcpStream, token: &str, handler: &impl Fn(Vec<String>) -> Result<(), String>) -> Result<(), Box<dyn Error>> {
+    let args = read_request(&stream, token)?;
+    match handler(args) {
+        Ok(()) => stream.write_all(b"ok\n")?,
+        Err(e) => writeln!(stream, "error: {}", e.replace('\n', " "))?,
+    }
+    Ok(())
+}
+
+// Become the running launcher: accept forwarded arguments on a background thread
+// and record where to find us in the lock file. Fails when another process
+// already is the running launcher
+pub fn listen(handler: impl Fn(Vec<String>) -> Result<(), String> + Send + 'static) -> Result<(), Box<dyn Error>> {
+    let config = config_dir()?;
+    if !claim(&OWNER, &config)? {
+        return Err("Another launcher is already running".into());
+    }
+    write_lock(&config.join(LOCK_FILE), &spawn_listener(handler)?)?;
+    Ok(())
+}
+
+// Accept forwarded arguments on a background thread, returning what goes into
+// the lock file
+fn spawn_listener(handler: impl Fn(Vec<String>) -> Result<(), String> + Send + 'static) -> Result<LockInfo, Box<dyn Error>> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let token = new_token()?;
+    let lock = LockInfo {
+        port: listener.local_addr()?.port(),
+        token: token.clone(),
+        pid: std::process::id(),
+    };
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(|e| e.into())
+                .and_then(|stream| serve(stream, &token, &handler));
+            if let Err(e) = result {
+                eprintln!("Ignoring forwarded request: {}", e);
+            }
+        }
+    });
+
+    Ok(lock)
+}
+
+// Remove the lock file if it still belongs to this process and let another
+// process become the running launcher
+pub fn release() {
+    if let Ok(path) = config_dir().map(|config| config.join(LOCK_FILE)) {
+        if read_lock(&path).is_ok_and(|lock| lock.pid == std::process::id()) {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("Failed to remove {:?}: {}", path, e);
+            }
+        }
+    }
+
+    if let Ok(mut owner) = OWNER.lock() {
+        owner.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn temp_config(name: &str) -> PathBuf {
+        let config = std::env::temp_dir().join(format!("intelligence-single-instance-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&config).unwrap();
+        config
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    // Launcher holding the lock in `config`, reporting forwarded arguments
+    fn start_launcher(config: &Path) -> (Mutex<Option<File>>, LockInfo, mpsc::Receiver<Vec<String>>) {
+        let owner = Mutex::new(None);
+        assert!(claim(&owner, config).unwrap());
+
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let lock = spawn_listener(move |args: Vec<String>| {
+            let result = match args.get(1) {
+                Some(instance) if instance == "missing" => Err("Instance missing\nnot found".to_string()),
+                _ => Ok(()),
+            };
+            sender.lock().unwrap().send(args).unwrap();
+            result
+        })
+        .unwrap();
+        write_lock(&config.join(LOCK_FILE), &lock).unwrap();
+        (owner, lock, receiver)
+    }
+
+    #[test]
+    fn only_one_owner_holds_the_lock() {
+        let config = temp_config("claim");
+        let first = Mutex::new(None);
+        let second = Mutex::new(None);
+        assert!(claim(&first, &config).unwrap());
+        assert!(claim(&first, &config).unwrap());
+        assert!(!claim(&second, &config).unwrap());
+
+        first.lock().unwrap().take();
+        assert!(claim(&second, &config).unwrap());
+        fs::remove_dir_all(config).unwrap();
+    }
+
+    #[test]
+    fn arguments_are_forwarded_to_the_owner() {
+        let config = temp_config("forward");
+        let (_launcher, _, received) = start_launcher(&config);
+        let second = Mutex::new(None);
+
+        let launch = args(&["launch", "survival", "--username", "Notch"]);
+        assert!(forward_in(&second, &config, &launch, Duration::ZERO).unwrap());
+        assert_eq!(received.recv().unwrap(), launch);
+
+        match forward_in(&second, &config, &args(&["launch", "missing"]), Duration::ZERO) {
+            Err(ForwardError::Refused(message)) => assert_eq!(message, "Instance missing not found"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(second.lock().unwrap().is_none());
+        fs::remove_dir_all(config).unwrap();
+    }
+
+    #[test]
+    fn requests_with_another_token_are_rejected() {
+        let config = temp_config("token");
+        let (_launcher, lock, received) = start_launcher(&config);
+
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, lock.port)).unwrap();
+        match send(stream, "0".repeat(64), &args(&["launch", "survival"])) {
+            Err(ForwardError::Refused(message)) => assert_eq!(message, "invalid token"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(received.try_recv().is_err());
+        fs::remove_dir_all(config).unwrap();
+    }
+
+    #[test]
+    fn unreachable_owner_is_not_replaced() {
+        let config = temp_config("unreachable");
+        let headless = Mutex::new(None);
+        assert!(claim(&headless, &config).unwrap());
+        let gui = Mutex::new(None);
+
+        // Holds the lock without listening, like a headless launch
+        assert!(matches!(forward_in(&gui, &config, &[], Duration::ZERO), Err(ForwardError::Unreachable(_))));
+
+        headless.lock().unwrap().take();
+        assert!(!forward_in(&gui, &config, &[], Duration::ZERO).unwrap());
+        assert!(gui.lock().unwrap().is_some());
+        fs::remove_dir_all(config).unwrap();
+    }
+
+    #[test]
+    fn only_headless_commands_skip_the_running_launcher() {
+        assert!(is_forwardable(&[]));
+        assert!(is_forwardable(&args(&["intelligence://launch/survival"])));
+        assert!(is_forwardable(&args(&["launch", "survival"])));
+        assert!(is_forwardable(&args(&["--unknown-flag"])));
+        assert!(!is_forwardable(&args(&["list"])));
+        assert!(!is_forwardable(&args(&["install", "latest"])));
+        assert!(!is_forwardable(&args(&["--help"])));
+    }
+}
@@ -0,0 +1,300 @@
+// System tray: quick launch of the last played instance, all instances, running
+// games with a stop item, and a way back to the window or out of the app
+use std::collections::HashMap;
+use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder, Submenu, SubmenuBuilder};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager};
+
+use crate::instances::Instance;
+use crate::launcher::RunningGame;
+use crate::translations::keys;
+use crate::{
+    get_data_path, instances, kill_running_game, running_games, show_main_window, start_instance, translate, AppState,
+};
+
+const TRAY_ID: &str = "main";
+
+// Menu item ids, instance ids and pids follow the prefix
+const SHOW_ID: &str = "show";
+const QUIT_ID: &str = "quit";
+const QUICK_LAUNCH_PREFIX: &str = "quick_launch:";
+const LAUNCH_PREFIX: &str = "launch:";
+const STOP_PREFIX: &str = "stop:";
+
+// Translated menu label, `name` fills the {name} argument
+fn label(app_handle: &AppHandle, key: &str, name: Option<&str>) -> String {
+    let args: HashMap<String, serde_json::Value> = name
+        .map(|name| HashMap::from([("name".to_string(), serde_json::Value::from(name))]))
+        .unwrap_or_default();
+    translate(&app_handle.state::<AppState>(), key, &args).unwrap_or_else(|_| key.to_string())
+}
+
+// Menu structure independent of tauri, ids carry the action
+#[derive(Debug, Clone, PartialEq)]
+enum MenuEntry {
+    Item { id: String, label: String },
+    // Greyed out placeholder
+    Disabled(String),
+    Submenu { label: String, entries: Vec<MenuEntry> },
+    Separator,
+}
+
+// What a menu item does when clicked
+#[derive(Debug, Clone, PartialEq)]
+enum TrayAction {
+    Show,
+    Quit,
+    Launch(String),
+    Stop(u32),
+}
+
+impl TrayAction {
+    fn parse(id: &str) -> Option<Self> {
+        if id == SHOW_ID {
+            Some(TrayAction::Show)
+        } else if id == QUIT_ID {
+            Some(TrayAction::Quit)
+        } else if let Some(instance_id) = id.strip_prefix(QUICK_LAUNCH_PREFIX).or_else(|| id.strip_prefix(LAUNCH_PREFIX)) {
+            Some(TrayAction::Launch(instance_id.to_string()))
+        } else {
+            id.strip_prefix(STOP_PREFIX).and_then(|pid| pid.parse().ok()).map(TrayAction::Stop)
+        }
+    }
+}
+
+// Entries for the instances (sorted by last played) and running games. `label`
+// translates a key, filling {name} with the second argument
+fn menu_model(
+    instances: &[Instance],
+    running: &[RunningGame],
+    label: impl Fn(&str, Option<&str>) -> String,
+) -> Vec<MenuEntry> {
+    let mut entries = Vec::new();
+
+    // The most recently played instance is the quick launch target
+    if let Some(last) = instances.iter().find(|instance| instance.last_played.is_some()) {
+        entries.push(MenuEntry::Item {
+            id: format!("{}{}", QUICK_LAUNCH_PREFIX, last.id),
+            label: label(keys::TRAY_QUICK_LAUNCH, Some(&last.name)),
+        });
+        entries.push(MenuEntry::Separator);
+    }
+
+    let mut instance_entries: Vec<MenuEntry> = instances
+        .iter()
+        .map(|instance| MenuEntry::Item { id: format!("{}{}", LAUNCH_PREFIX, instance.id), label: instance.name.clone() })
+        .collect();
+    if instance_entries.is_empty() {
+        instance_entries.push(MenuEntry::Disabled(label(keys::TRAY_NO_INSTANCES, None)));
+    }
+    entries.push(MenuEntry::Submenu { label: label(keys::TRAY_INSTANCES, None), entries: instance_entries });
+
+    if !running.is_empty() {
+        let running_entries = running
+            .iter()
+            .map(|game| MenuEntry::Item {
+                id: format!("{}{}", STOP_PREFIX, game.pid),
+                label: label(keys::TRAY_STOP, Some(&game.instance_name)),
+            })
+            .collect();
+        entries.push(MenuEntry::Submenu { label: label(keys::TRAY_RUNNING, None), entries: running_entries });
+    }
+
+    entries.push(MenuEntry::Separator);
+    entries.push(MenuEntry::Item { id: SHOW_ID.to_string(), label: label(keys::TRAY_SHOW, None) });
+    entries.push(MenuEntry::Item { id: QUIT_ID.to_string(), label: label(keys::TRAY_QUIT, None) });
+    entries
+}
+
+fn build_submenu(app_handle: &AppHandle, label: &str, entries: &[MenuEntry]) -> tauri::Result<Submenu<tauri::Wry>> {
+    let mut submenu = SubmenuBuilder::new(app_handle, label);
+    for entry in entries {
+        submenu = match entry {
+            MenuEntry::Item { id, label } => submenu.text(id, label),
+            MenuEntry::Disabled(label) => submenu.item(&MenuItemBuilder::new(label).enabled(false).build(app_handle)?),
+            MenuEntry::Submenu { label, entries } => submenu.item(&build_submenu(app_handle, label, entries)?),
+            MenuEntry::Separator => submenu.separator(),
+        };
+    }
+    submenu.build()
+}
+
+fn build_menu(app_handle: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let instances = get_data_path(app_handle)
+        .ok()
+        .and_then(|data_path| instances::list_instances(&data_path).ok())
+        .unwrap_or_default();
+    let entries = menu_model(&instances, &running_games(app_handle), |key, name| label(app_handle, key, name));
+
+    let mut menu = MenuBuilder::new(app_handle);
+    for entry in &entries {
+        menu = match entry {
+            MenuEntry::Item { id, label } => menu.text(id, label),
+            MenuEntry::Disabled(label) => menu.item(&MenuItemBuilder::new(label).enabled(false).build(app_handle)?),
+            MenuEntry::Submenu { label, entries } => menu.item(&build_submenu(app_handle, label, entries)?),
+            MenuEntry::Separator => menu.separator(),
+        };
+    }
+    menu.build()
+}
+
+fn handle_menu_event(app_handle: &AppHandle, id: &str) {
+    let result = match TrayAction::parse(id) {
+        Some(TrayAction::Show) => {
+            show_main_window(app_handle);
+            Ok(())
+        },
+        Some(TrayAction::Quit) => {
+            app_handle.exit(0);
+            Ok(())
+        },
+        Some(TrayAction::Launch(instance_id)) => start_instance(app_handle, &instance_id, None, None).map(|_| ()),
+        Some(TrayAction::Stop(pid)) => kill_running_game(app_handle, pid),
+        None => Ok(()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Tray action {} failed: {}", id, e);
+    }
+}
+
+// Create the tray icon
+pub fn create(app_handle: &AppHandle) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&build_menu(app_handle)?)
+        .tooltip("Intelligence")
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app_handle, event| handle_menu_event(app_handle, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            // Left click brings the window back, the menu opens on right click
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app_handle.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    builder.build(app_handle)?;
+    Ok(())
+}
+
+// Whether the tray icon exists, hiding the window without it would lose the app
+pub fn is_available(app_handle: &AppHandle) -> bool {
+    app_handle.tray_by_id(TRAY_ID).is_some()
+}
+
+// Rebuild the menu after instances, running games or the language changed
+pub fn refresh(app_handle: &AppHandle) {
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else { return };
+    if let Err(e) = build_menu(app_handle).and_then(|menu| tray.set_menu(Some(menu))) {
+        eprintln!("Failed to update tray menu: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: &str, last_played: Option<u64>) -> Instance {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id.to_uppercase(),
+            "game_version": "1.20.1",
+            "last_played": last_played,
+        }))
+        .unwrap()
+    }
+
+    fn running(pid: u32, instance_name: &str) -> RunningGame {
+        RunningGame {
+            pid,
+            instance_id: instance_name.to_lowercase(),
+            instance_name: instance_name.to_string(),
+            game_version: "1.20.1".to_string(),
+            loader: None,
+            server: None,
+            started_at: 0,
+        }
+    }
+
+    fn label(key: &str, name: Option<&str>) -> String {
+        match name {
+            Some(name) => format!("{}({})", key, name),
+            None => key.to_string(),
+        }
+    }
+
+    fn item(id: &str, label: &str) -> MenuEntry {
+        MenuEntry::Item { id: id.to_string(), label: label.to_string() }
+    }
+
+    fn footer() -> Vec<MenuEntry> {
+        vec![MenuEntry::Separator, item("show", keys::TRAY_SHOW), item("quit", keys::TRAY_QUIT)]
+    }
+
+    #[test]
+    fn empty_menu() {
+        let mut expected = vec![MenuEntry::Submenu {
+            label: keys::TRAY_INSTANCES.to_string(),
+            entries: vec![MenuEntry::Disabled(keys::TRAY_NO_INSTANCES.to_string())],
+        }];
+        expected.extend(footer());
+        assert_eq!(menu_model(&[], &[], label), expected);
+    }
+
+    #[test]
+    fn quick_launch_instances_and_running_games() {
+        // As sorted by list_instances, never played ones last
+        let instances = [instance("b", Some(20)), instance("a", Some(10)), instance("new", None)];
+        let running = [running(42, "A")];
+
+        let mut expected = vec![
+            item("quick_launch:b", "tray.quick_launch(B)"),
+            MenuEntry::Separator,
+            MenuEntry::Submenu {
+                label: keys::TRAY_INSTANCES.to_string(),
+                entries: vec![item("launch:b", "B"), item("launch:a", "A"), item("launch:new", "NEW")],
+            },
+            MenuEntry::Submenu { label: keys::TRAY_RUNNING.to_string(), entries: vec![item("stop:42", "tray.stop(A)")] },
+        ];
+        expected.extend(footer());
+        assert_eq!(menu_model(&instances, &running, label), expected);
+
+        // Nothing played yet, no quick launch
+        let model = menu_model(&[instance("new", None)], &[], label);
+        assert!(matches!(&model[0], MenuEntry::Submenu { label, .. } if label == keys::TRAY_INSTANCES));
+    }
+
+    #[test]
+    fn menu_ids_round_trip_to_actions() {
+        let instances = [instance("survival", Some(1))];
+        let entries = menu_model(&instances, &[running(7, "Survival")], label);
+
+        fn ids(entries: &[MenuEntry], out: &mut Vec<String>) {
+            for entry in entries {
+                match entry {
+                    MenuEntry::Item { id, .. } => out.push(id.clone()),
+                    MenuEntry::Submenu { entries, .. } => ids(entries, out),
+                    _ => {}
+                }
+            }
+        }
+        let mut all = Vec::new();
+        ids(&entries, &mut all);
+        let actions: Vec<Option<TrayAction>> = all.iter().map(|id| TrayAction::parse(id)).collect();
+
+        assert_eq!(
+            actions,
+            vec![
+                Some(TrayAction::Launch("survival".to_string())),
+                Some(TrayAction::Launch("survival".to_string())),
+                Some(TrayAction::Stop(7)),
+                Some(TrayAction::Show),
+                Some(TrayAction::Quit),
+            ]
+        );
+        assert_eq!(TrayAction::parse("stop:not-a-pid"), None);
+        assert_eq!(TrayAction::parse("unknown"), None);
+    }
+}
@@ -0,0 +1,686 @@
+// Minecraft version metadata and installation.
+//
+// Versions are installed from Mojang's launcher manifest into directories shared
+// by all instances:
+//
+//   <data>/versions/<id>/<id>.json     version metadata
+//   <data>/versions/<id>/<id>.jar      client jar
+//   <data>/libraries/...               maven layout
+//   <data>/assets/indexes|objects      asset index and hashed objects
+//
+// Loader profiles (Fabric, Quilt, ...) dropped into versions/ are supported
+// through `inheritsFrom`, see load_version.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+pub type InstallResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+const VERSION_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+const RESOURCES_URL: &str = "https://resources.download.minecraft.net";
+const LIBRARIES_URL: &str = "https://libraries.minecraft.net/";
+
+// Number of files downloaded at the same time
+const DOWNLOAD_CONCURRENCY: usize = 16;
+
+// Longest inheritsFrom chain followed, real profiles have one or two levels
+const MAX_INHERITANCE_DEPTH: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LatestVersions {
+    pub release: String,
+    pub snapshot: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestVersion {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub url: String,
+    #[serde(default)]
+    pub sha1: Option<String>,
+    #[serde(default, rename = "releaseTime")]
+    pub release_time: Option<String>,
+}
+
+// Mojang's list of all game versions
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VersionManifest {
+    pub latest: LatestVersions,
+    pub versions: Vec<ManifestVersion>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OsRule {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arch: Option<String>,
+}
+
+// Allow/disallow rule on libraries and arguments
+#[derive(Debug, Deserialize, Clone)]
+pub struct Rule {
+    pub action: String,
+    #[serde(default)]
+    pub os: Option<OsRule>,
+    #[serde(default)]
+    pub features: Option<HashMap<String, bool>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ArgumentValue {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Argument {
+    Plain(String),
+    Conditional { rules: Vec<Rule>, value: ArgumentValue },
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Arguments {
+    #[serde(default)]
+    pub game: Vec<Argument>,
+    #[serde(default)]
+    pub jvm: Vec<Argument>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Artifact {
+    #[serde(default)]
+    pub path: Option<String>,
+    pub url: String,
+    #[serde(default)]
+    pub sha1: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct LibraryDownloads {
+    #[serde(default)]
+    pub artifact: Option<Artifact>,
+    #[serde(default)]
+    pub classifiers: HashMap<String, Artifact>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ExtractRules {
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Library {
+    // Maven coordinates, group:artifact:version[:classifier]
+    pub name: String,
+    #[serde(default)]
+    pub downloads: Option<LibraryDownloads>,
+    // Maven repository for libraries listed without downloads (loader profiles)
+    #[serde(default)]
+    pub url: Option<String>,
+    // Hash of the artifact for libraries listed without downloads
+    #[serde(default)]
+    pub sha1: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    // Classifier per OS for pre-1.19 native libraries
+    #[serde(default)]
+    pub natives: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub extract: Option<ExtractRules>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AssetIndexRef {
+    pub id: String,
+    pub url: String,
+    #[serde(default)]
+    pub sha1: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct VersionDownloads {
+    #[serde(default)]
+    pub client: Option<Artifact>,
+}
+
+// Version metadata as stored in versions/<id>/<id>.json
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionJson {
+    pub id: String,
+    #[serde(default)]
+    pub inherits_from: Option<String>,
+    // Version whose client jar is used, set for inherited profiles
+    #[serde(default)]
+    pub jar: Option<String>,
+    #[serde(default)]
+    pub main_class: Option<String>,
+    // Pre-1.13 game arguments as a single string
+    #[serde(default)]
+    pub minecraft_arguments: Option<String>,
+    #[serde(default)]
+    pub arguments: Option<Arguments>,
+    #[serde(default)]
+    pub libraries: Vec<Library>,
+    #[serde(default)]
+    pub asset_index: Option<AssetIndexRef>,
+    #[serde(default)]
+    pub assets: Option<String>,
+    #[serde(default)]
+    pub downloads: Option<VersionDownloads>,
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AssetObject {
+    pub hash: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AssetIndex {
+    pub objects: HashMap<String, AssetObject>,
+    // Pre-1.7 indexes expect assets by name under assets/virtual/<id>
+    #[serde(default, rename = "virtual")]
+    pub is_virtual: bool,
+    #[serde(default)]
+    pub map_to_resources: bool,
+}
+
+// Progress of an install, reported per stage
+#[derive(Debug, Serialize, Clone)]
+pub struct InstallProgress {
+    pub version: String,
+    pub stage: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+pub fn versions_path(data_path: &Path) -> PathBuf {
+    data_path.join("versions")
+}
+
+pub fn libraries_path(data_path: &Path) -> PathBuf {
+    data_path.join("libraries")
+}
+
+pub fn assets_path(data_path: &Path) -> PathBuf {
+    data_path.join("assets")
+}
+
+pub fn version_json_path(data_path: &Path, id: &str) -> PathBuf {
+    versions_path(data_path).join(id).join(format!("{}.json", id))
+}
+
+pub fn version_jar_path(data_path: &Path, id: &str) -> PathBuf {
+    versions_path(data_path).join(id).join(format!("{}.jar", id))
+}
+
+// OS name as used in version rules
+pub fn current_os_name() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "osx",
+        other => other,
+    }
+}
+
+impl Rule {
+    // Whether the rule's conditions hold here with the given optional features
+    // (quick play, custom resolution, ...) enabled
+    fn matches(&self, features: &[&str]) -> bool {
+        if let Some(os) = &self.os {
+            if os.name.as_deref().is_some_and(|name| name != current_os_name()) {
+                return false;
+            }
+            if os.arch.as_deref().is_some_and(|arch| arch == "x86") && cfg!(not(target_arch = "x86")) {
+                return false;
+            }
+        }
+        if let Some(required) = &self.features {
+            if required.iter().any(|(name, enabled)| features.contains(&name.as_str()) != *enabled) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// Evaluate rules the way the vanilla launcher does: the last matching rule wins,
+// no rules means allowed
+pub fn rules_allow_with_features(rules: &[Rule], features: &[&str]) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+
+    let mut allowed = false;
+    for rule in rules.iter().filter(|rule| rule.matches(features)) {
+        allowed = rule.action == "allow";
+    }
+    allowed
+}
+
+// Evaluate rules with no optional features enabled
+pub fn rules_allow(rules: &[Rule]) -> bool {
+    rules_allow_with_features(rules, &[])
+}
+
+// Maven path for coordinates, "org.ow2.asm:asm:9.6" -> org/ow2/asm/asm/9.6/asm-9.6.jar
+pub fn maven_path(name: &str) -> Option<String> {
+    let (coordinates, extension) = name.split_once('@').unwrap_or((name, "jar"));
+    let parts: Vec<&str> = coordinates.split(':').collect();
+    let (group, artifact, version) = match parts.as_slice() {
+        [group, artifact, version, ..] => (group, artifact, version),
+        _ => return None,
+    };
+
+    let file_name = match parts.get(3) {
+        Some(classifier) => format!("{}-{}-{}.{}", artifact, version, classifier, extension),
+        None => format!("{}-{}.{}", artifact, version, extension),
+    };
+    Some(format!("{}/{}/{}/{}", group.replace('.', "/"), artifact, version, file_name))
+}
+
+impl Library {
+    // Main artifact of the library, if it has one
+    pub fn artifact(&self) -> Option<Artifact> {
+        if let Some(artifact) = self.downloads.as_ref().and_then(|downloads| downloads.artifact.clone()) {
+            return Some(artifact);
+        }
+        // Libraries with only native classifiers have no main artifact
+        if self.downloads.is_some() || self.natives.is_some() {
+            return None;
+        }
+
+        let path = maven_path(&self.name)?;
+        let repository = self.url.clone().unwrap_or_else(|| LIBRARIES_URL.to_string());
+        let separator = if repository.ends_with('/') { "" } else { "/" };
+        Some(Artifact {
+            url: format!("{}{}{}", repository, separator, path),
+            path: Some(path),
+            sha1: self.sha1.clone(),
+        })
+    }
+
+    // Native classifier artifact for this OS (pre-1.19 layout)
+    pub fn native_artifact(&self) -> Option<Artifact> {
+        let classifier = self
+            .natives
+            .as_ref()?
+            .get(current_os_name())?
+            .replace("${arch}", if cfg!(target_pointer_width = "64") { "64" } else { "32" });
+        self.downloads.as_ref()?.classifiers.get(&classifier).cloned()
+    }
+}
+
+// Path of an artifact inside the libraries directory
+pub fn artifact_path(data_path: &Path, library: &Library, artifact: &Artifact) -> Option<PathBuf> {
+    let relative = artifact.path.clone().or_else(|| maven_path(&library.name))?;
+    Some(libraries_path(data_path).join(relative))
+}
+
+// Load a version, merging it with the version it inherits from
+pub fn load_version(data_path: &Path, id: &str) -> InstallResult<VersionJson> {
+    load_inherited(data_path, id, &mut Vec::new())
+}
+
+// Load a version of an inheritsFrom chain, `visited` holds the ids below it
+fn load_inherited(data_path: &Path, id: &str, visited: &mut Vec<String>) -> InstallResult<VersionJson> {
+    if visited.iter().any(|seen| seen == id) {
+        return Err(format!("Version {} inherits from itself ({} -> {})", id, visited.join(" -> "), id).into());
+    }
+    if visited.len() >= MAX_INHERITANCE_DEPTH {
+        return Err(format!("Version {} inherits from too many versions ({})", visited[0], visited.join(" -> ")).into());
+    }
+    visited.push(id.to_string());
+
+    let contents = fs::read_to_string(version_json_path(data_path, id))
+        .map_err(|e| format!("Version {} is not installed ({})", id, e))?;
+    let version: VersionJson = serde_json::from_str(&contents)?;
+
+    match version.inherits_from.clone() {
+        Some(parent_id) => Ok(merge_versions(load_inherited(data_path, &parent_id, visited)?, version)),
+        None => Ok(version),
+    }
+}
+
+// Layer a child profile over its parent: child libraries take precedence and
+// arguments are appended
+fn merge_versions(parent: VersionJson, child: VersionJson) -> VersionJson {
+    let library_key = |library: &Library| {
+        let mut parts = library.name.split(':');
+        format!("{}:{}", parts.next().unwrap_or_default(), parts.next().unwrap_or_default())
+    };
+    let mut libraries = child.libraries.clone();
+    for library in parent.libraries {
+        let key = library_key(&library);
+        if library.natives.is_some() || !child.libraries.iter().any(|own| library_key(own) == key) {
+            libraries.push(library);
+        }
+    }
+
+    let arguments = match (parent.arguments, child.arguments) {
+        (Some(mut parent_args), Some(child_args)) => {
+            parent_args.game.extend(child_args.game);
+            parent_args.jvm.extend(child_args.jvm);
+            Some(parent_args)
+        },
+        (parent_args, child_args) => child_args.or(parent_args),
+    };
+
+    VersionJson {
+        jar: child.jar.or(parent.jar).or(Some(parent.id)),
+        id: child.id,
+        inherits_from: None,
+        main_class: child.main_class.or(parent.main_class),
+        minecraft_arguments: child.minecraft_arguments.or(parent.minecraft_arguments),
+        arguments,
+        libraries,
+        asset_index: child.asset_index.or(parent.asset_index),
+        assets: child.assets.or(parent.assets),
+        downloads: child.downloads.or(parent.downloads),
+        kind: child.kind.or(parent.kind),
+    }
+}
+
+// version.json from the client jar of an installed game version (1.14 and later)
+fn jar_version_info(data_path: &Path, id: &str) -> InstallResult<Option<serde_json::Value>> {
+    let version = load_version(data_path, id)?;
+    let jar_id = version.jar.unwrap_or(version.id);
+    let mut archive = zip::ZipArchive::new(fs::File::open(version_jar_path(data_path, &jar_id))?)?;
+
+    let Ok(file) = archive.by_name("version.json") else { return Ok(None) };
+    Ok(Some(serde_json::from_reader(file)?))
+}
+
+// World data version of an installed game version
+pub fn data_version(data_path: &Path, id: &str) -> InstallResult<Option<i64>> {
+    Ok(jar_version_info(data_path, id)?.and_then(|info| info["world_version"].as_i64()))
+}
+
+// Resource pack format of an installed game version. A plain number before
+// 1.18, then {"resource": n, "data": n}, and major/minor pairs since 1.21.9
+pub fn resource_pack_format(data_path: &Path, id: &str) -> InstallResult<Option<i64>> {
+    let Some(info) = jar_version_info(data_path, id)? else { return Ok(None) };
+    let pack_version = &info["pack_version"];
+    Ok(pack_version
+        .as_i64()
+        .or_else(|| pack_version["resource"].as_i64())
+        .or_else(|| pack_version["resource_major"].as_i64()))
+}
+
+pub fn sha1_hex(contents: &[u8]) -> String {
+    hex::encode(Sha1::digest(contents))
+}
+
+// Whether a value looks like a hex encoded SHA-1 hash
+pub fn is_sha1_hex(value: &str) -> bool {
+    value.len() == 40 && value.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+// Whether a path is relative and stays inside the directory it is joined to
+fn is_contained_path(value: &str) -> bool {
+    !value.is_empty() && Path::new(value).components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+// Whether a file exists and matches the expected hash, when there is one
+fn is_valid_file(path: &Path, sha1: Option<&str>) -> bool {
+    match sha1 {
+        Some(expected) => fs::read(path).is_ok_and(|contents| sha1_hex(&contents).eq_ignore_ascii_case(expected)),
+        None => path.is_file(),
+    }
+}
+
+// Hash published next to an artifact in a Maven repository, for loader
+// libraries whose profile doesn't list one
+async fn fetch_maven_sha1(client: &reqwest::Client, url: &str) -> InstallResult<String> {
+    let response = client.get(format!("{}.sha1", url)).send().await?.error_for_status()?;
+    let text = response.text().await?;
+    // Some repositories append the file name after the hash
+    let hash = text.split_whitespace().next().unwrap_or_default();
+    if !is_sha1_hex(hash) {
+        return Err(format!("No valid checksum published for {}", url).into());
+    }
+    Ok(hash.to_string())
+}
+
+// Download a file unless a valid copy is already present, verifying its hash
+async fn download_file(client: &reqwest::Client, url: &str, dest: &Path, sha1: Option<&str>) -> InstallResult<()> {
+    if is_valid_file(dest, sha1) {
+        return Ok(());
+    }
+
+    let bytes = client.get(url).send().await?.error_for_status()?.bytes().await?;
+    if let Some(expected) = sha1 {
+        let actual = sha1_hex(&bytes);
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!("Checksum mismatch for {}: expected {}, got {}", url, expected, actual).into());
+        }
+    }
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = dest.as_os_str().to_os_string();
+    tmp_name.push(".part");
+    fs::write(&tmp_name, &bytes)?;
+    fs::rename(&tmp_name, dest)?;
+    Ok(())
+}
+
+// A file to download as part of an install. Files without a hash are
+// libraries from a Maven repository, which publishes one next to them
+struct Download {
+    url: String,
+    dest: PathBuf,
+    sha1: Option<String>,
+}
+
+// Download files in batches, reporting progress after each batch
+async fn download_all(
+    client: &reqwest::Client,
+    downloads: Vec<Download>,
+    report: impl Fn(usize, usize),
+) -> InstallResult<()> {
+    let total = downloads.len();
+    let mut done = 0;
+    report(done, total);
+
+    for batch in downloads.chunks(DOWNLOAD_CONCURRENCY) {
+        let handles: Vec<_> = batch
+            .iter()
+            .map(|download| {
+                let client = client.clone();
+                let url = download.url.clone();
+                let dest = download.dest.clone();
+                let sha1 = download.sha1.clone();
+                tauri::async_runtime::spawn(async move {
+                    let sha1 = match sha1 {
+                        Some(sha1) => sha1,
+                        None => fetch_maven_sha1(&client, &url).await.map_err(|e| format!("{}: {}", url, e))?,
+                    };
+                    download_file(&client, &url, &dest, Some(&sha1))
+                        .await
+                        .map_err(|e| format!("{}: {}", url, e))
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.await??;
+        }
+
+        done += batch.len();
+        report(done, total);
+    }
+
+    Ok(())
+}
+
+// Fetch Mojang's version manifest
+pub async fn fetch_manifest() -> InstallResult<VersionManifest> {
+    let response = reqwest::get(VERSION_MANIFEST_URL).await?.error_for_status()?;
+    Ok(response.json().await?)
+}
+
+// Install a version with its libraries and assets. "latest" and "snapshot"
+// resolve to the newest release and snapshot. Returns the installed version id
+pub async fn install_version(
+    data_path: &Path,
+    version: &str,
+    progress: &(dyn Fn(InstallProgress) + Send + Sync),
+) -> InstallResult<String> {
+    let client = reqwest::Client::new();
+    let manifest = fetch_manifest().await?;
+    let id = match version {
+        "latest" | "release" => manifest.latest.release.clone(),
+        "snapshot" => manifest.latest.snapshot.clone(),
+        other => other.to_string(),
+    };
+    let entry = manifest
+        .versions
+        .iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("Unknown Minecraft version: {}", id))?;
+
+    let report = |stage: &str, done: usize, total: usize| {
+        progress(InstallProgress { version: id.clone(), stage: stage.to_string(), done, total });
+    };
+
+    // Version metadata
+    report("metadata", 0, 1);
+    let json_path = version_json_path(data_path, &id);
+    download_file(&client, &entry.url, &json_path, entry.sha1.as_deref()).await?;
+    let version_json: VersionJson = serde_json::from_str(&fs::read_to_string(&json_path)?)?;
+    report("metadata", 1, 1);
+
+    // Client jar and libraries
+    let mut downloads = Vec::new();
+    if let Some(client_jar) = version_json.downloads.as_ref().and_then(|downloads| downloads.client.clone()) {
+        downloads.push(Download {
+            url: client_jar.url,
+            dest: version_jar_path(data_path, &id),
+            sha1: client_jar.sha1,
+        });
+    }
+    for library in version_json.libraries.iter().filter(|library| rules_allow(&library.rules)) {
+        for artifact in library.artifact().into_iter().chain(library.native_artifact()) {
+            if let Some(dest) = artifact_path(data_path, library, &artifact) {
+                downloads.push(Download { url: artifact.url, dest, sha1: artifact.sha1 });
+            }
+        }
+    }
+    download_all(&client, downloads, |done, total| report("libraries", done, total)).await?;
+
+    // Assets
+    if let Some(asset_index) = &version_json.asset_index {
+        install_assets(&client, data_path, asset_index, |done, total| report("assets", done, total)).await?;
+    }
+
+    Ok(id)
+}
+
+// Download an asset index and its objects
+async fn install_assets(
+    client: &reqwest::Client,
+    data_path: &Path,
+    asset_index: &AssetIndexRef,
+    report: impl Fn(usize, usize),
+) -> InstallResult<()> {
+    let assets = assets_path(data_path);
+    let index_path = assets.join("indexes").join(format!("{}.json", asset_index.id));
+    download_file(client, &asset_index.url, &index_path, asset_index.sha1.as_deref()).await?;
+    let index: AssetIndex = serde_json::from_str(&fs::read_to_string(&index_path)?)?;
+
+    // Objects are named after their SHA-1 hash
+    if let Some((name, object)) = index.objects.iter().find(|(_, object)| !is_sha1_hex(&object.hash)) {
+        return Err(format!("Asset index {} has an invalid hash for {}: {:?}", asset_index.id, name, object.hash).into());
+    }
+    // Names become paths under virtual/ for old versions
+    if let Some(name) = index.objects.keys().find(|name| !is_contained_path(name)) {
+        return Err(format!("Asset index {} has an invalid object name: {}", asset_index.id, name).into());
+    }
+    let object_path = |hash: &str| assets.join("objects").join(&hash[..2]).join(hash);
+    let downloads = index
+        .objects
+        .values()
+        .map(|object| Download {
+            url: format!("{}/{}/{}", RESOURCES_URL, &object.hash[..2], object.hash),
+            dest: object_path(&object.hash),
+            sha1: Some(object.hash.clone()),
+        })
+        .collect();
+    download_all(client, downloads, report).await?;
+
+    // Old versions read assets by name instead of by hash
+    if index.is_virtual || index.map_to_resources {
+        let virtual_dir = assets.join("virtual").join(&asset_index.id);
+        for (name, object) in &index.objects {
+            let dest = virtual_dir.join(name);
+            if dest.exists() {
+                continue;
+            }
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(object_path(&object.hash), dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_version(data_path: &Path, id: &str, inherits_from: Option<&str>) {
+        let path = version_json_path(data_path, id);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let json = serde_json::json!({ "id": id, "inheritsFrom": inherits_from, "libraries": [] });
+        fs::write(path, json.to_string()).unwrap();
+    }
+
+    #[test]
+    fn inheritance_cycles_are_rejected() {
+        let data_path = std::env::temp_dir().join(format!("intelligence-versions-{}", std::process::id()));
+        write_version(&data_path, "fabric", Some("1.21"));
+        write_version(&data_path, "1.21", None);
+        write_version(&data_path, "loop-a", Some("loop-b"));
+        write_version(&data_path, "loop-b", Some("loop-a"));
+        write_version(&data_path, "self", Some("self"));
+
+        let fabric = load_version(&data_path, "fabric").unwrap();
+        assert_eq!(fabric.jar.as_deref(), Some("1.21"));
+        assert!(load_version(&data_path, "loop-a").unwrap_err().to_string().contains("inherits from itself"));
+        assert!(load_version(&data_path, "self").is_err());
+        fs::remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn sha1_hex_values() {
+        assert!(is_sha1_hex(&sha1_hex(b"")));
+        assert!(!is_sha1_hex("da39a3ee"));
+        assert!(!is_sha1_hex("zz39a3ee5e6b4b0d3255bfef95601890afd80709"));
+        assert!(!is_sha1_hex("é9a3ee5e6b4b0d3255bfef95601890afd807099"));
+    }
+
+    #[test]
+    fn asset_names_stay_inside_the_assets_dir() {
+        assert!(is_contained_path("minecraft/sounds/ambient/cave/cave1.ogg"));
+        assert!(is_contained_path("icons/icon_16x16.png"));
+        assert!(!is_contained_path(""));
+        assert!(!is_contained_path("../../x"));
+        assert!(!is_contained_path("minecraft/../../x"));
+        assert!(!is_contained_path("/etc/passwd"));
+        assert!(!is_contained_path("./pack.mcmeta"));
+    }
+}
@@ -0,0 +1,238 @@
+// Watches settings.json, the languages directory and its overrides for external edits
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    tray, apply_settings, changed_settings_keys, emit_settings_changed, get_language_file_path,
+    get_language_overrides_path, get_languages_path, list_languages, load_language, read_settings_file,
+    validate_settings, AppState, AvailableLanguage, Language,
+};
+
+// How often watched files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Payload of the `watch-error` event, sent when an external edit is rejected
+#[derive(Debug, Serialize, Clone)]
+pub struct WatchError {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+// Payload of the `languages-changed` event
+#[derive(Debug, Serialize, Clone)]
+pub struct LanguagesChanged {
+    pub languages: Vec<AvailableLanguage>,
+    pub current: Language,
+}
+
+// Get modification time of a file, if it exists
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// Get modification times of all JSON files in a directory
+fn snapshot_dir(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    let mut snapshot = HashMap::new();
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(modified) = modified_time(&path) {
+                    snapshot.insert(path, modified);
+                }
+            }
+        }
+    }
+
+    snapshot
+}
+
+// List files that were added, removed or modified between two snapshots
+fn changed_files(old: &HashMap<PathBuf, SystemTime>, new: &HashMap<PathBuf, SystemTime>) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = new
+        .iter()
+        .filter(|(path, modified)| old.get(*path) != Some(*modified))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(old.keys().filter(|path| !new.contains_key(*path)).cloned());
+    changed
+}
+
+// Report a rejected external edit to the frontend
+fn emit_watch_error(app_handle: &AppHandle, path: &Path, error: String) {
+    eprintln!("Ignoring invalid edit of {:?}: {}", path, error);
+
+    let payload = WatchError { path: path.to_path_buf(), error };
+    if let Err(e) = app_handle.emit("watch-error", payload) {
+        eprintln!("Failed to emit watch error event: {}", e);
+    }
+}
+
+// Reload settings.json after an external edit
+fn reload_settings(app_handle: &AppHandle, path: &Path) {
+    // An external edit with invalid values is rejected as a whole rather than
+    // applied with some fields silently reset
+    let settings = match read_settings_file(path) {
+        Ok((settings, reset_fields)) if reset_fields.is_empty() => settings,
+        Ok((_, reset_fields)) => {
+            return emit_watch_error(app_handle, path, format!("Invalid settings values: {}", reset_fields.join(", ")))
+        },
+        Err(e) => return emit_watch_error(app_handle, path, e.to_string()),
+    };
+
+    // The same checks as changes made in the launcher
+    if let Err(e) = validate_settings(app_handle, &settings) {
+        return emit_watch_error(app_handle, path, e.to_string());
+    }
+
+    let state = app_handle.state::<AppState>();
+    let change = {
+        let mut current_settings = state.settings.lock().unwrap();
+
+        // Our own saves also touch the file, those produce no changes
+        let changed = changed_settings_keys(&current_settings, &settings);
+        if changed.is_empty() {
+            return;
+        }
+
+        match apply_settings(app_handle, &state, &mut current_settings, settings, changed) {
+            Ok(change) => change,
+            Err(e) => return emit_watch_error(app_handle, path, e.to_string()),
+        }
+    };
+
+    println!("Reloaded settings after external edit: {}", change.changed.join(", "));
+    emit_settings_changed(app_handle, &change);
+}
+
+// Reload languages after files in the languages directory changed
+fn reload_languages(app_handle: &AppHandle, changed: &[PathBuf]) {
+    let state = app_handle.state::<AppState>();
+    let lang_code = state.settings.lock().unwrap().language.clone();
+
+    // Reject broken edits to the active language instead of replacing it with defaults
+    if let Ok(current_path) = get_language_file_path(app_handle, &lang_code) {
+        if changed.contains(&current_path) && current_path.exists() {
+            let parsed = fs::read_to_string(&current_path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str::<Language>(&content).map_err(|e| e.to_string()));
+            if let Err(e) = parsed {
+                return emit_watch_error(app_handle, &current_path, e);
+            }
+        }
+    }
+
+    let current = match load_language(app_handle, &lang_code) {
+        Ok(language) => language,
+        Err(e) => {
+            eprintln!("Failed to reload language {}: {}", lang_code, e);
+            return;
+        }
+    };
+    *state.current_language.lock().unwrap() = current.clone();
+    tray::refresh(app_handle);
+
+    let languages = list_languages(app_handle).unwrap_or_default();
+    if let Err(e) = app_handle.emit("languages-changed", LanguagesChanged { languages, current }) {
+        eprintln!("Failed to emit languages change event: {}", e);
+    }
+}
+
+// Start a background thread polling settings and language files for changes
+pub fn spawn(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let settings_path = match app_handle.state::<AppState>().settings_path.lock().unwrap().clone() {
+            Some(path) => path,
+            None => return,
+        };
+        let languages_path = match get_languages_path(&app_handle) {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("Not watching languages directory: {}", e);
+                return;
+            }
+        };
+
+        let overrides_path = get_language_overrides_path(&app_handle).unwrap_or_else(|_| languages_path.join("overrides"));
+        let snapshot_languages = || {
+            let mut snapshot = snapshot_dir(&languages_path);
+            snapshot.extend(snapshot_dir(&overrides_path));
+            snapshot
+        };
+
+        let mut settings_modified = modified_time(&settings_path);
+        let mut languages_snapshot = snapshot_languages();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let modified = modified_time(&settings_path);
+            if modified != settings_modified {
+                settings_modified = modified;
+                // A missing file is recreated on the next save, nothing to reload
+                if modified.is_some() {
+                    reload_settings(&app_handle, &settings_path);
+                }
+            }
+
+            let snapshot = snapshot_languages();
+            let changed = changed_files(&languages_snapshot, &snapshot);
+            languages_snapshot = snapshot;
+            if !changed.is_empty() {
+                reload_languages(&app_handle, &changed);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, u64)]) -> HashMap<PathBuf, SystemTime> {
+        entries
+            .iter()
+            .map(|(path, secs)| (PathBuf::from(path), SystemTime::UNIX_EPOCH + Duration::from_secs(*secs)))
+            .collect()
+    }
+
+    #[test]
+    fn changed_files_lists_added_removed_and_modified() {
+        let old = snapshot(&[("de_DE.json", 1), ("fr_FR.json", 1), ("ru_RU.json", 1)]);
+        let new = snapshot(&[("de_DE.json", 1), ("fr_FR.json", 2), ("uk_UA.json", 1)]);
+
+        let mut changed = changed_files(&old, &new);
+        changed.sort();
+        assert_eq!(changed, vec![PathBuf::from("fr_FR.json"), PathBuf::from("ru_RU.json"), PathBuf::from("uk_UA.json")]);
+        assert!(changed_files(&old, &old).is_empty());
+        assert_eq!(changed_files(&HashMap::new(), &old).len(), 3);
+    }
+
+    #[test]
+    fn snapshot_only_includes_json_files() {
+        let dir = std::env::temp_dir().join(format!("intelligence-watcher-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("overrides")).unwrap();
+        fs::write(dir.join("de_DE.json"), "{}").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        fs::write(dir.join("de_DE.json.tmp"), "").unwrap();
+
+        let snapshot = snapshot_dir(&dir);
+        let mut paths: Vec<&PathBuf> = snapshot.keys().collect();
+        paths.sort();
+        assert_eq!(paths, vec![&dir.join("de_DE.json")]);
+        assert!(snapshot_dir(&dir.join("missing")).is_empty());
+
+        // Rewriting a file with a new modification time shows up as a change
+        let modified = SystemTime::now() + Duration::from_secs(10);
+        fs::File::options().write(true).open(dir.join("de_DE.json")).unwrap().set_modified(modified).unwrap();
+        assert_eq!(changed_files(&snapshot, &snapshot_dir(&dir)), vec![dir.join("de_DE.json")]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
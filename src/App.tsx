@@ -1,11 +1,12 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useState, useEffect } from 'react';
 import SplashScreen from './components/SplashScreen';
 import Titlebar from './components/Titlebar';
 import Sidebar from './components/Sidebar';
 import MainContent from './components/MainContent';
 import SettingsModal from './components/SettingsModal';
-import { Settings, ThemeClasses } from './types/interfaces';
+import { DeepLink, DeepLinkFailed, Settings, SettingsChanged, SettingsRecovery, ThemeClasses, TranslateFunction } from './types/interfaces';
 
 // Интерфейс для тултипа
 interface TooltipData {
@@ -26,7 +27,7 @@ function App() {
   const [selectedTitlebarStyle, setSelectedTitlebarStyle] = useState('custom');
   const [isLoading, setIsLoading] = useState(true);
   const [loadingProgress, setLoadingProgress] = useState(0);
-  const [currentLanguage, setCurrentLanguage] = useState<string>('en_US');
+  const [, setCurrentLanguage] = useState<string>('en_US');
   const [availableLanguages, setAvailableLanguages] = useState<any[]>([]);
   const [translations, setTranslations] = useState<{ [key: string]: string }>({});
 
@@ -41,9 +42,10 @@ function App() {
 
   // Add state for advanced rendering
   const [advancedRendering, setAdvancedRendering] = useState(true);
+  const [minimizeToTray, setMinimizeToTray] = useState(false);
 
   // Функция для перевода текста с использованием загруженных переводов
-  const t = (key: string, defaultValue?: string): string => {
+  const t: TranslateFunction = (key, defaultValue) => {
     return translations[key] || defaultValue || key;
   };
 
@@ -90,6 +92,7 @@ function App() {
             setDiscordRpcEnabled(settings.discord_rpc);
             setCurrentLanguage(settings.language);
             setAdvancedRendering(settings.advanced_rendering);
+            setMinimizeToTray(settings.minimize_to_tray);
             // Set titlebar style if available in settings, otherwise use 'custom' by default
             setSelectedTitlebarStyle(settings.titlebar_style || 'custom');
 
@@ -130,25 +133,101 @@ function App() {
     loadSystemAndSettings();
   }, []);
 
-  // Function to save settings through Rust backend
-  const updateSettings = async (settings: Settings) => {
+  // Warn when the backend had to recover settings from a backup or defaults
+  useEffect(() => {
+    const unlisten = listen<SettingsRecovery>('settings-recovered', (event) => {
+      if (event.payload.source === 'fields') {
+        console.warn('Invalid settings were reset to defaults:', event.payload.reset_fields.join(', '));
+        return;
+      }
+      console.warn(`Settings file was corrupted and restored from ${event.payload.source}:`, event.payload.error);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // intelligence:// links resolved by the backend, launches need the user's consent
+  // since any website can open a link
+  useEffect(() => {
+    const unlisten = listen<DeepLink>('deep-link', async (event) => {
+      const link = event.payload;
+      // Modpack links are reported through deep-link-failed until installs are supported
+      if (link.action === 'install_modpack') {
+        return;
+      }
+
+      const question = link.action === 'launch'
+        ? await invoke<string>('format_message', { key: 'deep_link.confirm_launch', args: { instance: link.instance } })
+        : await invoke<string>('format_message', { key: 'deep_link.confirm_join', args: { instance: link.instance, address: link.address } });
+      if (!window.confirm(question)) {
+        return;
+      }
+
+      try {
+        await invoke('launch_instance', {
+          instanceId: link.instance,
+          server: link.action === 'join_server' ? link.address : null,
+        });
+      } catch (error) {
+        console.error('Failed to launch from link:', error);
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Links the backend could not open, including unsupported ones
+  useEffect(() => {
+    const unlisten = listen<DeepLinkFailed>('deep-link-failed', async (event) => {
+      console.error('Failed to open link', event.payload.link, event.payload.message);
+      window.alert(await invoke<string>('format_message', { key: 'deep_link.failed', args: { message: event.payload.message } }));
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Function to save changed settings through Rust backend
+  const updateSettings = async (patch: Partial<Settings>) => {
     try {
-      await invoke('update_settings', { settings });
+      await invoke('patch_settings', { patch });
     } catch (error) {
       console.error('Failed to save settings:', error);
     }
   };
 
+  // Keep state in sync with settings changed by other windows
+  useEffect(() => {
+    const unlisten = listen<SettingsChanged>('settings-changed', (event) => {
+      const settings = event.payload.settings;
+      setSelectedTheme(settings.theme);
+      setDiscordRpcEnabled(settings.discord_rpc);
+      setCurrentLanguage(settings.language);
+      setAdvancedRendering(settings.advanced_rendering);
+      setMinimizeToTray(settings.minimize_to_tray);
+      setSelectedTitlebarStyle(settings.titlebar_style);
+      if (event.payload.changed.includes('theme')) {
+        applyTheme(settings.theme);
+      }
+      if (event.payload.restart_required.length > 0) {
+        console.info('Restart required to apply:', event.payload.restart_required.join(', '));
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Handle theme change
   const handleThemeChange = (theme: string) => {
     setSelectedTheme(theme);
-    updateSettings({
-      theme,
-      discord_rpc: discordRpcEnabled,
-      advanced_rendering: advancedRendering,
-      language: currentLanguage,
-      titlebar_style: selectedTitlebarStyle
-    });
+    updateSettings({ theme });
 
     // Apply theme to document
     applyTheme(theme);
@@ -158,38 +237,27 @@ function App() {
   const handleDiscordRpcToggle = () => {
     const newValue = !discordRpcEnabled;
     setDiscordRpcEnabled(newValue);
-    updateSettings({
-      theme: selectedTheme,
-      discord_rpc: newValue,
-      advanced_rendering: advancedRendering,
-      language: currentLanguage,
-      titlebar_style: selectedTitlebarStyle
-    });
+    updateSettings({ discord_rpc: newValue });
   };
 
   // Handle Advanced Rendering toggle
   const handleAdvancedRenderingToggle = () => {
     const newValue = !advancedRendering;
     setAdvancedRendering(newValue);
-    updateSettings({
-      theme: selectedTheme,
-      discord_rpc: discordRpcEnabled,
-      advanced_rendering: newValue,
-      language: currentLanguage,
-      titlebar_style: selectedTitlebarStyle
-    });
+    updateSettings({ advanced_rendering: newValue });
+  };
+
+  // Handle minimize to tray toggle
+  const handleMinimizeToTrayToggle = () => {
+    const newValue = !minimizeToTray;
+    setMinimizeToTray(newValue);
+    updateSettings({ minimize_to_tray: newValue });
   };
 
   // Handle titlebar style change
   const handleTitlebarStyleChange = (style: string) => {
     setSelectedTitlebarStyle(style);
-    updateSettings({
-      theme: selectedTheme,
-      discord_rpc: discordRpcEnabled,
-      advanced_rendering: advancedRendering,
-      language: currentLanguage,
-      titlebar_style: style
-    });
+    updateSettings({ titlebar_style: style });
   };
 
   // Function to apply theme
@@ -368,8 +436,10 @@ function App() {
         handleThemeChange={handleThemeChange}
         handleTitlebarStyleChange={handleTitlebarStyleChange}
         handleAdvancedRenderingToggle={handleAdvancedRenderingToggle}
+        handleMinimizeToTrayToggle={handleMinimizeToTrayToggle}
         t={t}
         advancedRendering={advancedRendering}
+        minimizeToTray={minimizeToTray}
       />
     </div>
   );
@@ -1,5 +1,6 @@
 import React from 'react';
 import ThemeCard from './ThemeCard';
+import { TranslateFunction } from '../types/interfaces';
 
 interface AppearanceSettingsProps {
   themeClasses: {
@@ -16,7 +17,9 @@ interface AppearanceSettingsProps {
   handleTitlebarStyleChange: (style: string) => void;
   handleAdvancedRenderingToggle?: () => void;
   advancedRendering?: boolean;
-  t: (key: string, defaultValue?: string) => string;
+  handleMinimizeToTrayToggle?: () => void;
+  minimizeToTray?: boolean;
+  t: TranslateFunction;
 }
 
 const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
@@ -27,6 +30,8 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
   handleTitlebarStyleChange,
   handleAdvancedRenderingToggle,
   advancedRendering,
+  handleMinimizeToTrayToggle,
+  minimizeToTray,
   t
 }) => {
   return (
@@ -41,6 +46,7 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
           theme="dark"
           selectedTheme={selectedTheme}
           title="Dark"
+          titleKey="settings.appearance.theme.dark"
           themeClasses={themeClasses}
           t={t}
           onClick={() => handleThemeChange('dark')}
@@ -65,6 +71,7 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
           theme="light"
           selectedTheme={selectedTheme}
           title="Light"
+          titleKey="settings.appearance.theme.light"
           themeClasses={themeClasses}
           t={t}
           onClick={() => handleThemeChange('light')}
@@ -97,6 +104,7 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
           theme="oled"
           selectedTheme={selectedTheme}
           title="OLED"
+          titleKey="settings.appearance.theme.oled"
           themeClasses={themeClasses}
           t={t}
           onClick={() => handleThemeChange('oled')}
@@ -116,6 +124,7 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
           theme="system"
           selectedTheme={selectedTheme}
           title="Sync"
+          titleKey="settings.appearance.theme.sync"
           themeClasses={themeClasses}
           t={t}
           onClick={() => handleThemeChange('system')}
@@ -146,12 +155,9 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
           theme="custom"
           selectedTheme={selectedTitlebarStyle}
           title="Custom"
+          titleKey="settings.appearance.titlebar.custom"
           themeClasses={themeClasses}
-          t={(key, defaultValue) => {
-            // Adjust the key for titlebar styles
-            const adjustedKey = key.replace('theme', 'titlebar');
-            return t(adjustedKey, defaultValue);
-          }}
+          t={t}
           onClick={() => handleTitlebarStyleChange('custom')}
           previewContent={
             <div className={`${themeClasses.secondaryBackground} p-2 rounded-t-2xl mb-0 h-[50px] relative overflow-hidden border-b ${themeClasses.border} flex items-center justify-center`}>
@@ -183,12 +189,9 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
           theme="native"
           selectedTheme={selectedTitlebarStyle}
           title="Native"
+          titleKey="settings.appearance.titlebar.native"
           themeClasses={themeClasses}
-          t={(key, defaultValue) => {
-            // Adjust the key for titlebar styles
-            const adjustedKey = key.replace('theme', 'titlebar');
-            return t(adjustedKey, defaultValue);
-          }}
+          t={t}
           onClick={() => handleTitlebarStyleChange('native')}
           previewContent={
             <div className="bg-[#f0f0f0] p-2 rounded-t-2xl mb-0 h-[50px] relative overflow-hidden flex items-center justify-center">
@@ -261,6 +264,38 @@ const AppearanceSettings: React.FC<AppearanceSettingsProps> = ({
             
           </div>
         </div>
+        
+        {/* Minimize to tray card */}
+        <div className={`p-4 rounded-2xl ${themeClasses.cardBackground} border ${themeClasses.border} shadow-sm`}>
+          <div className="flex items-center justify-between mb-3">
+            <div className="flex items-center">
+              <svg xmlns="http://www.w3.org/2000/svg" width="18" height="18" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round" className={`${themeClasses.text} mr-2`}>
+                <rect x="3" y="3" width="18" height="18" rx="2"></rect>
+                <path d="M7 15h10"></path>
+              </svg>
+              <h3 className={`font-medium ${themeClasses.text}`}>{t('settings.appearance.minimize_to_tray', 'Minimize to tray')}</h3>
+            </div>
+            <div className="relative">
+              <button
+                onClick={handleMinimizeToTrayToggle}
+                className={`w-12 h-6 rounded-full ${
+                  minimizeToTray ? 'bg-green-500' : 'bg-gray-400'
+                } transition-colors duration-200 focus:outline-none`}
+                aria-label={t('settings.appearance.toggle_minimize_to_tray', 'Toggle minimize to tray')}
+              >
+                <span
+                  className={`block w-4 h-4 bg-white rounded-full transition-transform duration-200 ${
+                    minimizeToTray ? 'translate-x-7' : 'translate-x-1'
+                  }`}
+                />
+              </button>
+            </div>
+          </div>
+          
+          <p className={`text-sm ${themeClasses.secondaryText}`}>
+            {t('settings.appearance.minimize_to_tray_desc', 'Keep Intelligence running in the system tray when the window is closed.')}
+          </p>
+        </div>
       </div>
     </div>
   );
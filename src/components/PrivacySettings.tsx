@@ -1,4 +1,5 @@
 import React from 'react';
+import { TranslateFunction } from '../types/interfaces';
 
 interface PrivacySettingsProps {
   themeClasses: {
@@ -9,7 +10,7 @@ interface PrivacySettingsProps {
     secondaryText: string;
     border: string;
   };
-  t: (key: string, defaultValue?: string) => string;
+  t: TranslateFunction;
 }
 
 const PrivacySettings: React.FC<PrivacySettingsProps> = ({
@@ -45,7 +46,7 @@ const PrivacySettings: React.FC<PrivacySettingsProps> = ({
             {t('settings.privacy.discord_rpc.description', 'Manages the Discord Rich Presence integration. Disabling this will cause \'Modrinth\' to no longer show up as a game or app you are using on your Discord profile.')}
           </p>
           <p className="text-gray-500 text-sm mt-2 italic">
-            {t('settings.privacy.discord_rpc.note', 'Note: This will not prevent any instance-specific Discord Rich Presence integrations, such as those added by mods. (app restart required to take effect)')}
+            {t('settings.privacy.discord_rpc.note', 'Note: This will not prevent any instance-specific Discord Rich Presence integrations, such as those added by mods.')}
           </p>
         </div>
         
@@ -2,6 +2,7 @@ import React, { useState, useEffect } from 'react';
 import SettingsSidebar from './SettingsSidebar';
 import AppearanceSettings from './AppearanceSettings';
 import PrivacySettings from './PrivacySettings';
+import { TranslateFunction } from '../types/interfaces';
 
 interface SettingsModalProps {
   themeClasses: {
@@ -22,8 +23,10 @@ interface SettingsModalProps {
   handleThemeChange: (theme: string) => void;
   handleTitlebarStyleChange: (style: string) => void;
   handleAdvancedRenderingToggle: () => void;
-  t: (key: string, defaultValue?: string) => string;
+  handleMinimizeToTrayToggle: () => void;
+  t: TranslateFunction;
   advancedRendering: boolean;
+  minimizeToTray: boolean;
 }
 
 const SettingsModal: React.FC<SettingsModalProps> = ({
@@ -38,8 +41,10 @@ const SettingsModal: React.FC<SettingsModalProps> = ({
   handleThemeChange,
   handleTitlebarStyleChange,
   handleAdvancedRenderingToggle,
+  handleMinimizeToTrayToggle,
   t,
-  advancedRendering = true // Default to true if not provided
+  advancedRendering = true, // Default to true if not provided
+  minimizeToTray
 }) => {
   // Animation state
   const [isVisible, setIsVisible] = useState(false);
@@ -153,6 +158,8 @@ const SettingsModal: React.FC<SettingsModalProps> = ({
                   handleTitlebarStyleChange={handleTitlebarStyleChange}
                   handleAdvancedRenderingToggle={handleAdvancedRenderingToggle}
                   advancedRendering={advancedRendering}
+                  handleMinimizeToTrayToggle={handleMinimizeToTrayToggle}
+                  minimizeToTray={minimizeToTray}
                   t={t}
                 />
               )}
@@ -1,5 +1,6 @@
 import React, { useEffect, useState } from 'react';
 import { useRotatingThemeIcon } from '../utils/themeUtils';
+import { TranslateFunction } from '../types/interfaces';
 
 interface SettingsSidebarProps {
   themeClasses: {
@@ -13,7 +14,7 @@ interface SettingsSidebarProps {
   activeSettingsTab: string;
   setActiveSettingsTab: (tab: string) => void;
   systemInfo: { os: string; version: string };
-  t: (key: string, defaultValue?: string) => string;
+  t: TranslateFunction;
   selectedTheme: string;
 }
 
@@ -1,5 +1,5 @@
 import React, { useState } from 'react';
-import { ThemeClasses } from '../types/interfaces';
+import { ThemeClasses, TranslateFunction } from '../types/interfaces';
 
 interface SidebarProps {
   themeClasses: ThemeClasses;
@@ -7,7 +7,7 @@ interface SidebarProps {
   overlapAmount: number;
   bottomPadding: number;
   onSettingsClick: () => void;
-  t: (key: string, defaultValue?: string) => string;
+  t: TranslateFunction;
   showTooltip: (text: string, position: { top: number; left?: number }) => void;
   hideTooltip: () => void;
 }
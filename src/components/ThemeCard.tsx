@@ -1,9 +1,12 @@
 import React from 'react';
+import { TranslateFunction } from '../types/interfaces';
+import { TranslationKey } from '../types/translationKeys';
 
 interface ThemeCardProps {
   theme: string;
   selectedTheme: string;
   title: string;
+  titleKey: TranslationKey;
   themeClasses: {
     background: string;
     secondaryBackground: string;
@@ -12,7 +15,7 @@ interface ThemeCardProps {
     secondaryText: string;
     border: string;
   };
-  t: (key: string, defaultValue?: string) => string;
+  t: TranslateFunction;
   onClick: () => void;
   icon?: React.ReactNode;
   previewContent: React.ReactNode;
@@ -22,6 +25,7 @@ const ThemeCard: React.FC<ThemeCardProps> = ({
   theme, 
   selectedTheme, 
   title,
+  titleKey,
   themeClasses,
   t,
   onClick,
@@ -50,7 +54,7 @@ const ThemeCard: React.FC<ThemeCardProps> = ({
           )}
         </div>
         <span className={`mr-1 ${selectedTheme === theme ? 'text-[#ffcc40]' : ''}`}>
-          {t(`settings.appearance.theme.${theme.toLowerCase()}`, title)}
+          {t(titleKey, title)}
         </span>
         {icon && icon}
       </div>
@@ -1,10 +1,11 @@
 import { Window } from '@tauri-apps/api/window';
 import { useRotatingThemeIcon } from '../utils/themeUtils';
 import { useEffect, useState } from 'react';
+import { TranslateFunction } from '../types/interfaces';
 
 interface TitlebarProps {
   selectedTitlebarStyle: string;
-  t: (key: string, defaultValue?: string) => string;
+  t: TranslateFunction;
   themeClasses: {
     background: string;
     secondaryBackground: string;
@@ -1,5 +1,10 @@
 // Application-wide interfaces
 
+import { TranslationKey } from './translationKeys';
+
+// Looks up a translation, falling back to the default and then the key
+export type TranslateFunction = (key: TranslationKey, defaultValue?: string) => string;
+
 export interface ThemeClasses {
   background: string;
   secondaryBackground: string;
@@ -15,15 +20,218 @@ export interface Settings {
   advanced_rendering: boolean;
   language: string;
   titlebar_style: string;
+  data_directory: string | null;
+  language_repository_url: string | null;
+  username: string;
+  minimize_to_tray: boolean;
+  backups: BackupSettings;
+}
+
+export interface BackupSettings {
+  interval_minutes: number | null;
+  on_exit: boolean;
+  keep_last: number;
+  keep_daily: number;
+  keep_weekly: number;
+}
+
+export interface SettingsChanged {
+  settings: Settings;
+  changed: string[];
+  restart_required: string[];
+}
+
+export interface SettingsRecovery {
+  source: 'backup' | 'defaults' | 'fields';
+  error: string;
+  // Dotted names of fields reset to their defaults when source is 'fields'
+  reset_fields: string[];
 }
 
 export interface LanguageMetadata {
   id: string;
   version: string;
   author: string;
+  native_name: string | null;
+  english_name: string | null;
+  direction: 'ltr' | 'rtl';
+  font_family: string | null;
+  format_locale: string;
+}
+
+export interface AvailableLanguage extends LanguageMetadata {
+  completeness: number;
+  status: 'valid' | 'warnings' | 'invalid';
 }
 
 export interface Language {
   metadata: LanguageMetadata;
   [key: string]: string | LanguageMetadata;
-} 
\ No newline at end of file
+} 
+
+export interface Instance {
+  id: string;
+  name: string;
+  game_version: string;
+  loader: string | null;
+  last_played: number | null;
+  java_path: string | null;
+  jvm: JvmSettings;
+}
+
+export interface JvmSettings {
+  min_memory: number | null;
+  max_memory: number | null;
+  jvm_args: string[];
+  env: Record<string, string>;
+  wrapper: string | null;
+  pre_launch: string | null;
+  post_exit: string | null;
+}
+
+export type JvmPreset = 'default' | 'aikar' | 'low_memory';
+
+export interface JvmPresetSettings {
+  preset: JvmPreset;
+  min_memory: number | null;
+  max_memory: number | null;
+  jvm_args: string[];
+}
+
+export interface SystemInfo {
+  os: string;
+  version: string;
+  arch: string;
+  cpu_model: string | null;
+  cpu_cores: number;
+  physical_cores: number | null;
+  total_memory: number;
+  available_memory: number;
+  data_free_space: number | null;
+}
+
+export interface RunningGame {
+  pid: number;
+  instance_id: string;
+  instance_name: string;
+  game_version: string;
+  loader: string | null;
+  server: string | null;
+  started_at: number;
+}
+
+export type CrashCause =
+  | 'wrong_java'
+  | 'out_of_memory'
+  | 'missing_dependency'
+  | 'mixin_failure'
+  | 'duplicate_mods';
+
+export interface CrashSummary {
+  kind: 'crash_report' | 'jvm_fatal_error';
+  path: string;
+  time: string | null;
+  description: string | null;
+  exception: string | null;
+  stack_trace: string[];
+  suspected_mods: string[];
+  jvm: {
+    java_version: string | null;
+    vm: string | null;
+    flags: string | null;
+    memory: string | null;
+  };
+  causes: { cause: CrashCause; line: string }[];
+}
+
+export interface GameExited {
+  instance_id: string;
+  exit_code: number | null;
+  crash: CrashSummary | null;
+}
+
+export interface LogSession {
+  id: string;
+  instance_id: string;
+  started_at: number;
+  size: number;
+  active: boolean;
+}
+
+export interface LogMatch {
+  instance_id: string;
+  session_id: string;
+  line_number: number;
+  line: string;
+}
+
+export interface World {
+  folder: string;
+  name: string;
+  game_mode: 'survival' | 'creative' | 'adventure' | 'spectator' | null;
+  hardcore: boolean;
+  difficulty: 'peaceful' | 'easy' | 'normal' | 'hard' | null;
+  seed: number | null;
+  last_played: number | null;
+  version: string | null;
+  data_version: number | null;
+  size: number;
+  icon: string | null;
+}
+
+export interface CopiedWorld {
+  world: World;
+  warning: string | null;
+}
+
+export type BackupReason = 'manual' | 'scheduled' | 'on_exit' | 'pre_restore';
+
+export interface BackupInfo {
+  id: string;
+  instance_id: string;
+  world: string;
+  world_name: string;
+  created_at: number;
+  reason: BackupReason;
+  file_count: number;
+  size: number;
+  added_size: number;
+}
+
+export interface BackupProgress {
+  instance_id: string;
+  world: string;
+  stage: 'backup' | 'restore';
+  done: number;
+  total: number;
+}
+
+export type PackCompatibility = 'compatible' | 'too_old' | 'too_new' | 'unknown';
+
+export interface ResourcePack {
+  id: string;
+  file: string;
+  description: string | null;
+  pack_format: number | null;
+  supported_formats: [number, number] | null;
+  compatibility: PackCompatibility;
+  icon: string | null;
+  enabled: boolean;
+  size: number;
+}
+
+export interface ShaderPack {
+  file: string;
+  size: number;
+  enabled: boolean;
+}
+
+export type DeepLink =
+  | { action: 'launch'; instance: string }
+  | { action: 'join_server'; address: string; instance: string | null }
+  | { action: 'install_modpack'; url: string };
+
+export interface DeepLinkFailed {
+  link: string;
+  message: string;
+}
\ No newline at end of file
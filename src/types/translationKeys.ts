@@ -0,0 +1,60 @@
+// Generated by scripts/generate-translation-keys.mjs from public/languages/en_US.json, do not edit
+
+export const TRANSLATION_KEYS = [
+  'app.status.no_instances',
+  'app.title',
+  'crash.cause.duplicate_mods',
+  'crash.cause.missing_dependency',
+  'crash.cause.mixin_failure',
+  'crash.cause.out_of_memory',
+  'crash.cause.wrong_java',
+  'crash.title',
+  'deep_link.confirm_join',
+  'deep_link.confirm_launch',
+  'deep_link.failed',
+  'discord.idle',
+  'discord.playing',
+  'discord.server',
+  'discord.version',
+  'discord.version_loader',
+  'settings.appearance.advanced_rendering',
+  'settings.appearance.advanced_rendering_desc',
+  'settings.appearance.advanced_settings',
+  'settings.appearance.advanced_settings.description',
+  'settings.appearance.color_theme',
+  'settings.appearance.color_theme.description',
+  'settings.appearance.minimize_to_tray',
+  'settings.appearance.minimize_to_tray_desc',
+  'settings.appearance.theme.dark',
+  'settings.appearance.theme.light',
+  'settings.appearance.theme.oled',
+  'settings.appearance.theme.sync',
+  'settings.appearance.titlebar.custom',
+  'settings.appearance.titlebar.macos',
+  'settings.appearance.titlebar.native',
+  'settings.appearance.titlebar.note',
+  'settings.appearance.titlebar.restart_required',
+  'settings.appearance.titlebar_style',
+  'settings.appearance.titlebar_style.description',
+  'settings.appearance.toggle_advanced_rendering',
+  'settings.appearance.toggle_minimize_to_tray',
+  'settings.privacy.discord_rpc',
+  'settings.privacy.discord_rpc.description',
+  'settings.privacy.discord_rpc.note',
+  'settings.tab.appearance',
+  'settings.tab.privacy',
+  'settings.title',
+  'tooltip.settings',
+  'tray.instances',
+  'tray.no_instances',
+  'tray.quick_launch',
+  'tray.quit',
+  'tray.running',
+  'tray.show',
+  'tray.stop',
+] as const;
+
+export type TranslationKey = typeof TRANSLATION_KEYS[number];
+
+export const isTranslationKey = (key: string): key is TranslationKey =>
+  (TRANSLATION_KEYS as readonly string[]).includes(key);